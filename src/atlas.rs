@@ -0,0 +1,156 @@
+use eframe::egui::{self, pos2, vec2, Color32, ColorImage, Rect, TextureHandle, TextureOptions};
+use std::sync::Arc;
+
+/// most cells a page is split into each way
+const PAGE_COLUMNS: usize = 4;
+const PAGE_ROWS: usize = 4;
+
+/// A frame's texture: either one of its own, or a cell of an atlas page
+/// shared with other frames of the same size.
+#[derive(Clone)]
+pub struct Sprite {
+    pub texture: Arc<TextureHandle>,
+    /// the frame's size in pixels
+    pub size: egui::Vec2,
+    // the frame's part of `texture`, in texture coordinates
+    uv: Rect,
+    // top left of the frame's padded cell on the page, `None` for a texture
+    // of its own
+    cell: Option<[usize; 2]>,
+}
+
+impl Sprite {
+    /// a frame with `texture` all to itself
+    pub fn single(texture: TextureHandle) -> Self {
+        Self {
+            size: texture.size_vec2(),
+            texture: Arc::new(texture),
+            uv: Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)),
+            cell: None,
+        }
+    }
+
+    /// `uv` across the frame, mapped into the frame's part of the texture
+    pub fn map(&self, uv: Rect) -> Rect {
+        Rect::from_min_max(
+            self.uv.lerp_inside(uv.min.to_vec2()),
+            self.uv.lerp_inside(uv.max.to_vec2()),
+        )
+    }
+
+    /// Upload `image`, the same size as the frame, in its place.
+    pub fn write(&self, image: ColorImage, options: TextureOptions) {
+        // every handle to a texture can update it, so a page needn't be
+        // borrowed mutably by one frame while others share it
+        let mut texture = TextureHandle::clone(&self.texture);
+        match self.cell {
+            Some(cell) => texture.set_partial(cell, extrude(&image), options),
+            None => texture.set(image, options),
+        }
+    }
+}
+
+/// Textures that frames are packed into as a grid of same-sized cells, so a
+/// GIF with hundreds of frames takes a handful of textures rather than one
+/// per frame. Pages are refilled by the next input once their frames are gone.
+#[derive(Default)]
+pub struct Atlas {
+    pages: Vec<Page>,
+}
+
+struct Page {
+    texture: Arc<TextureHandle>,
+    // size of each cell, the frame plus a pixel of padding all round
+    cell: [usize; 2],
+    columns: usize,
+    rows: usize,
+    used: usize,
+}
+
+impl Atlas {
+    /// Upload `image` into the next free cell of a page for frames its size,
+    /// starting a new page if they're all full. Frames too big to share a
+    /// page get a texture of their own.
+    pub fn insert(
+        &mut self,
+        ctx: &egui::Context,
+        image: ColorImage,
+        options: TextureOptions,
+    ) -> Sprite {
+        let cell = [image.width() + 2, image.height() + 2];
+        let max_side = ctx.input(|i| i.max_texture_side);
+        let columns = (max_side / cell[0]).min(PAGE_COLUMNS);
+        let rows = (max_side / cell[1]).min(PAGE_ROWS);
+        if columns * rows < 2 || image.pixels.is_empty() {
+            return Sprite::single(ctx.load_texture("frame", image, options));
+        }
+
+        let free = self
+            .pages
+            .iter()
+            .position(|page| page.cell == cell && page.used < page.columns * page.rows);
+        let idx = free.unwrap_or_else(|| {
+            let size = [cell[0] * columns, cell[1] * rows];
+            let texture = ctx.load_texture(
+                format!("frame_atlas_{}", self.pages.len()),
+                ColorImage::new(size, Color32::TRANSPARENT),
+                options,
+            );
+            self.pages.push(Page {
+                texture: Arc::new(texture),
+                cell,
+                columns,
+                rows,
+                used: 0,
+            });
+            self.pages.len() - 1
+        });
+        let page = &mut self.pages[idx];
+        let origin = [
+            page.used % page.columns * cell[0],
+            page.used / page.columns * cell[1],
+        ];
+        page.used += 1;
+
+        let page_size = vec2(
+            (cell[0] * page.columns) as f32,
+            (cell[1] * page.rows) as f32,
+        );
+        let size = vec2(image.width() as f32, image.height() as f32);
+        let min = pos2(origin[0] as f32 + 1.0, origin[1] as f32 + 1.0);
+        let sprite = Sprite {
+            texture: page.texture.clone(),
+            uv: Rect::from_min_size((min.to_vec2() / page_size).to_pos2(), size / page_size),
+            size,
+            cell: Some(origin),
+        };
+        sprite.write(image, options);
+        sprite
+    }
+
+    /// Free every page up for the next input once the current one's frames
+    /// are gone. Pages something still shows (a crossfade) are let go, and so
+    /// are ones the last input didn't use, so odd sizes don't pile up.
+    pub fn recycle(&mut self) {
+        self.pages.retain_mut(|page| {
+            let reusable = page.used > 0 && Arc::strong_count(&page.texture) == 1;
+            page.used = 0;
+            reusable
+        });
+    }
+}
+
+/// `image` with its edge pixels repeated a pixel further out all round, so
+/// linear filtering along its border doesn't pick up the next cell over
+fn extrude(image: &ColorImage) -> ColorImage {
+    let [width, height] = image.size;
+    let mut padded = ColorImage::new([width + 2, height + 2], Color32::TRANSPARENT);
+    for y in 0..height + 2 {
+        let source_y = y.saturating_sub(1).min(height - 1);
+        for x in 0..width + 2 {
+            let source_x = x.saturating_sub(1).min(width - 1);
+            padded.pixels[y * (width + 2) + x] = image.pixels[source_y * width + source_x];
+        }
+    }
+    padded
+}
@@ -0,0 +1,189 @@
+use clap::ValueEnum;
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    Sample, SizedSample,
+};
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+use tracing::{info, warn};
+
+/// how quickly the level rises with a louder sound, and falls back after it,
+/// in seconds
+const ATTACK_SECS: f32 = 0.03;
+const RELEASE_SECS: f32 = 0.25;
+
+/// What `--audio-react` moves with the sound level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Target {
+    /// bounce bigger with louder sound
+    Scale,
+    /// fade back while it's quiet
+    Opacity,
+}
+
+/// Loudness of the system audio, smoothed for drawing, and how it bends the
+/// overlay's scale or opacity.
+pub struct AudioReactive {
+    // capture stops when this is dropped
+    _stream: cpal::Stream,
+    // RMS of the latest buffer, as f32 bits, written by the audio thread
+    raw: Arc<AtomicU32>,
+    target: Target,
+    amount: f32,
+    gain: f32,
+    level: f32,
+    last_step: Option<Instant>,
+}
+
+impl AudioReactive {
+    /// Start capturing from the input device called `device`, or what's
+    /// playing on the default output where that can be recorded (Windows),
+    /// or the default input otherwise.
+    pub fn start(
+        device: Option<&str>,
+        target: Target,
+        amount: f32,
+        gain: f32,
+    ) -> Result<Self, String> {
+        let (device, config) = open_device(device)?;
+        info!(
+            "Capturing audio from {}",
+            device
+                .name()
+                .unwrap_or_else(|_| "unknown device".to_string())
+        );
+
+        let raw = Arc::new(AtomicU32::new(0));
+        let stream_config = config.config();
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::F32 => build_stream::<f32>(&device, &stream_config, raw.clone()),
+            cpal::SampleFormat::I16 => build_stream::<i16>(&device, &stream_config, raw.clone()),
+            cpal::SampleFormat::U16 => build_stream::<u16>(&device, &stream_config, raw.clone()),
+            other => return Err(format!("unsupported sample format {:?}", other)),
+        }
+        .map_err(|err| err.to_string())?;
+        stream.play().map_err(|err| err.to_string())?;
+
+        Ok(Self {
+            _stream: stream,
+            raw,
+            target,
+            amount: amount.clamp(0.0, 1.0),
+            gain,
+            level: 0.0,
+            last_step: None,
+        })
+    }
+
+    /// follow the latest loudness, quickly up and slowly back down
+    pub fn step(&mut self) {
+        let raw = f32::from_bits(self.raw.load(Ordering::Relaxed));
+        let target = (raw * self.gain).clamp(0.0, 1.0);
+        let now = Instant::now();
+        let secs = self
+            .last_step
+            .replace(now)
+            .map_or(0.0, |last| (now - last).as_secs_f32());
+
+        let time_constant = if target > self.level {
+            ATTACK_SECS
+        } else {
+            RELEASE_SECS
+        };
+        self.level += (target - self.level) * (1.0 - (-secs / time_constant).exp());
+    }
+
+    /// how much bigger than usual to draw the frame right now
+    pub fn scale(&self) -> f32 {
+        match self.target {
+            Target::Scale => 1.0 + self.amount * self.level,
+            Target::Opacity => 1.0,
+        }
+    }
+
+    /// the most `scale` can get, to leave room for it
+    pub fn max_scale(&self) -> f32 {
+        match self.target {
+            Target::Scale => 1.0 + self.amount,
+            Target::Opacity => 1.0,
+        }
+    }
+
+    /// what to multiply the opacity by right now
+    pub fn opacity(&self) -> f32 {
+        match self.target {
+            Target::Scale => 1.0,
+            Target::Opacity => 1.0 - self.amount * (1.0 - self.level),
+        }
+    }
+}
+
+fn open_device(name: Option<&str>) -> Result<(cpal::Device, cpal::SupportedStreamConfig), String> {
+    let host = cpal::default_host();
+    if let Some(name) = name {
+        let mut devices = host.input_devices().map_err(|err| err.to_string())?;
+        let device = devices
+            .find(|device| device.name().is_ok_and(|device_name| device_name == name))
+            .ok_or_else(|| {
+                let names: Vec<String> = host
+                    .input_devices()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|device| device.name().ok())
+                    .collect();
+                format!(
+                    "no audio input named {:?}, available: {}",
+                    name,
+                    names.join(", ")
+                )
+            })?;
+        let config = device
+            .default_input_config()
+            .map_err(|err| err.to_string())?;
+        return Ok((device, config));
+    }
+
+    // WASAPI records an output device's own mix when asked for input from it
+    if cfg!(windows) {
+        if let Some(device) = host.default_output_device() {
+            let config = device
+                .default_output_config()
+                .map_err(|err| err.to_string())?;
+            return Ok((device, config));
+        }
+    }
+    let device = host.default_input_device().ok_or("no audio input device")?;
+    let config = device
+        .default_input_config()
+        .map_err(|err| err.to_string())?;
+    Ok((device, config))
+}
+
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    raw: Arc<AtomicU32>,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample,
+    f32: cpal::FromSample<T>,
+{
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            let sum: f32 = data
+                .iter()
+                .map(|&sample| sample.to_sample::<f32>().powi(2))
+                .sum();
+            let rms = (sum / data.len().max(1) as f32).sqrt();
+            raw.store(rms.to_bits(), Ordering::Relaxed);
+        },
+        |err| warn!("audio capture error: {}", err),
+        None,
+    )
+}
@@ -0,0 +1,244 @@
+use crate::{
+    atlas, filters, info, spawn_loader, validate, InputFormat, LoaderOptions, LoadingMessage,
+};
+use eframe::egui;
+use serde::Serialize;
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use tracing::error;
+
+/// How `overlay_app bench --upload` turns frames into textures, the way the
+/// overlay would.
+pub struct Upload {
+    pub filters: filters::ColorFilters,
+    pub texture_options: egui::TextureOptions,
+}
+
+/// One decode of an input, start to finish.
+struct Run {
+    first_frame: Duration,
+    total: Duration,
+    // how long each frame took to arrive after the one before
+    frame_times: Vec<Duration>,
+    width: usize,
+    height: usize,
+    decoded_bytes: usize,
+    // only kept for `--upload`, since a long video won't fit in memory
+    frames: Vec<(Vec<u8>, [usize; 2])>,
+}
+
+/// What `overlay_app bench` measured for an input, from its median run.
+#[derive(Debug, Serialize)]
+struct Report {
+    input: PathBuf,
+    runs: usize,
+    frames: usize,
+    width: usize,
+    height: usize,
+    first_frame_ms: f64,
+    decode_ms: f64,
+    frame_mean_ms: f64,
+    frame_median_ms: f64,
+    frame_p95_ms: f64,
+    frame_max_ms: f64,
+    frames_per_sec: f64,
+    decoded_mb_per_sec: f64,
+    /// RGBA bytes the decoded frames take up in memory
+    decoded_bytes: usize,
+    /// `None` without `--upload`
+    upload_mean_ms: Option<f64>,
+    upload_total_ms: Option<f64>,
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Decode `path` once, timing each frame as it comes out of the loader.
+fn decode(
+    path: &Path,
+    format: Option<InputFormat>,
+    options: LoaderOptions,
+    keep_frames: bool,
+) -> Result<Run, String> {
+    let started = Instant::now();
+    let messages = if format == Some(InputFormat::Video) {
+        info::decode_video(path, options)
+    } else {
+        let (receiver, _) = spawn_loader(&egui::Context::default(), path.to_path_buf(), options);
+        receiver
+    };
+
+    let mut run = Run {
+        first_frame: Duration::ZERO,
+        total: Duration::ZERO,
+        frame_times: Vec::new(),
+        width: 0,
+        height: 0,
+        decoded_bytes: 0,
+        frames: Vec::new(),
+    };
+    let mut last = started;
+    for message in messages {
+        match message {
+            LoadingMessage::FrameReady(_, pixels, size, _)
+            | LoadingMessage::StreamFrame(_, pixels, size, _) => {
+                let now = Instant::now();
+                if run.frame_times.is_empty() {
+                    run.first_frame = now - started;
+                }
+                run.frame_times.push(now - last);
+                last = now;
+                run.width = run.width.max(size[0]);
+                run.height = run.height.max(size[1]);
+                run.decoded_bytes += pixels.len();
+                if keep_frames {
+                    run.frames.push((pixels, size));
+                }
+            }
+            LoadingMessage::LoadingComplete(_) | LoadingMessage::RenderScale(_) => {}
+            LoadingMessage::Error(err) => return Err(format!("{}: {}", path.display(), err)),
+        }
+    }
+    run.total = started.elapsed();
+
+    if run.frame_times.is_empty() {
+        return Err(format!("{}: no frames decoded", path.display()));
+    }
+    Ok(run)
+}
+
+/// Time turning each of `frames` into a texture: the color effects, packing
+/// into atlas pages, and egui's share of the upload. There's no GPU behind
+/// the context, so the transfer itself isn't counted.
+fn upload(frames: &[(Vec<u8>, [usize; 2])], upload: &Upload) -> Vec<Duration> {
+    let ctx = egui::Context::default();
+    let mut atlas = atlas::Atlas::default();
+    let mut times = Vec::with_capacity(frames.len());
+    for (pixels, size) in frames {
+        let started = Instant::now();
+        let image = upload.filters.image(pixels, *size);
+        atlas.insert(&ctx, image, upload.texture_options);
+        times.push(started.elapsed());
+        // hand over the queued texture data as a UI update would, so it
+        // doesn't pile up
+        let _ = ctx.run(egui::RawInput::default(), |_| {});
+    }
+    times
+}
+
+/// Decode `path` `runs` times and report on the median run.
+fn measure(
+    path: &Path,
+    options: LoaderOptions,
+    runs: usize,
+    upload_with: Option<&Upload>,
+) -> Result<Report, String> {
+    // what's measured is decoding, not reading frames back or playing them
+    let options = LoaderOptions {
+        stream_frames: None,
+        cache: false,
+        ..options
+    };
+    let format = validate::input(path, options.sprite_sheet.is_some())?;
+
+    let mut results = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        results.push(decode(path, format, options, upload_with.is_some())?);
+    }
+    results.sort_by_key(|run| run.total);
+    let run = results.swap_remove(runs / 2);
+
+    let mut frame_times = run.frame_times.clone();
+    frame_times.sort();
+    let percentile = |p: f64| {
+        let idx = ((frame_times.len() - 1) as f64 * p).round() as usize;
+        millis(frame_times[idx])
+    };
+    let frames = run.frame_times.len();
+    let seconds = run.total.as_secs_f64().max(f64::EPSILON);
+    let upload_total =
+        upload_with.map(|upload_with| upload(&run.frames, upload_with).iter().sum::<Duration>());
+
+    Ok(Report {
+        input: path.to_path_buf(),
+        runs,
+        frames,
+        width: run.width,
+        height: run.height,
+        first_frame_ms: millis(run.first_frame),
+        decode_ms: millis(run.total),
+        frame_mean_ms: millis(run.frame_times.iter().sum::<Duration>()) / frames as f64,
+        frame_median_ms: percentile(0.5),
+        frame_p95_ms: percentile(0.95),
+        frame_max_ms: percentile(1.0),
+        frames_per_sec: frames as f64 / seconds,
+        decoded_mb_per_sec: run.decoded_bytes as f64 / (1024.0 * 1024.0) / seconds,
+        decoded_bytes: run.decoded_bytes,
+        upload_mean_ms: upload_total.map(|total| millis(total) / frames as f64),
+        upload_total_ms: upload_total.map(millis),
+    })
+}
+
+impl Report {
+    fn print(&self, json: bool) {
+        if json {
+            match serde_json::to_string(self) {
+                Ok(json) => println!("{}", json),
+                Err(err) => error!("failed to write report: {}", err),
+            }
+            return;
+        }
+
+        println!("{}", self.input.display());
+        println!(
+            "  frames:     {} at {}x{}",
+            self.frames, self.width, self.height
+        );
+        println!(
+            "  decode:     {:.1}ms, first frame after {:.1}ms (median of {} runs)",
+            self.decode_ms, self.first_frame_ms, self.runs
+        );
+        println!(
+            "  per frame:  {:.2}ms mean, {:.2}ms median, {:.2}ms p95, {:.2}ms max",
+            self.frame_mean_ms, self.frame_median_ms, self.frame_p95_ms, self.frame_max_ms
+        );
+        println!(
+            "  throughput: {:.1} frames/s, {:.1} MB/s decoded",
+            self.frames_per_sec, self.decoded_mb_per_sec
+        );
+        if let (Some(mean), Some(total)) = (self.upload_mean_ms, self.upload_total_ms) {
+            println!(
+                "  upload:     {:.2}ms mean per frame, {:.1}ms total",
+                mean, total
+            );
+        }
+        println!(
+            "  memory:     {:.1} MB decoded",
+            self.decoded_bytes as f64 / (1024.0 * 1024.0)
+        );
+    }
+}
+
+/// Time decoding each of `inputs`, returning whether they all decoded.
+pub fn run(
+    inputs: &[PathBuf],
+    options: LoaderOptions,
+    runs: usize,
+    upload: Option<&Upload>,
+    json: bool,
+) -> bool {
+    let mut all_ok = true;
+    for path in inputs {
+        match measure(path, options, runs, upload) {
+            Ok(report) => report.print(json),
+            Err(err) => {
+                error!("{}", err);
+                all_ok = false;
+            }
+        }
+    }
+    all_ok
+}
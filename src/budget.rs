@@ -0,0 +1,101 @@
+use crate::InputFormat;
+use gif::DecodeOptions;
+use image::{
+    imageops::{self, FilterType},
+    ImageBuffer, Rgba,
+};
+use std::io::Cursor;
+
+/// How much to shrink `frames` frames of `size` pixels by so their decoded
+/// RGBA fits in `budget` bytes, or `None` if it already does.
+pub fn shrink_factor(budget: usize, size: [usize; 2], frames: usize) -> Option<f32> {
+    let needed = size[0] as f64 * size[1] as f64 * 4.0 * frames as f64;
+    (needed > budget as f64).then(|| (budget as f64 / needed).sqrt() as f32)
+}
+
+/// `size` shrunk by `factor`, at least a pixel each way
+pub fn shrunk_size(size: [usize; 2], factor: f32) -> [usize; 2] {
+    size.map(|side| ((side as f32 * factor).round() as usize).max(1))
+}
+
+/// Shrink a frame's RGBA `pixels` by `factor`.
+pub fn shrink_frame(pixels: Vec<u8>, size: [usize; 2], factor: f32) -> (Vec<u8>, [usize; 2]) {
+    let target = shrunk_size(size, factor);
+    let shrunk = ImageBuffer::<Rgba<u8>, &[u8]>::from_raw(size[0] as u32, size[1] as u32, &pixels)
+        .map(|image| {
+            imageops::resize(
+                &image,
+                target[0] as u32,
+                target[1] as u32,
+                FilterType::Triangle,
+            )
+        });
+    match shrunk {
+        Some(shrunk) => (shrunk.into_raw(), target),
+        None => (pixels, size),
+    }
+}
+
+/// Estimate the frame size and count of an encoded animation without
+/// decoding its pixels.
+pub fn estimate(format: InputFormat, data: &[u8]) -> Option<([usize; 2], usize)> {
+    let frames = match format {
+        InputFormat::Gif => count_gif_frames(data)?,
+        InputFormat::Png => count_png_frames(data)?,
+        InputFormat::WebP => count_webp_frames(data),
+        _ => 1,
+    };
+    let (width, height) = image::io::Reader::new(Cursor::new(data))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()?;
+    Some(([width as usize, height as usize], frames))
+}
+
+fn count_gif_frames(data: &[u8]) -> Option<usize> {
+    let mut options = DecodeOptions::new();
+    options.skip_frame_decoding(true);
+    let mut decoder = options.read_info(data).ok()?;
+    let mut frames = 0;
+    while let Ok(Some(_)) = decoder.next_frame_info() {
+        frames += 1;
+    }
+    Some(frames)
+}
+
+/// the frame count in an APNG's `acTL` chunk, or 1 for a plain PNG
+fn count_png_frames(data: &[u8]) -> Option<usize> {
+    let mut chunks = data.get(8..)?;
+    while chunks.len() >= 12 {
+        let len = u32::from_be_bytes(chunks[0..4].try_into().ok()?) as usize;
+        match &chunks[4..8] {
+            b"acTL" => {
+                let frames = chunks.get(8..12)?;
+                return Some(u32::from_be_bytes(frames.try_into().ok()?) as usize);
+            }
+            // `acTL` has to come before the image data
+            b"IDAT" => break,
+            _ => chunks = chunks.get(12 + len..)?,
+        }
+    }
+    Some(1)
+}
+
+/// the number of `ANMF` chunks in an animated WebP, or 1 for a still one
+fn count_webp_frames(data: &[u8]) -> usize {
+    let mut chunks = data.get(12..).unwrap_or_default();
+    let mut frames = 0;
+    while chunks.len() >= 8 {
+        if &chunks[0..4] == b"ANMF" {
+            frames += 1;
+        }
+        let len = u32::from_le_bytes([chunks[4], chunks[5], chunks[6], chunks[7]]) as usize;
+        // chunks are padded to an even length
+        let Some(rest) = chunks.get(8 + len + len % 2..) else {
+            break;
+        };
+        chunks = rest;
+    }
+    frames.max(1)
+}
@@ -0,0 +1,221 @@
+use crate::{deliver, LoaderOptions, LoadingMessage};
+use eframe::egui;
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use sha2::{Digest, Sha256};
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+    time::Duration,
+};
+use tracing::{debug, info, warn};
+
+/// bumped whenever the file layout, or what the decoders make of an input,
+/// changes, so older entries are never read back
+const VERSION: u8 = 1;
+
+/// size of the cache folder past which the oldest entries are removed
+const SIZE_LIMIT: u64 = 1024 * 1024 * 1024;
+
+/// where `--cache` keeps decoded frames, e.g. `~/.cache/overlay_app/frames`
+fn cache_dir() -> Option<PathBuf> {
+    Some(dirs::cache_dir()?.join("overlay_app").join("frames"))
+}
+
+/// The cache file for `input` decoded with `options`, keyed by a hash of the
+/// file's contents, the options, and the screen that decides what size
+/// frames come out at.
+pub fn entry(input: &Path, options: &LoaderOptions, ctx: &egui::Context) -> Option<PathBuf> {
+    let dir = cache_dir()?;
+    let mut hasher = Sha256::new();
+    let hashed = File::open(input).and_then(|mut file| io::copy(&mut file, &mut hasher));
+    if let Err(err) = hashed {
+        debug!("failed to hash {} for the cache: {}", input.display(), err);
+        return None;
+    }
+    let screen = ctx.input(|i| i.viewport().monitor_size);
+    hasher.update(format!(
+        "{}|{:?}|{}|{:?}",
+        VERSION,
+        options,
+        ctx.pixels_per_point(),
+        screen
+    ));
+    let key: String = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    Some(dir.join(format!("{}.frames", key)))
+}
+
+/// Send the frames cached at `entry` to the UI, returning whether there were
+/// any. A damaged entry is removed and left for decoding to fill in again.
+pub fn replay(entry: &Path, sender: &Sender<LoadingMessage>) -> Result<bool, String> {
+    let Ok(file) = File::open(entry) else {
+        return Ok(false);
+    };
+    info!("Loading frames from the cache");
+    match read_entry(DeflateDecoder::new(BufReader::new(file)), sender) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {
+            Err("the overlay stopped listening".to_string())
+        }
+        Err(err) => {
+            warn!("failed to read cached frames, decoding again: {}", err);
+            let _ = fs::remove_file(entry);
+            Ok(false)
+        }
+    }
+}
+
+fn read_entry(mut reader: impl Read, sender: &Sender<LoadingMessage>) -> io::Result<()> {
+    let send = |message| {
+        deliver(sender, message).map_err(|err| io::Error::new(io::ErrorKind::BrokenPipe, err))
+    };
+    let mut frames = 0;
+    loop {
+        let mut tag = [0];
+        reader.read_exact(&mut tag)?;
+        match &tag {
+            b"S" => send(LoadingMessage::RenderScale(f32::from_le_bytes(read_array(
+                &mut reader,
+            )?)))?,
+            b"F" => {
+                let width = u32::from_le_bytes(read_array(&mut reader)?) as usize;
+                let height = u32::from_le_bytes(read_array(&mut reader)?) as usize;
+                let delay = Duration::from_micros(u64::from_le_bytes(read_array(&mut reader)?));
+                let mut pixels = vec![0; width * height * 4];
+                reader.read_exact(&mut pixels)?;
+                send(LoadingMessage::FrameReady(
+                    frames,
+                    pixels,
+                    [width, height],
+                    delay,
+                ))?;
+                frames += 1;
+            }
+            // only written once every frame made it in
+            b"E" => return send(LoadingMessage::LoadingComplete(frames)),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "unknown record")),
+        }
+    }
+}
+
+fn read_array<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Writes the frames of an input into its cache entry as they're sent,
+/// compressed. The entry only appears once the last frame is in, so a load
+/// that fails or is cut short leaves nothing behind.
+pub struct Recorder {
+    entry: PathBuf,
+    partial: PathBuf,
+    writer: Option<DeflateEncoder<BufWriter<File>>>,
+}
+
+impl Recorder {
+    pub fn create(entry: PathBuf) -> Option<Self> {
+        let partial = entry.with_extension("partial");
+        let file = entry
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| File::create(&partial));
+        match file {
+            Ok(file) => Some(Self {
+                entry,
+                partial,
+                writer: Some(DeflateEncoder::new(
+                    BufWriter::new(file),
+                    Compression::fast(),
+                )),
+            }),
+            Err(err) => {
+                warn!("failed to create cache entry: {}", err);
+                None
+            }
+        }
+    }
+
+    /// add `message` to the entry, if it's one that needs replaying
+    pub fn record(&mut self, message: &LoadingMessage) {
+        let Some(writer) = &mut self.writer else {
+            return;
+        };
+        let written = match message {
+            LoadingMessage::RenderScale(scale) => writer
+                .write_all(b"S")
+                .and_then(|()| writer.write_all(&scale.to_le_bytes())),
+            LoadingMessage::FrameReady(_, pixels, size, delay) => writer
+                .write_all(b"F")
+                .and_then(|()| writer.write_all(&(size[0] as u32).to_le_bytes()))
+                .and_then(|()| writer.write_all(&(size[1] as u32).to_le_bytes()))
+                .and_then(|()| writer.write_all(&(delay.as_micros() as u64).to_le_bytes()))
+                .and_then(|()| writer.write_all(pixels)),
+            _ => Ok(()),
+        };
+        if let Err(err) = written {
+            warn!(
+                "failed to write cache entry, not caching this input: {}",
+                err
+            );
+            self.writer = None;
+        }
+    }
+
+    /// mark the entry complete and make it available to the next run
+    pub fn save(mut self) {
+        let Some(mut writer) = self.writer.take() else {
+            return;
+        };
+        let saved = writer
+            .write_all(b"E")
+            .and_then(|()| writer.finish())
+            .and_then(|mut file| file.flush())
+            .and_then(|()| fs::rename(&self.partial, &self.entry));
+        match saved {
+            Ok(()) => {
+                debug!("Cached frames in {}", self.entry.display());
+                prune();
+            }
+            Err(err) => warn!("failed to save cache entry: {}", err),
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        // never renamed into place, so nothing else refers to it
+        let _ = fs::remove_file(&self.partial);
+    }
+}
+
+/// remove the oldest entries until the cache fits in `SIZE_LIMIT`
+fn prune() {
+    let Some(Ok(entries)) = cache_dir().map(fs::read_dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let metadata = entry.metadata().ok()?;
+            Some((metadata.modified().ok()?, metadata.len(), entry.path()))
+        })
+        .filter(|(_, _, path)| path.extension().is_some_and(|ext| ext == "frames"))
+        .collect();
+    let mut total: u64 = entries.iter().map(|(_, len, _)| len).sum();
+    entries.sort();
+    for (_, len, path) in entries {
+        if total <= SIZE_LIMIT {
+            break;
+        }
+        debug!("Removing old cache entry {}", path.display());
+        if fs::remove_file(&path).is_ok() {
+            total -= len;
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use chrono::format::{Item, StrftimeItems};
+use std::time::{Duration, Instant};
+
+/// Text shown under the animation: a countdown (`--timer`) or the time of
+/// day (`--clock`).
+pub enum Clock {
+    /// counts down to zero, then stays there
+    Timer { ends_at: Instant },
+    /// local time, in a strftime format
+    WallClock { format: String },
+}
+
+impl Clock {
+    pub fn timer(duration: Duration) -> Self {
+        Self::Timer {
+            ends_at: Instant::now() + duration,
+        }
+    }
+
+    pub fn wall_clock(format: String) -> Self {
+        Self::WallClock { format }
+    }
+
+    /// what to show right now
+    pub fn text(&self) -> String {
+        match self {
+            Self::Timer { ends_at } => {
+                // round up so `10:00` shows at the start and `00:00` only at the end
+                let remaining = ends_at.saturating_duration_since(Instant::now());
+                let secs = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+                let (hours, minutes, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+                if hours > 0 {
+                    format!("{}:{:02}:{:02}", hours, minutes, secs)
+                } else {
+                    format!("{:02}:{:02}", minutes, secs)
+                }
+            }
+            Self::WallClock { format } => chrono::Local::now().format(format).to_string(),
+        }
+    }
+
+    /// time until the text next changes, at most a second away
+    pub fn until_next_tick(&self) -> Duration {
+        let subsec = match self {
+            Self::Timer { ends_at } => ends_at
+                .saturating_duration_since(Instant::now())
+                .subsec_nanos(),
+            Self::WallClock { .. } => {
+                let now = chrono::Local::now().timestamp_subsec_nanos();
+                1_000_000_000 - now.min(999_999_999)
+            }
+        };
+        if subsec == 0 {
+            Duration::from_secs(1)
+        } else {
+            Duration::from_nanos(subsec.into())
+        }
+    }
+}
+
+/// parse a `--timer` length: `SS`, `MM:SS`, or `HH:MM:SS`
+pub fn parse_timer(s: &str) -> Result<Duration, String> {
+    let parts = s
+        .trim()
+        .split(':')
+        .map(|part| part.parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("expected MM:SS or HH:MM:SS, got {:?}", s))?;
+    if parts.is_empty() || parts.len() > 3 {
+        return Err(format!("expected MM:SS or HH:MM:SS, got {:?}", s));
+    }
+
+    let secs = parts.iter().fold(0, |total, part| total * 60 + part);
+    Ok(Duration::from_secs(secs))
+}
+
+/// check a `--clock-format` up front, since chrono panics on bad ones
+pub fn parse_format(s: &str) -> Result<String, String> {
+    if StrftimeItems::new(s).any(|item| matches!(item, Item::Error)) {
+        return Err(format!("invalid time format {:?}", s));
+    }
+    Ok(s.to_string())
+}
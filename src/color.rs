@@ -0,0 +1,28 @@
+use eframe::egui::Color32;
+
+/// parse a `#RRGGBB` or `#RRGGBBAA` color, the leading `#` being optional
+pub fn parse_color(s: &str) -> Result<Color32, String> {
+    let hex = s.trim().trim_start_matches('#');
+    if !matches!(hex.len(), 6 | 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("expected #RRGGBB or #RRGGBBAA, got {:?}", s));
+    }
+
+    let channel = |idx: usize| u8::from_str_radix(&hex[idx * 2..idx * 2 + 2], 16).unwrap_or(0);
+    let alpha = if hex.len() == 8 { channel(3) } else { 255 };
+    Ok(Color32::from_rgba_unmultiplied(
+        channel(0),
+        channel(1),
+        channel(2),
+        alpha,
+    ))
+}
+
+/// format a color the way `parse_color` reads it
+pub fn format_color(color: Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    if a == 255 {
+        format!("#{:02X}{:02X}{:02X}", r, g, b)
+    } else {
+        format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
+    }
+}
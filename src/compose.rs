@@ -0,0 +1,155 @@
+use gif::{streaming_decoder::FrameDecoder, ColorOutput, DecodeOptions, Decoder, DisposalMethod};
+use image::{
+    error::{DecodingError, ImageFormatHint},
+    Delay, Frame, Frames, ImageError, ImageFormat, ImageResult, Rgba, RgbaImage,
+};
+use rayon::prelude::*;
+use std::{collections::VecDeque, io::Read};
+
+/// GIF frame iterator that composites every frame onto a persistent canvas,
+/// honouring each frame's left/top offset and disposal method, so optimized
+/// GIFs made of partial frames come out as full images.
+///
+/// Frames are read from the file a batch at a time, one per thread in rayon's
+/// pool, and decompressed in parallel; only compositing happens in order.
+pub struct ComposedGifFrames<R: Read> {
+    decoder: Decoder<R>,
+    options: DecodeOptions,
+    global_palette: Vec<u8>,
+    canvas: RgbaImage,
+    // decompressed frames of the current batch, in file order
+    ready: VecDeque<ImageResult<gif::Frame<'static>>>,
+    // the last frame has been read from the file
+    exhausted: bool,
+}
+
+impl<R: Read> ComposedGifFrames<R> {
+    pub fn new(reader: R) -> ImageResult<Self> {
+        let mut options = DecodeOptions::new();
+        options.set_color_output(ColorOutput::RGBA);
+        // frames come out still compressed, to be decompressed in parallel
+        options.skip_frame_decoding(true);
+        let decoder = options.clone().read_info(reader).map_err(decoding_error)?;
+        let global_palette = decoder.global_palette().unwrap_or_default().to_vec();
+        let canvas = RgbaImage::new(decoder.width() as u32, decoder.height() as u32);
+
+        Ok(Self {
+            decoder,
+            options,
+            global_palette,
+            canvas,
+            ready: VecDeque::new(),
+            exhausted: false,
+        })
+    }
+
+    pub fn into_frames<'a>(self) -> Frames<'a>
+    where
+        R: 'a,
+    {
+        Frames::new(Box::new(self))
+    }
+
+    /// read the next batch of compressed frames and decompress them all at once
+    fn read_batch(&mut self) {
+        let mut batch = Vec::new();
+        let mut read_error = None;
+        while batch.len() < rayon::current_num_threads() {
+            match self.decoder.read_next_frame() {
+                Ok(Some(frame)) => batch.push(frame.clone()),
+                Ok(None) => {
+                    self.exhausted = true;
+                    break;
+                }
+                Err(err) => {
+                    read_error = Some(decoding_error(err));
+                    break;
+                }
+            }
+        }
+
+        let decompressed: Vec<_> = batch
+            .into_par_iter()
+            .map_init(
+                || {
+                    let mut decoder = FrameDecoder::new(self.options.clone());
+                    decoder.set_global_palette(self.global_palette.clone());
+                    decoder
+                },
+                |decoder, mut frame| {
+                    decoder
+                        .decode_lzw_encoded_frame(&mut frame)
+                        .map(|()| frame)
+                        .map_err(decoding_error)
+                },
+            )
+            .collect();
+        self.ready.extend(decompressed);
+        // the frames before a damaged one still play
+        self.ready.extend(read_error.map(Err));
+    }
+
+    fn compose_next(&mut self) -> ImageResult<Option<Frame>> {
+        if self.ready.is_empty() && !self.exhausted {
+            self.read_batch();
+        }
+        let Some(frame) = self.ready.pop_front().transpose()? else {
+            return Ok(None);
+        };
+
+        let left = frame.left as u32;
+        let top = frame.top as u32;
+        let width = frame.width as u32;
+        let height = frame.height as u32;
+
+        // "restore to previous" needs the canvas as it was before this frame
+        let previous = (frame.dispose == DisposalMethod::Previous).then(|| self.canvas.clone());
+
+        for (i, pixel) in frame.buffer.chunks_exact(4).enumerate() {
+            let x = left + i as u32 % width;
+            let y = top + i as u32 / width;
+            // transparent pixels let the existing canvas show through
+            if pixel[3] != 0 && x < self.canvas.width() && y < self.canvas.height() {
+                self.canvas
+                    .put_pixel(x, y, Rgba([pixel[0], pixel[1], pixel[2], pixel[3]]));
+            }
+        }
+
+        let composed = self.canvas.clone();
+        // gif delays are in units of 10ms
+        let delay = Delay::from_numer_denom_ms(frame.delay as u32 * 10, 1);
+
+        match frame.dispose {
+            DisposalMethod::Background => {
+                for y in top..(top + height).min(self.canvas.height()) {
+                    for x in left..(left + width).min(self.canvas.width()) {
+                        self.canvas.put_pixel(x, y, Rgba([0, 0, 0, 0]));
+                    }
+                }
+            }
+            DisposalMethod::Previous => {
+                if let Some(previous) = previous {
+                    self.canvas = previous;
+                }
+            }
+            DisposalMethod::Any | DisposalMethod::Keep => {}
+        }
+
+        Ok(Some(Frame::from_parts(composed, 0, 0, delay)))
+    }
+}
+
+impl<R: Read> Iterator for ComposedGifFrames<R> {
+    type Item = ImageResult<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.compose_next().transpose()
+    }
+}
+
+fn decoding_error(err: gif::DecodingError) -> ImageError {
+    ImageError::Decoding(DecodingError::new(
+        ImageFormatHint::Exact(ImageFormat::Gif),
+        err,
+    ))
+}
@@ -0,0 +1,228 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Sender},
+    time::Duration,
+};
+
+/// how long a status request waits for the UI thread to answer
+const STATUS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A request from outside the process (`--http-port`, `--ipc`,
+/// `--websocket-port`, `--mqtt-broker`, `--osc-port`, `--dbus`, `--twitch`,
+/// `--midi`, `--gamepad`)
+/// for the running overlay, handled on the UI thread.
+pub enum Command {
+    Show,
+    Hide,
+    Trigger,
+    Pause,
+    Resume,
+    /// jump to this frame of the looped range
+    Seek(usize),
+    /// replace whatever is playing with this input
+    Play(PathBuf),
+    /// play this once, with an optional caption under it, then go back to
+    /// whatever was playing
+    Alert(PathBuf, Option<String>),
+    Opacity(f32),
+    Scale(f32),
+    /// put the window's top left corner here, in desktop points
+    Move(egui::Pos2),
+    Quit,
+    Status(Sender<Status>),
+}
+
+/// What `Command::Status` answers with.
+#[derive(Debug, Serialize)]
+pub struct Status {
+    pub visible: bool,
+    pub paused: bool,
+    pub opacity: f32,
+    pub scale: f32,
+    pub input: PathBuf,
+    pub frame: usize,
+    pub frames: usize,
+}
+
+/// hand `command` to the UI thread and wake it up to carry it out
+pub fn send(
+    commands: &Sender<Command>,
+    ctx: &egui::Context,
+    command: Command,
+) -> Result<(), String> {
+    commands
+        .send(command)
+        .map_err(|_| "overlay is closing".to_string())?;
+    ctx.request_repaint();
+    Ok(())
+}
+
+/// ask the UI thread for the overlay's status
+pub fn status(commands: &Sender<Command>, ctx: &egui::Context) -> Result<Status, String> {
+    let (reply, status) = channel();
+    send(commands, ctx, Command::Status(reply))?;
+    status
+        .recv_timeout(STATUS_TIMEOUT)
+        .map_err(|_| "overlay didn't answer".to_string())
+}
+
+/// What a `--midi` note or `--gamepad` button does when it's pressed.
+#[derive(Debug, Clone)]
+pub enum ButtonAction {
+    Show,
+    Hide,
+    Trigger,
+    Pause,
+    Resume,
+    Quit,
+    /// play this once, then go back to whatever was playing
+    Alert(PathBuf),
+}
+
+impl ButtonAction {
+    /// `show`, `hide`, `trigger`, `pause`, `resume`, or `quit`, or else a file
+    /// or URL to play as an alert, with relative paths resolved against `base`
+    pub fn parse(value: &str, base: &Path) -> Self {
+        match value {
+            "show" => Self::Show,
+            "hide" => Self::Hide,
+            "trigger" => Self::Trigger,
+            "pause" => Self::Pause,
+            "resume" => Self::Resume,
+            "quit" => Self::Quit,
+            _ => {
+                let file = PathBuf::from(value);
+                if file.is_relative() && !crate::is_stream(&file) {
+                    Self::Alert(base.join(file))
+                } else {
+                    Self::Alert(file)
+                }
+            }
+        }
+    }
+
+    pub fn command(&self) -> Command {
+        match self {
+            Self::Show => Command::Show,
+            Self::Hide => Command::Hide,
+            Self::Trigger => Command::Trigger,
+            Self::Pause => Command::Pause,
+            Self::Resume => Command::Resume,
+            Self::Quit => Command::Quit,
+            Self::Alert(file) => Command::Alert(file.clone(), None),
+        }
+    }
+}
+
+/// A JSON command as sent by `--ipc` and `--websocket-port` clients, e.g.
+/// `{"cmd":"seek","frame":10}`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "kebab-case")]
+pub enum Request {
+    Show,
+    Hide,
+    Trigger,
+    Pause,
+    Resume,
+    Seek {
+        frame: usize,
+    },
+    /// switch to `file`, moving the window to `x`,`y` first if given
+    Play {
+        file: PathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        x: Option<f32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        y: Option<f32>,
+    },
+    Alert {
+        file: PathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        caption: Option<String>,
+    },
+    Opacity {
+        value: f32,
+    },
+    Scale {
+        value: f32,
+    },
+    Move {
+        x: f32,
+        y: f32,
+    },
+    Quit,
+    Status,
+}
+
+/// The answer sent back for every request.
+#[derive(Debug, Serialize)]
+struct Reply {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<Status>,
+}
+
+/// Carry out one JSON request, returning the JSON reply.
+pub fn handle_request(json: &str, commands: &Sender<Command>, ctx: &egui::Context) -> String {
+    let reply = match serde_json::from_str::<Request>(json) {
+        Ok(request) => handle(request, commands, ctx),
+        Err(err) => Reply::error(format!("invalid command: {}", err)),
+    };
+    serde_json::to_string(&reply).unwrap_or_default()
+}
+
+fn handle(request: Request, commands: &Sender<Command>, ctx: &egui::Context) -> Reply {
+    let command = match request {
+        Request::Show => Command::Show,
+        Request::Hide => Command::Hide,
+        Request::Trigger => Command::Trigger,
+        Request::Pause => Command::Pause,
+        Request::Resume => Command::Resume,
+        Request::Seek { frame } => Command::Seek(frame),
+        Request::Play { file, x, y } => {
+            if let (Some(x), Some(y)) = (x, y) {
+                if let Err(err) = send(commands, ctx, Command::Move(egui::pos2(x, y))) {
+                    return Reply::error(err);
+                }
+            }
+            Command::Play(file)
+        }
+        Request::Alert { file, caption } => Command::Alert(file, caption),
+        Request::Opacity { value } => Command::Opacity(value),
+        Request::Scale { value } => Command::Scale(value),
+        Request::Move { x, y } => Command::Move(egui::pos2(x, y)),
+        Request::Quit => Command::Quit,
+        Request::Status => {
+            return match status(commands, ctx) {
+                Ok(status) => Reply {
+                    ok: true,
+                    error: None,
+                    status: Some(status),
+                },
+                Err(err) => Reply::error(err),
+            };
+        }
+    };
+    match send(commands, ctx, command) {
+        Ok(()) => Reply {
+            ok: true,
+            error: None,
+            status: None,
+        },
+        Err(err) => Reply::error(err),
+    }
+}
+
+impl Reply {
+    fn error(error: String) -> Self {
+        Self {
+            ok: false,
+            error: Some(error),
+            status: None,
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use crate::control::Command;
+use eframe::egui;
+use std::sync::mpsc::Sender;
+
+/// The overlay's D-Bus service, taken down again when this is dropped.
+pub struct Service {
+    #[cfg(target_os = "linux")]
+    _connection: zbus::blocking::Connection,
+}
+
+/// Offer the `org.overlay_app.Overlay` interface on the session bus,
+/// passing method calls on to `commands` and waking the UI through `ctx`.
+#[cfg(target_os = "linux")]
+pub fn serve(commands: Sender<Command>, ctx: &egui::Context) -> Result<Service, String> {
+    linux::serve(commands, ctx).map(|connection| Service {
+        _connection: connection,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn serve(_commands: Sender<Command>, _ctx: &egui::Context) -> Result<Service, String> {
+    Err("D-Bus is only available on Linux".to_string())
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use crate::control::{self, Command, Status};
+    use eframe::egui;
+    use std::{path::PathBuf, sync::mpsc::Sender};
+    use tracing::info;
+    use zbus::{blocking::ConnectionBuilder, dbus_interface, fdo};
+
+    const BUS_NAME: &str = "org.overlay_app.Overlay";
+    const OBJECT_PATH: &str = "/org/overlay_app/Overlay";
+
+    struct Overlay {
+        commands: Sender<Command>,
+        ctx: egui::Context,
+    }
+
+    impl Overlay {
+        fn send(&self, command: Command) -> fdo::Result<()> {
+            control::send(&self.commands, &self.ctx, command).map_err(fdo::Error::Failed)
+        }
+
+        fn status(&self) -> fdo::Result<Status> {
+            control::status(&self.commands, &self.ctx).map_err(fdo::Error::Failed)
+        }
+    }
+
+    #[dbus_interface(name = "org.overlay_app.Overlay")]
+    impl Overlay {
+        fn show(&self) -> fdo::Result<()> {
+            self.send(Command::Show)
+        }
+
+        fn hide(&self) -> fdo::Result<()> {
+            self.send(Command::Hide)
+        }
+
+        fn trigger(&self) -> fdo::Result<()> {
+            self.send(Command::Trigger)
+        }
+
+        fn pause(&self) -> fdo::Result<()> {
+            self.send(Command::Pause)
+        }
+
+        fn resume(&self) -> fdo::Result<()> {
+            self.send(Command::Resume)
+        }
+
+        fn seek(&self, frame: u32) -> fdo::Result<()> {
+            self.send(Command::Seek(frame as usize))
+        }
+
+        /// switch to another file or URL
+        fn load(&self, path: String) -> fdo::Result<()> {
+            // stdin is taken by the running process, so it can't be switched to
+            if path.is_empty() || path == "-" {
+                return Err(fdo::Error::InvalidArgs("expected a path".to_string()));
+            }
+            self.send(Command::Play(PathBuf::from(path)))
+        }
+
+        fn set_opacity(&self, opacity: f64) -> fdo::Result<()> {
+            self.send(Command::Opacity(opacity as f32))
+        }
+
+        fn set_scale(&self, scale: f64) -> fdo::Result<()> {
+            self.send(Command::Scale(scale as f32))
+        }
+
+        /// put the window's top left corner at `x`,`y` in desktop points
+        fn move_to(&self, x: f64, y: f64) -> fdo::Result<()> {
+            self.send(Command::Move(egui::pos2(x as f32, y as f32)))
+        }
+
+        fn quit(&self) -> fdo::Result<()> {
+            self.send(Command::Quit)
+        }
+
+        #[dbus_interface(property)]
+        fn visible(&self) -> fdo::Result<bool> {
+            Ok(self.status()?.visible)
+        }
+
+        #[dbus_interface(property)]
+        fn paused(&self) -> fdo::Result<bool> {
+            Ok(self.status()?.paused)
+        }
+
+        #[dbus_interface(property)]
+        fn opacity(&self) -> fdo::Result<f64> {
+            Ok(self.status()?.opacity as f64)
+        }
+
+        #[dbus_interface(property)]
+        fn scale(&self) -> fdo::Result<f64> {
+            Ok(self.status()?.scale as f64)
+        }
+
+        #[dbus_interface(property)]
+        fn input(&self) -> fdo::Result<String> {
+            Ok(self.status()?.input.to_string_lossy().into_owned())
+        }
+
+        #[dbus_interface(property)]
+        fn frame(&self) -> fdo::Result<u32> {
+            Ok(self.status()?.frame as u32)
+        }
+
+        #[dbus_interface(property)]
+        fn frames(&self) -> fdo::Result<u32> {
+            Ok(self.status()?.frames as u32)
+        }
+    }
+
+    pub fn serve(
+        commands: Sender<Command>,
+        ctx: &egui::Context,
+    ) -> Result<zbus::blocking::Connection, String> {
+        let overlay = Overlay {
+            commands,
+            ctx: ctx.clone(),
+        };
+        let connection = ConnectionBuilder::session()
+            .and_then(|builder| builder.serve_at(OBJECT_PATH, overlay))
+            .and_then(|builder| builder.build())
+            .map_err(|err| err.to_string())?;
+
+        // like MPRIS players, later overlays tell themselves apart by process id
+        let instance = format!("{}.instance{}", BUS_NAME, std::process::id());
+        let name = [BUS_NAME, instance.as_str()]
+            .into_iter()
+            .find(|name| connection.request_name(*name).is_ok())
+            .ok_or_else(|| format!("couldn't own {} on the session bus", instance))?;
+        info!("D-Bus service {} at {}", name, OBJECT_PATH);
+        Ok(connection)
+    }
+}
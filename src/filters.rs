@@ -0,0 +1,289 @@
+use clap::ValueEnum;
+use eframe::egui::{Color32, ColorImage};
+use std::{borrow::Cow, rc::Rc};
+
+/// Every color effect applied to decoded frames before they're uploaded.
+/// Frames keep their decoded pixels, so the effects can change at runtime.
+#[derive(Debug, Default)]
+pub struct ColorFilters {
+    pub chroma_key: Option<ChromaKey>,
+    pub hue_shift: Option<HueShift>,
+    pub levels: Levels,
+    pub filter: Filter,
+    pub outline: Option<Outline>,
+    // `--plugin` frame filters, run after the built-in effects
+    pub plugins: Rc<[Box<dyn crate::plugin::Plugin>]>,
+}
+
+impl ColorFilters {
+    /// run the effects over unmultiplied RGBA `pixels`, ready for a texture
+    pub fn image(&self, pixels: &[u8], size: [usize; 2]) -> ColorImage {
+        let mut pixels = Cow::Borrowed(pixels);
+        if let Some(chroma_key) = &self.chroma_key {
+            chroma_key.apply(pixels.to_mut());
+        }
+        if let Some(hue_shift) = &self.hue_shift {
+            hue_shift.apply(pixels.to_mut());
+        }
+        if self.levels != Levels::default() {
+            self.levels.apply(pixels.to_mut());
+        }
+        if self.filter != Filter::None {
+            self.filter.apply(pixels.to_mut());
+        }
+        // last, so the outline keeps its own color
+        if let Some(outline) = &self.outline {
+            outline.apply(pixels.to_mut(), size);
+        }
+        for plugin in self.plugins.iter() {
+            plugin.filter_frame(pixels.to_mut(), size);
+        }
+        ColorImage::from_rgba_unmultiplied(size, &pixels)
+    }
+}
+
+/// Brightness, contrast and gamma corrections, for animations authored
+/// against a different background than the one they end up on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Levels {
+    // added to every channel, -1.0 (black) to 1.0 (white)
+    pub brightness: f32,
+    // spread of the channels around mid-gray, 1.0 leaves them alone
+    pub contrast: f32,
+    // above 1.0 brightens the midtones, below darkens them
+    pub gamma: f32,
+}
+
+impl Default for Levels {
+    fn default() -> Self {
+        Self {
+            brightness: 0.0,
+            contrast: 1.0,
+            gamma: 1.0,
+        }
+    }
+}
+
+impl Levels {
+    /// correct unmultiplied RGBA `pixels` in place, leaving alpha alone
+    pub fn apply(&self, pixels: &mut [u8]) {
+        // every channel value maps the same way, so work it out once per value
+        let table: Vec<u8> = (0..=255u8)
+            .map(|value| {
+                let value = (f32::from(value) / 255.0).powf(1.0 / self.gamma.max(0.01));
+                let value = (value - 0.5) * self.contrast + 0.5 + self.brightness;
+                (value * 255.0).round().clamp(0.0, 255.0) as u8
+            })
+            .collect();
+
+        for pixel in pixels.chunks_exact_mut(4) {
+            for channel in &mut pixel[..3] {
+                *channel = table[usize::from(*channel)];
+            }
+        }
+    }
+}
+
+/// Style filter picked with `--filter` and cycled at runtime with `F`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Filter {
+    #[default]
+    None,
+    Grayscale,
+    Sepia,
+    Invert,
+}
+
+impl Filter {
+    /// the filter after this one, wrapping back to none
+    pub fn next(self) -> Self {
+        match self {
+            Filter::None => Filter::Grayscale,
+            Filter::Grayscale => Filter::Sepia,
+            Filter::Sepia => Filter::Invert,
+            Filter::Invert => Filter::None,
+        }
+    }
+
+    /// restyle unmultiplied RGBA `pixels` in place, leaving alpha alone
+    pub fn apply(self, pixels: &mut [u8]) {
+        for pixel in pixels.chunks_exact_mut(4) {
+            let [r, g, b] = [pixel[0], pixel[1], pixel[2]].map(f32::from);
+            let rgb = match self {
+                Filter::None => continue,
+                Filter::Grayscale => {
+                    let luma = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+                    [luma; 3]
+                }
+                Filter::Sepia => [
+                    0.393 * r + 0.769 * g + 0.189 * b,
+                    0.349 * r + 0.686 * g + 0.168 * b,
+                    0.272 * r + 0.534 * g + 0.131 * b,
+                ],
+                Filter::Invert => [255.0 - r, 255.0 - g, 255.0 - b],
+            };
+            for (channel, value) in rgb.into_iter().enumerate() {
+                pixel[channel] = value.round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+}
+
+/// Makes pixels close to a key color transparent, turning a solid
+/// "green screen" background into a real overlay.
+#[derive(Debug, Clone, Copy)]
+pub struct ChromaKey {
+    color: Color32,
+    // largest distance from the key color, 0.0 (exact match) to 1.0 (everything)
+    tolerance: f32,
+}
+
+impl ChromaKey {
+    pub fn new(color: Color32, tolerance: f32) -> Self {
+        Self {
+            color,
+            tolerance: tolerance.clamp(0.0, 1.0),
+        }
+    }
+
+    /// clear the alpha of every matching pixel in unmultiplied RGBA `pixels`
+    pub fn apply(&self, pixels: &mut [u8]) {
+        let key = [self.color.r(), self.color.g(), self.color.b()].map(f32::from);
+        // compare squared distances, normalized so black to white is 1.0
+        let max_distance = (self.tolerance * 255.0).powi(2) * 3.0;
+
+        for pixel in pixels.chunks_exact_mut(4) {
+            let distance: f32 = (0..3)
+                .map(|channel| (f32::from(pixel[channel]) - key[channel]).powi(2))
+                .sum();
+            if distance <= max_distance {
+                pixel[3] = 0;
+            }
+        }
+    }
+}
+
+/// Rotates the hue of every pixel by a fixed angle while keeping its
+/// brightness, so one asset can be recolored to match different themes.
+#[derive(Debug, Clone, Copy)]
+pub struct HueShift {
+    // the hue-rotate matrix from the CSS filter effects spec
+    matrix: [[f32; 3]; 3],
+}
+
+impl HueShift {
+    pub fn new(degrees: f32) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self {
+            matrix: [
+                [
+                    0.213 + cos * 0.787 - sin * 0.213,
+                    0.715 - cos * 0.715 - sin * 0.715,
+                    0.072 - cos * 0.072 + sin * 0.928,
+                ],
+                [
+                    0.213 - cos * 0.213 + sin * 0.143,
+                    0.715 + cos * 0.285 + sin * 0.140,
+                    0.072 - cos * 0.072 - sin * 0.283,
+                ],
+                [
+                    0.213 - cos * 0.213 - sin * 0.787,
+                    0.715 - cos * 0.715 + sin * 0.715,
+                    0.072 + cos * 0.928 + sin * 0.072,
+                ],
+            ],
+        }
+    }
+
+    /// shift the hue of unmultiplied RGBA `pixels` in place
+    pub fn apply(&self, pixels: &mut [u8]) {
+        for pixel in pixels.chunks_exact_mut(4) {
+            let rgb = [pixel[0], pixel[1], pixel[2]].map(f32::from);
+            for (channel, row) in self.matrix.iter().enumerate() {
+                let value = row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2];
+                pixel[channel] = value.round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+}
+
+/// An outline around the opaque parts of a frame, grown out of its alpha
+/// channel. It stays inside the frame, so content touching the edge isn't
+/// outlined there.
+#[derive(Debug, Clone, Copy)]
+pub struct Outline {
+    color: Color32,
+    // in pixels of the decoded frame
+    width: usize,
+}
+
+impl Outline {
+    /// draw the outline under unmultiplied RGBA `pixels` of `size` in place
+    pub fn apply(&self, pixels: &mut [u8], size: [usize; 2]) {
+        let [width, height] = size;
+        let alpha: Vec<u8> = pixels.chunks_exact(4).map(|pixel| pixel[3]).collect();
+        // grow the alpha by `width` pixels, one axis at a time
+        let grown = dilate(
+            &dilate(&alpha, width, height, self.width, 1),
+            width,
+            height,
+            self.width,
+            width,
+        );
+
+        let [r, g, b, a] = self.color.to_srgba_unmultiplied();
+        for (pixel, &coverage) in pixels.chunks_exact_mut(4).zip(&grown) {
+            let src_alpha = f32::from(pixel[3]) / 255.0;
+            let outline_alpha = f32::from(coverage) / 255.0 * f32::from(a) / 255.0;
+            // the frame drawn over the outline
+            let under = outline_alpha * (1.0 - src_alpha);
+            let out_alpha = src_alpha + under;
+            if out_alpha <= 0.0 {
+                continue;
+            }
+            for (channel, outline) in [r, g, b].into_iter().enumerate() {
+                let value = (f32::from(pixel[channel]) * src_alpha + f32::from(outline) * under)
+                    / out_alpha;
+                pixel[channel] = value.round().clamp(0.0, 255.0) as u8;
+            }
+            pixel[3] = (out_alpha * 255.0).round() as u8;
+        }
+    }
+}
+
+/// Largest value within `radius` steps along one axis of a `width`×`height`
+/// grid, `stride` being 1 for rows and `width` for columns.
+fn dilate(values: &[u8], width: usize, height: usize, radius: usize, stride: usize) -> Vec<u8> {
+    let len = if stride == 1 { width } else { height };
+    let mut grown = vec![0; values.len()];
+    for (idx, out) in grown.iter_mut().enumerate() {
+        let pos = if stride == 1 {
+            idx % width
+        } else {
+            idx / width
+        };
+        let start = idx - pos.min(radius) * stride;
+        let end = idx + (len - 1 - pos).min(radius) * stride;
+        *out = (start..=end)
+            .step_by(stride)
+            .map(|idx| values[idx])
+            .max()
+            .unwrap_or(0);
+    }
+    grown
+}
+
+/// parse `--outline` as `COLOR,WIDTH`
+pub fn parse_outline(s: &str) -> Result<Outline, String> {
+    let (color, width) = s
+        .split_once(',')
+        .ok_or_else(|| format!("expected COLOR,WIDTH, got {:?}", s))?;
+    let width = width
+        .trim()
+        .parse::<usize>()
+        .map_err(|err| format!("invalid outline width {:?}: {}", width, err))?;
+    Ok(Outline {
+        color: crate::color::parse_color(color)?,
+        width,
+    })
+}
@@ -0,0 +1,57 @@
+use device_query::{DeviceQuery, DeviceState};
+use eframe::egui::{pos2, vec2, Pos2, Vec2};
+use std::time::Instant;
+
+/// Moves the overlay after the mouse cursor, easing towards it so the window
+/// trails behind instead of sticking to the pointer.
+pub struct CursorFollower {
+    device: DeviceState,
+    offset: Vec2,
+    // fraction of the distance still left to cover after 1/60s, 0 = no lag
+    smoothing: f32,
+    position: Option<Pos2>,
+    last_step: Instant,
+}
+
+impl CursorFollower {
+    pub fn new(offset: Vec2, smoothing: f32) -> Self {
+        Self {
+            device: DeviceState::new(),
+            offset,
+            smoothing: smoothing.clamp(0.0, 0.99),
+            position: None,
+            last_step: Instant::now(),
+        }
+    }
+
+    /// Where the window should move to now, starting from `current` on the
+    /// first step. The cursor is reported in physical pixels, so it's divided
+    /// by `pixels_per_point` to match egui's window coordinates.
+    pub fn step(&mut self, current: Pos2, pixels_per_point: f32) -> Pos2 {
+        let (x, y) = self.device.get_mouse().coords;
+        let target = pos2(x as f32, y as f32) / pixels_per_point + self.offset;
+
+        // scale the easing by elapsed time so the speed doesn't depend on frame rate
+        let elapsed = self.last_step.elapsed().as_secs_f32();
+        self.last_step = Instant::now();
+        let progress = 1.0 - self.smoothing.powf(elapsed * 60.0);
+
+        let position = self.position.unwrap_or(current);
+        let next = position + (target - position) * progress;
+        self.position = Some(next);
+        next
+    }
+}
+
+/// parse `--follow-offset` as `X,Y` in points
+pub fn parse_offset(s: &str) -> Result<Vec2, String> {
+    let (x, y) = s
+        .split_once(',')
+        .ok_or_else(|| format!("expected X,Y, got {:?}", s))?;
+    let parse = |v: &str| {
+        v.trim()
+            .parse::<f32>()
+            .map_err(|err| format!("invalid offset {:?}: {}", v, err))
+    };
+    Ok(vec2(parse(x)?, parse(y)?))
+}
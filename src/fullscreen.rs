@@ -0,0 +1,157 @@
+use std::error::Error;
+use tracing::warn;
+
+/// Watches whether the focused window of another application covers its
+/// whole monitor, e.g. a game or a fullscreen video.
+///
+/// Supported on Windows and X11; elsewhere the watcher reports that nothing
+/// is fullscreen.
+pub struct FullscreenWatcher {
+    platform: Option<platform::Watcher>,
+}
+
+impl FullscreenWatcher {
+    pub fn new() -> Self {
+        let platform = platform::Watcher::new()
+            .map_err(|err| warn!("fullscreen detection unavailable: {}", err))
+            .ok();
+        Self { platform }
+    }
+
+    /// whether the foreground window is fullscreen right now
+    pub fn foreground_is_fullscreen(&self) -> bool {
+        self.platform
+            .as_ref()
+            .is_some_and(|watcher| watcher.foreground_is_fullscreen().unwrap_or(false))
+    }
+}
+
+impl Default for FullscreenWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::Error;
+    use std::mem;
+    use windows_sys::Win32::{
+        Foundation::RECT,
+        Graphics::Gdi::{
+            GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+        },
+        UI::WindowsAndMessaging::{
+            GetDesktopWindow, GetForegroundWindow, GetShellWindow, GetWindowRect,
+        },
+    };
+
+    pub struct Watcher;
+
+    impl Watcher {
+        pub fn new() -> Result<Self, Box<dyn Error>> {
+            Ok(Self)
+        }
+
+        pub fn foreground_is_fullscreen(&self) -> Result<bool, Box<dyn Error>> {
+            unsafe {
+                let hwnd = GetForegroundWindow();
+                // the desktop itself spans the whole screen but isn't an app
+                if hwnd == 0 || hwnd == GetDesktopWindow() || hwnd == GetShellWindow() {
+                    return Ok(false);
+                }
+
+                let mut window: RECT = mem::zeroed();
+                if GetWindowRect(hwnd, &mut window) == 0 {
+                    return Ok(false);
+                }
+
+                let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+                let mut info: MONITORINFO = mem::zeroed();
+                info.cbSize = mem::size_of::<MONITORINFO>() as u32;
+                if GetMonitorInfoW(monitor, &mut info) == 0 {
+                    return Ok(false);
+                }
+
+                let screen = info.rcMonitor;
+                Ok(window.left <= screen.left
+                    && window.top <= screen.top
+                    && window.right >= screen.right
+                    && window.bottom >= screen.bottom)
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::Error;
+    use x11rb::{
+        protocol::xproto::{Atom, AtomEnum, ConnectionExt, Window},
+        rust_connection::RustConnection,
+    };
+
+    pub struct Watcher {
+        conn: RustConnection,
+        root: Window,
+        active_window: Atom,
+        wm_state: Atom,
+        fullscreen: Atom,
+    }
+
+    impl Watcher {
+        pub fn new() -> Result<Self, Box<dyn Error>> {
+            let (conn, screen_num) = x11rb::connect(None)?;
+            let root = x11rb::connection::Connection::setup(&conn).roots[screen_num].root;
+            let atom = |name: &[u8]| -> Result<Atom, Box<dyn Error>> {
+                Ok(conn.intern_atom(false, name)?.reply()?.atom)
+            };
+
+            Ok(Self {
+                root,
+                active_window: atom(b"_NET_ACTIVE_WINDOW")?,
+                wm_state: atom(b"_NET_WM_STATE")?,
+                fullscreen: atom(b"_NET_WM_STATE_FULLSCREEN")?,
+                conn,
+            })
+        }
+
+        pub fn foreground_is_fullscreen(&self) -> Result<bool, Box<dyn Error>> {
+            let active = self
+                .conn
+                .get_property(false, self.root, self.active_window, AtomEnum::WINDOW, 0, 1)?
+                .reply()?;
+            let Some(window) = active.value32().and_then(|mut values| values.next()) else {
+                return Ok(false);
+            };
+            if window == 0 {
+                return Ok(false);
+            }
+
+            let state = self
+                .conn
+                .get_property(false, window, self.wm_state, AtomEnum::ATOM, 0, 32)?
+                .reply()?;
+            Ok(state
+                .value32()
+                .is_some_and(|mut atoms| atoms.any(|atom| atom == self.fullscreen)))
+        }
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+mod platform {
+    use super::Error;
+
+    pub struct Watcher;
+
+    impl Watcher {
+        pub fn new() -> Result<Self, Box<dyn Error>> {
+            Err("not supported on this platform".into())
+        }
+
+        pub fn foreground_is_fullscreen(&self) -> Result<bool, Box<dyn Error>> {
+            Ok(false)
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::control::{self, ButtonAction, Command};
+use eframe::egui;
+use gilrs::{Button, EventType, Gilrs};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+    thread,
+    time::Duration,
+};
+use tracing::{info, warn};
+
+/// how often to check for button presses while none are waiting
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A `--gamepad` file: what each button does on any connected controller.
+///
+/// ```toml
+/// [buttons]
+/// a = "reactions/lol.gif"     # played once as an alert
+/// b = "reactions/rip.gif"
+/// y = "trigger"               # also show, hide, pause, resume, and quit
+/// select = "hide"
+/// start = "show"
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    buttons: HashMap<String, String>,
+    // the file's folder, which alerts are relative to
+    #[serde(skip)]
+    base: PathBuf,
+}
+
+/// read and parse a `--gamepad` file
+pub fn load(path: &Path) -> io::Result<Config> {
+    let contents = std::fs::read_to_string(path)?;
+    let mut config: Config =
+        toml::from_str(&contents).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    config.base = path.parent().unwrap_or(Path::new("")).to_path_buf();
+    Ok(config)
+}
+
+/// A button by its name in a `--gamepad` file: the face buttons by position
+/// (`south`, ...) or by their Xbox letter (`a`, ...), then `left-bumper`,
+/// `left-trigger`, `select`, `start`, `mode`, `left-stick` for pressing it
+/// in, and `dpad-up`, ...
+fn button(name: &str) -> Option<Button> {
+    Some(match name {
+        "south" | "a" => Button::South,
+        "east" | "b" => Button::East,
+        "west" | "x" => Button::West,
+        "north" | "y" => Button::North,
+        "left-bumper" => Button::LeftTrigger,
+        "left-trigger" => Button::LeftTrigger2,
+        "right-bumper" => Button::RightTrigger,
+        "right-trigger" => Button::RightTrigger2,
+        "select" => Button::Select,
+        "start" => Button::Start,
+        "mode" => Button::Mode,
+        "left-stick" => Button::LeftThumb,
+        "right-stick" => Button::RightThumb,
+        "dpad-up" => Button::DPadUp,
+        "dpad-down" => Button::DPadDown,
+        "dpad-left" => Button::DPadLeft,
+        "dpad-right" => Button::DPadRight,
+        _ => return None,
+    })
+}
+
+/// Watch connected gamepads in the background, passing the commands for
+/// pressed buttons on to `commands` and waking the UI through `ctx`.
+pub fn listen(
+    config: &Config,
+    commands: Sender<Command>,
+    ctx: &egui::Context,
+) -> Result<(), String> {
+    let buttons: HashMap<Button, ButtonAction> = config
+        .buttons
+        .iter()
+        .map(|(name, action)| {
+            let button = button(&name.to_lowercase())
+                .ok_or_else(|| format!("no gamepad button called {:?}", name))?;
+            Ok((button, ButtonAction::parse(action, &config.base)))
+        })
+        .collect::<Result<_, String>>()?;
+
+    let ctx = ctx.clone();
+    thread::spawn(move || {
+        let mut gilrs = match Gilrs::new() {
+            Ok(gilrs) => gilrs,
+            Err(err) => {
+                warn!("failed to open gamepads: {}", err);
+                return;
+            }
+        };
+        for (_, gamepad) in gilrs.gamepads() {
+            info!("Listening to gamepad {}", gamepad.name());
+        }
+        loop {
+            while let Some(event) = gilrs.next_event() {
+                match event.event {
+                    EventType::ButtonPressed(button, _) => {
+                        if let Some(action) = buttons.get(&button) {
+                            let _ = control::send(&commands, &ctx, action.command());
+                        }
+                    }
+                    EventType::Connected => {
+                        info!("Gamepad {} connected", gilrs.gamepad(event.id).name());
+                    }
+                    _ => {}
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+    Ok(())
+}
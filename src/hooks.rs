@@ -0,0 +1,78 @@
+use std::{
+    fmt,
+    path::Path,
+    process::{Command, Stdio},
+    str::FromStr,
+    thread,
+};
+use tracing::warn;
+
+/// A shell command run whenever playback reaches a given frame, written on
+/// the command line as `FRAME:COMMAND` (e.g. `12:curl -s localhost/flash`).
+#[derive(Debug, Clone)]
+pub struct FrameHook {
+    pub frame: usize,
+    command: String,
+}
+
+impl FrameHook {
+    /// Start the command without waiting for it, so a slow hook never stalls
+    /// playback. The frame index and input are passed as `OVERLAY_FRAME` and
+    /// `OVERLAY_INPUT`.
+    pub fn run(&self, input: &Path) {
+        let mut command = shell_command(&self.command);
+        command
+            .env("OVERLAY_FRAME", self.frame.to_string())
+            .env("OVERLAY_INPUT", input)
+            .stdin(Stdio::null());
+
+        match command.spawn() {
+            Ok(mut child) => {
+                // reap the process once it exits
+                thread::spawn(move || child.wait());
+            }
+            Err(err) => warn!("failed to run frame {} hook: {}", self.frame, err),
+        }
+    }
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.args(["/C", command]);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.args(["-c", command]);
+    shell
+}
+
+#[derive(Debug)]
+pub struct ParseFrameHookError(String);
+
+impl fmt::Display for ParseFrameHookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid frame hook {:?}, expected FRAME:COMMAND", self.0)
+    }
+}
+
+impl std::error::Error for ParseFrameHookError {}
+
+impl FromStr for FrameHook {
+    type Err = ParseFrameHookError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_once(':')
+            .and_then(|(frame, command)| {
+                let command = command.trim();
+                Some(Self {
+                    frame: frame.trim().parse().ok()?,
+                    command: (!command.is_empty()).then(|| command.to_string())?,
+                })
+            })
+            .ok_or_else(|| ParseFrameHookError(s.to_string()))
+    }
+}
@@ -0,0 +1,100 @@
+use eframe::egui;
+use global_hotkey::{hotkey::HotKey, GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    io,
+    path::Path,
+    sync::mpsc::{channel, Receiver},
+};
+use tracing::warn;
+
+/// What a system-wide hotkey does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    /// hide or show the overlay
+    Toggle,
+    /// skip to the next playlist entry
+    Next,
+    OpacityUp,
+    OpacityDown,
+    /// pause or resume playback
+    Pause,
+    /// play the `--active` animation
+    Trigger,
+    /// show or hide the performance HUD
+    Hud,
+    Quit,
+}
+
+/// A `--hotkeys` file, e.g.
+///
+/// ```toml
+/// [hotkeys]
+/// "ctrl+shift+KeyH" = "toggle"
+/// "ctrl+shift+KeyN" = "next"
+/// ```
+#[derive(Debug, Deserialize)]
+struct HotkeysFile {
+    #[serde(default)]
+    hotkeys: BTreeMap<String, Action>,
+}
+
+/// read the key combos and their actions from a `--hotkeys` file
+pub fn load(path: &Path) -> io::Result<Vec<(String, Action)>> {
+    let contents = std::fs::read_to_string(path)?;
+    let file: HotkeysFile =
+        toml::from_str(&contents).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    Ok(file.hotkeys.into_iter().collect())
+}
+
+/// System-wide hotkeys that fire even while the overlay isn't focused.
+pub struct GlobalHotkeys {
+    // unregisters the hotkeys when dropped
+    _manager: GlobalHotKeyManager,
+    pub presses: Receiver<Action>,
+}
+
+impl GlobalHotkeys {
+    /// Register each combo in `bindings` (e.g. `ctrl+shift+KeyH`), waking the
+    /// UI through `ctx` on every press. Combos that can't be registered are
+    /// reported and left out.
+    pub fn register(bindings: &[(String, Action)], ctx: &egui::Context) -> Result<Self, String> {
+        let manager = GlobalHotKeyManager::new().map_err(|err| err.to_string())?;
+        let mut actions = HashMap::new();
+        for (keys, action) in bindings {
+            let registered = keys
+                .parse::<HotKey>()
+                .map_err(|err| format!("{:?}", err))
+                .and_then(|hotkey| {
+                    manager.register(hotkey).map_err(|err| err.to_string())?;
+                    Ok(hotkey)
+                });
+            match registered {
+                Ok(hotkey) => {
+                    actions.insert(hotkey.id(), *action);
+                }
+                Err(err) => warn!("failed to register hotkey {:?}: {}", keys, err),
+            }
+        }
+
+        // there's a single handler for the whole process
+        let (sender, presses) = channel();
+        let ctx = ctx.clone();
+        GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+            if event.state != HotKeyState::Pressed {
+                return;
+            }
+            if let Some(action) = actions.get(&event.id) {
+                let _ = sender.send(*action);
+                ctx.request_repaint();
+            }
+        }));
+
+        Ok(Self {
+            _manager: manager,
+            presses,
+        })
+    }
+}
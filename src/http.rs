@@ -0,0 +1,154 @@
+use crate::{
+    control::{self, Command},
+    webhook,
+};
+use eframe::egui;
+use std::{io::Read, path::PathBuf, sync::mpsc::Sender, thread};
+use tiny_http::{Header, Request, Response, Server};
+use tracing::info;
+
+/// Serve the control API on `127.0.0.1:port`, passing each request on to
+/// `commands` and waking the UI through `ctx`:
+///
+/// - `/show`, `/hide`, `/trigger`
+/// - `/play?file=PATH` switches to another input
+/// - `/opacity?value=0.5`
+/// - `/status` answers with JSON describing the overlay
+/// - `/webhook` and `/webhook/<source>` take JSON payloads for `webhooks`
+pub fn serve(
+    port: u16,
+    webhooks: Option<webhook::Rules>,
+    commands: Sender<Command>,
+    ctx: &egui::Context,
+) -> Result<(), String> {
+    let server = Server::http(("127.0.0.1", port)).map_err(|err| err.to_string())?;
+    info!("Control API listening on http://127.0.0.1:{}", port);
+
+    let ctx = ctx.clone();
+    thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let source = webhook_source(request.url()).map(|source| source.map(str::to_string));
+            let (status, body) = match (source, &webhooks) {
+                (Some(source), Some(webhooks)) => {
+                    handle_webhook(&mut request, source.as_deref(), webhooks, &commands, &ctx)
+                }
+                _ => handle(request.url(), &commands, &ctx),
+            };
+            let mut response = Response::from_string(body).with_status_code(status);
+            if status == 200 && request.url().starts_with("/status") {
+                if let Ok(header) = Header::from_bytes("Content-Type", "application/json") {
+                    response.add_header(header);
+                }
+            }
+            let _ = request.respond(response);
+        }
+    });
+    Ok(())
+}
+
+/// route one request, returning the HTTP status code and body to answer with
+fn handle(url: &str, commands: &Sender<Command>, ctx: &egui::Context) -> (u16, String) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let param = |name: &str| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| percent_decode(value))
+    };
+
+    let command = match path {
+        "/show" => Command::Show,
+        "/hide" => Command::Hide,
+        "/trigger" => Command::Trigger,
+        // stdin is taken by the running process, so it can't be switched to
+        "/play" => match param("file").filter(|file| !file.is_empty() && file != "-") {
+            Some(file) => Command::Play(PathBuf::from(file)),
+            None => return (400, "expected /play?file=PATH".to_string()),
+        },
+        "/opacity" => match param("value").and_then(|value| value.parse().ok()) {
+            Some(value) => Command::Opacity(value),
+            None => return (400, "expected /opacity?value=0.0-1.0".to_string()),
+        },
+        "/status" => {
+            return match control::status(commands, ctx) {
+                Ok(status) => (200, serde_json::to_string(&status).unwrap_or_default()),
+                Err(err) => (503, err),
+            };
+        }
+        _ => return (404, format!("no endpoint {}", path)),
+    };
+
+    match control::send(commands, ctx, command) {
+        Ok(()) => (200, "ok".to_string()),
+        Err(err) => (503, err),
+    }
+}
+
+/// `Some` for `/webhook` URLs, holding the `<source>` of `/webhook/<source>`
+fn webhook_source(url: &str) -> Option<Option<&str>> {
+    let (path, _) = url.split_once('?').unwrap_or((url, ""));
+    match path.strip_prefix("/webhook")? {
+        "" | "/" => Some(None),
+        rest => rest.strip_prefix('/').map(Some),
+    }
+}
+
+/// play the alert the first matching rule names for the request's payload
+fn handle_webhook(
+    request: &mut Request,
+    source: Option<&str>,
+    webhooks: &webhook::Rules,
+    commands: &Sender<Command>,
+    ctx: &egui::Context,
+) -> (u16, String) {
+    let mut body = String::new();
+    if let Err(err) = request.as_reader().read_to_string(&mut body) {
+        return (400, format!("failed to read payload: {}", err));
+    }
+    // Ko-fi sends its JSON as the `data` field of a form
+    let body = match body.strip_prefix("data=") {
+        Some(data) => percent_decode(data),
+        None => body,
+    };
+    let payload = match serde_json::from_str::<serde_json::Value>(&body) {
+        Ok(payload) => payload,
+        Err(err) => return (400, format!("expected a JSON payload: {}", err)),
+    };
+
+    let Some((alert, caption)) = webhooks.alert(source, &payload) else {
+        return (200, "no matching rule".to_string());
+    };
+    info!("Webhook matched, playing {}", alert.display());
+    match control::send(commands, ctx, Command::Alert(alert, caption)) {
+        Ok(()) => (200, "ok".to_string()),
+        Err(err) => (503, err),
+    }
+}
+
+/// undo URL encoding (`%20`, `+`) in a query value
+fn percent_decode(value: &str) -> String {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = rest
+                    .get(..2)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match hex {
+                    Some(decoded) => {
+                        bytes.push(decoded);
+                        rest = &rest[2..];
+                    }
+                    None => bytes.push(b'%'),
+                }
+            }
+            other => bytes.push(other),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
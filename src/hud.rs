@@ -0,0 +1,128 @@
+use eframe::egui::{self, Align2, RichText};
+use egui_plot::{Line, Plot, PlotPoints};
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// UI updates the frame timing graph covers
+const HISTORY: usize = 240;
+
+/// gaps between updates longer than this are the overlay sitting idle, not
+/// slow frames, so they're left out
+const IDLE_GAP: Duration = Duration::from_secs(1);
+
+/// What the overlay is up to, for the HUD to show.
+pub struct Stats {
+    pub frame: usize,
+    pub frames: usize,
+    pub loaded: usize,
+    /// the frame count, once loading is over
+    pub total: Option<usize>,
+    /// RGBA bytes of the decoded frames kept for playback
+    pub decoded_bytes: usize,
+    /// GPU memory of the frames' textures, estimated
+    pub texture_bytes: usize,
+    /// the whole process's resident memory, where the system reports it
+    pub resident_bytes: Option<usize>,
+}
+
+/// An on-screen readout of the frame rate, loading progress, memory, and a
+/// graph of how long each UI update took, drawn over the animation.
+pub struct Hud {
+    last_update: Instant,
+    // milliseconds between UI updates, oldest first
+    update_times: VecDeque<f64>,
+}
+
+impl Default for Hud {
+    fn default() -> Self {
+        Self {
+            last_update: Instant::now(),
+            update_times: VecDeque::with_capacity(HISTORY),
+        }
+    }
+}
+
+impl Hud {
+    /// note that the UI is being updated again
+    pub fn tick(&mut self) {
+        let elapsed = self.last_update.elapsed();
+        self.last_update = Instant::now();
+        if elapsed >= IDLE_GAP {
+            return;
+        }
+        if self.update_times.len() == HISTORY {
+            self.update_times.pop_front();
+        }
+        self.update_times.push_back(elapsed.as_secs_f64() * 1000.0);
+    }
+
+    /// updates per second over the last second's worth of them
+    fn fps(&self) -> f64 {
+        let mut total = 0.0;
+        let mut count = 0;
+        for ms in self.update_times.iter().rev() {
+            total += ms;
+            count += 1;
+            if total >= 1000.0 {
+                break;
+            }
+        }
+        if total > 0.0 {
+            count as f64 * 1000.0 / total
+        } else {
+            0.0
+        }
+    }
+
+    pub fn show(&self, ctx: &egui::Context, stats: &Stats) {
+        let mb = |bytes: usize| bytes as f64 / (1024.0 * 1024.0);
+        let loading = match stats.total {
+            Some(total) => format!("{} frames loaded", total),
+            None => format!("loading, {} frames so far", stats.loaded),
+        };
+        let lines = [
+            format!("{:.1} FPS", self.fps()),
+            format!("frame {}/{}, {}", stats.frame + 1, stats.frames, loading),
+            match stats.resident_bytes {
+                Some(bytes) => format!("{:.1} MB in use", mb(bytes)),
+                None => "memory in use unknown".to_string(),
+            },
+            format!(
+                "{:.1} MB decoded, ~{:.1} MB textures",
+                mb(stats.decoded_bytes),
+                mb(stats.texture_bytes)
+            ),
+        ];
+
+        egui::Area::new("hud")
+            .anchor(Align2::LEFT_TOP, egui::vec2(4.0, 4.0))
+            .order(egui::Order::Foreground)
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    for line in lines {
+                        ui.label(RichText::new(line).monospace().small());
+                    }
+                    let points: PlotPoints = self
+                        .update_times
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, &ms)| [idx as f64, ms])
+                        .collect();
+                    Plot::new("hud_update_times")
+                        .width(180.0)
+                        .height(48.0)
+                        .include_x(HISTORY as f64)
+                        .include_y(0.0)
+                        .show_axes([false, true])
+                        .allow_drag(false)
+                        .allow_zoom(false)
+                        .allow_scroll(false)
+                        .allow_boxed_zoom(false)
+                        .show(ui, |plot_ui| plot_ui.line(Line::new(points).name("ms")));
+                });
+            });
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{
+    spawn_loader, validate, video, Downscale, InputFormat, LoaderOptions, LoadingMessage,
+    STATIC_FRAME_DELAY,
+};
+use eframe::egui;
+use serde::Serialize;
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver},
+    thread,
+    time::Duration,
+};
+use tracing::error;
+
+/// What `overlay_app info` found out about an input by decoding it.
+#[derive(Debug, Serialize)]
+struct Summary {
+    input: PathBuf,
+    /// `None` for stdin and URLs, which are only sniffed once downloaded
+    format: Option<String>,
+    frames: usize,
+    width: usize,
+    height: usize,
+    /// one loop, or `None` for a still image
+    duration_ms: Option<u64>,
+    average_delay_ms: Option<f64>,
+    /// RGBA bytes the decoded frames take up in memory
+    decoded_bytes: usize,
+    /// videos are decoded while they play, holding one frame at a time
+    streamed: bool,
+}
+
+/// Decode `path` the way the overlay would, without opening a window.
+fn summarize(path: &Path, options: LoaderOptions) -> Result<Summary, String> {
+    // streamed frames never stop coming, so count them all instead, and at
+    // the size they are rather than the size they'd be shown at
+    let options = LoaderOptions {
+        stream_frames: None,
+        downscale: Downscale::Off,
+        cache: false,
+        ..options
+    };
+    let format = validate::input(path, options.sprite_sheet.is_some())?;
+    let streamed = format == Some(InputFormat::Video);
+    // a video stream plays in real time and never ends, so read it in one go
+    let messages = if streamed {
+        decode_video(path, options)
+    } else {
+        // no rescales are coming, so SVGs stop after the first render
+        let (receiver, _) = spawn_loader(&egui::Context::default(), path.to_path_buf(), options);
+        receiver
+    };
+
+    let mut summary = Summary {
+        input: path.to_path_buf(),
+        format: format.map(|format| format!("{:?}", format)),
+        frames: 0,
+        width: 0,
+        height: 0,
+        duration_ms: None,
+        average_delay_ms: None,
+        decoded_bytes: 0,
+        streamed,
+    };
+    let mut duration = Duration::ZERO;
+    let mut largest_frame = 0;
+    for message in messages {
+        match message {
+            LoadingMessage::FrameReady(_, pixels, size, delay)
+            | LoadingMessage::StreamFrame(_, pixels, size, delay) => {
+                summary.frames += 1;
+                summary.width = summary.width.max(size[0]);
+                summary.height = summary.height.max(size[1]);
+                summary.decoded_bytes += pixels.len();
+                largest_frame = largest_frame.max(pixels.len());
+                if delay != STATIC_FRAME_DELAY {
+                    duration += delay;
+                }
+            }
+            LoadingMessage::LoadingComplete(_) | LoadingMessage::RenderScale(_) => {}
+            LoadingMessage::Error(err) => return Err(format!("{}: {}", path.display(), err)),
+        }
+    }
+
+    if summary.frames == 0 {
+        return Err(format!("{}: no frames decoded", path.display()));
+    }
+    if !duration.is_zero() {
+        summary.duration_ms = Some(duration.as_millis() as u64);
+        summary.average_delay_ms = Some(duration.as_secs_f64() * 1000.0 / summary.frames as f64);
+    }
+    if streamed {
+        summary.decoded_bytes = largest_frame;
+    }
+    Ok(summary)
+}
+
+/// decode every frame of a video through ffmpeg, as AVIF sequences are
+pub fn decode_video(path: &Path, options: LoaderOptions) -> Receiver<LoadingMessage> {
+    let (sender, receiver) = channel();
+    let path = path.to_path_buf();
+    // played one frame at a time, so at full size whatever the memory limit
+    let options = LoaderOptions {
+        max_memory: None,
+        ..options
+    };
+    thread::spawn(move || {
+        let ctx = egui::Context::default();
+        if let Err(err) = video::decode_sequence(&path, options, &sender, &ctx) {
+            let _ = sender.send(LoadingMessage::Error(err));
+        }
+    });
+    receiver
+}
+
+impl Summary {
+    fn print(&self, json: bool) {
+        if json {
+            match serde_json::to_string(self) {
+                Ok(json) => println!("{}", json),
+                Err(err) => error!("failed to write summary: {}", err),
+            }
+            return;
+        }
+
+        println!("{}", self.input.display());
+        if let Some(format) = &self.format {
+            println!("  format:   {}", format);
+        }
+        println!("  frames:   {}", self.frames);
+        println!("  size:     {}x{}", self.width, self.height);
+        match (self.duration_ms, self.average_delay_ms) {
+            (Some(duration), Some(delay)) => println!(
+                "  duration: {:.2}s (average delay {:.1}ms)",
+                duration as f64 / 1000.0,
+                delay
+            ),
+            _ => println!("  duration: still image"),
+        }
+        println!(
+            "  memory:   {:.1} MB decoded{}",
+            self.decoded_bytes as f64 / (1024.0 * 1024.0),
+            if self.streamed {
+                ", one frame at a time"
+            } else {
+                ""
+            }
+        );
+    }
+}
+
+/// Print a summary of each of `inputs`, returning whether they all decoded.
+pub fn run(inputs: &[PathBuf], options: LoaderOptions, json: bool) -> bool {
+    let mut all_ok = true;
+    for path in inputs {
+        match summarize(path, options) {
+            Ok(summary) => summary.print(json),
+            Err(err) => {
+                error!("{}", err);
+                all_ok = false;
+            }
+        }
+    }
+    all_ok
+}
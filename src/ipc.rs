@@ -0,0 +1,191 @@
+use crate::control::{self, Command, Request};
+use eframe::egui;
+use interprocess::local_socket::{LocalSocketListener, LocalSocketStream, NameTypeSupport};
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+    thread,
+};
+use tracing::info;
+
+/// Socket name for overlays started with `--ipc NAME`: a named pipe on
+/// Windows, an abstract socket on Linux, and a file in the temp folder on
+/// other Unixes.
+fn socket_name(name: &str) -> String {
+    match NameTypeSupport::query() {
+        NameTypeSupport::OnlyPaths => std::env::temp_dir()
+            .join(format!("{}.sock", name))
+            .to_string_lossy()
+            .into_owned(),
+        NameTypeSupport::OnlyNamespaced | NameTypeSupport::Both => format!("@{}.sock", name),
+    }
+}
+
+/// Listen for line-based JSON commands on the socket called `name`, passing
+/// them on to `commands` and waking the UI through `ctx`.
+pub fn listen(name: &str, commands: Sender<Command>, ctx: &egui::Context) -> io::Result<()> {
+    let socket = socket_name(name);
+    // a socket file left behind by a crashed overlay would block the name
+    if matches!(NameTypeSupport::query(), NameTypeSupport::OnlyPaths)
+        && LocalSocketStream::connect(socket.as_str()).is_err()
+    {
+        let _ = std::fs::remove_file(&socket);
+    }
+    let listener = LocalSocketListener::bind(socket.as_str())?;
+    info!("Listening for commands on {}", socket);
+
+    let ctx = ctx.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming().filter_map(Result::ok) {
+            let commands = commands.clone();
+            let ctx = ctx.clone();
+            thread::spawn(move || serve_client(stream, &commands, &ctx));
+        }
+    });
+    Ok(())
+}
+
+fn serve_client(stream: LocalSocketStream, commands: &Sender<Command>, ctx: &egui::Context) {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    while matches!(reader.read_line(&mut line), Ok(read) if read > 0) {
+        let mut reply = control::handle_request(line.trim(), commands, ctx);
+        line.clear();
+        reply.push('\n');
+        if reader.get_mut().write_all(reply.as_bytes()).is_err() {
+            break;
+        }
+    }
+}
+
+/// `file` as the overlay should see it: it may be running from another folder
+pub fn input_path(file: &Path) -> Result<PathBuf, String> {
+    if file.is_relative() && !crate::is_stream(file) {
+        Ok(std::env::current_dir()
+            .map_err(|err| err.to_string())?
+            .join(file))
+    } else {
+        Ok(file.to_path_buf())
+    }
+}
+
+/// Turn `overlay_app ctl` arguments such as `seek 10` or `move 100 200` into
+/// a request line; a JSON object is passed through as is.
+fn request_line(words: &[String]) -> Result<String, String> {
+    if let [json] = words {
+        if json.trim_start().starts_with('{') {
+            return Ok(json.clone());
+        }
+    }
+
+    let number = |value: &str| {
+        value
+            .parse::<f32>()
+            .map_err(|err| format!("invalid number {:?}: {}", value, err))
+    };
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let request = match words[..] {
+        ["show"] => Request::Show,
+        ["hide"] => Request::Hide,
+        ["trigger"] => Request::Trigger,
+        ["pause"] => Request::Pause,
+        ["resume"] => Request::Resume,
+        ["quit"] => Request::Quit,
+        ["status"] => Request::Status,
+        ["seek", frame] => Request::Seek {
+            frame: frame
+                .parse()
+                .map_err(|err| format!("invalid frame {:?}: {}", frame, err))?,
+        },
+        ["play", file] => Request::Play {
+            file: input_path(Path::new(file))?,
+            x: None,
+            y: None,
+        },
+        ["play", file, x, y] => Request::Play {
+            file: input_path(Path::new(file))?,
+            x: Some(number(x)?),
+            y: Some(number(y)?),
+        },
+        ["alert", file] => Request::Alert {
+            file: input_path(Path::new(file))?,
+            caption: None,
+        },
+        ["opacity", value] => Request::Opacity {
+            value: number(value)?,
+        },
+        ["scale", value] => Request::Scale {
+            value: number(value)?,
+        },
+        ["move", x, y] => Request::Move {
+            x: number(x)?,
+            y: number(y)?,
+        },
+        _ => {
+            return Err(format!(
+                "unknown command {:?}, expected show, hide, trigger, pause, resume, \
+                 seek FRAME, play FILE [X Y], alert FILE, opacity VALUE, scale VALUE, move X Y, status, or quit",
+                words.join(" ")
+            ))
+        }
+    };
+    serde_json::to_string(&request).map_err(|err| err.to_string())
+}
+
+/// Send one command to the overlay listening on `name` and print its reply,
+/// failing if the overlay couldn't carry it out.
+pub fn run_client(name: &str, words: &[String]) -> Result<(), String> {
+    let request = request_line(words)?;
+    let socket = socket_name(name);
+    let stream = LocalSocketStream::connect(socket.as_str())
+        .map_err(|err| format!("no overlay listening on {}: {}", socket, err))?;
+
+    let reply = exchange(&mut BufReader::new(stream), &request)?;
+    println!("{}", reply);
+    if is_ok(&reply) {
+        Ok(())
+    } else {
+        Err("the overlay rejected the command".to_string())
+    }
+}
+
+/// Hand `requests` to the overlay listening on `name`, one after another,
+/// returning `false` if there's no such overlay.
+pub fn forward(name: &str, requests: &[Request]) -> Result<bool, String> {
+    let socket = socket_name(name);
+    let Ok(stream) = LocalSocketStream::connect(socket.as_str()) else {
+        return Ok(false);
+    };
+
+    let mut reader = BufReader::new(stream);
+    for request in requests {
+        let request = serde_json::to_string(request).map_err(|err| err.to_string())?;
+        let reply = exchange(&mut reader, &request)?;
+        if !is_ok(&reply) {
+            return Err(format!(
+                "the running overlay rejected {}: {}",
+                request, reply
+            ));
+        }
+    }
+    Ok(true)
+}
+
+/// write one request line and read back the reply line
+fn exchange(reader: &mut BufReader<LocalSocketStream>, request: &str) -> Result<String, String> {
+    reader
+        .get_mut()
+        .write_all(format!("{}\n", request).as_bytes())
+        .map_err(|err| err.to_string())?;
+    let mut reply = String::new();
+    reader
+        .read_line(&mut reply)
+        .map_err(|err| err.to_string())?;
+    Ok(reply.trim_end().to_string())
+}
+
+fn is_ok(reply: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(reply)
+        .is_ok_and(|reply| reply["ok"] == serde_json::Value::Bool(true))
+}
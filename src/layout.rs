@@ -0,0 +1,210 @@
+use crate::obs;
+use serde::Deserialize;
+use std::{
+    ffi::OsString,
+    io,
+    path::{Path, PathBuf},
+    process::{Child, Command},
+    sync::mpsc::channel,
+    thread,
+};
+use tracing::{info, warn};
+
+/// A `--layout` file: named scenes, each a set of overlays brought up together.
+///
+/// ```toml
+/// [[scene]]
+/// name = "starting"
+///
+/// [[scene.overlay]]
+/// input = "intro.gif"
+/// position = "bottom-right"
+/// scale = 1.5
+/// z = 1
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Layout {
+    #[serde(rename = "scene")]
+    scenes: Vec<Scene>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Scene {
+    name: String,
+    #[serde(default, rename = "overlay")]
+    overlays: Vec<Overlay>,
+}
+
+/// One overlay window; anything without a field here can go in `args`.
+#[derive(Debug, Deserialize)]
+struct Overlay {
+    input: PathBuf,
+    x: Option<f32>,
+    y: Option<f32>,
+    position: Option<String>,
+    monitor: Option<String>,
+    scale: Option<f32>,
+    opacity: Option<f32>,
+    playback: Option<String>,
+    loops: Option<u32>,
+    // overlays with a higher z are opened later, so they end up on top
+    #[serde(default)]
+    z: i32,
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+impl Overlay {
+    /// command line for an overlay_app process showing this overlay
+    fn command_args(&self, base: &Path) -> Vec<OsString> {
+        let input = if self.input.is_relative() && !crate::is_stream(&self.input) {
+            base.join(&self.input)
+        } else {
+            self.input.clone()
+        };
+
+        let mut args: Vec<OsString> = vec!["--input".into(), input.into()];
+        let mut push = |name: &str, value: Option<String>| {
+            if let Some(value) = value {
+                args.push(name.into());
+                args.push(value.into());
+            }
+        };
+        push("--x", self.x.map(|x| x.to_string()));
+        push("--y", self.y.map(|y| y.to_string()));
+        push("--position", self.position.clone());
+        push("--monitor", self.monitor.clone());
+        push("--scale", self.scale.map(|scale| scale.to_string()));
+        push("--opacity", self.opacity.map(|opacity| opacity.to_string()));
+        push("--playback", self.playback.clone());
+        push("--loops", self.loops.map(|loops| loops.to_string()));
+        args.extend(self.args.iter().map(OsString::from));
+        args
+    }
+}
+
+/// read and parse a layout file
+pub fn load(path: &Path) -> io::Result<Layout> {
+    let contents = std::fs::read_to_string(path)?;
+    let layout: Layout =
+        toml::from_str(&contents).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    if layout.scenes.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "layout has no scenes",
+        ));
+    }
+    Ok(layout)
+}
+
+/// Bring up the first scene of the layout at `path`, then switch scenes as
+/// their names (or 1-based numbers) are typed on stdin, or as OBS switches to
+/// a scene of the same name if `obs` is given. Each overlay runs as its own
+/// overlay_app process. `quit` closes every overlay; once stdin is closed
+/// (and without OBS) the current scene keeps running until its overlays exit.
+pub fn run(path: &Path, obs: Option<obs::Connection>) -> io::Result<()> {
+    let layout = load(path)?;
+    let base = path.parent().unwrap_or(Path::new(""));
+    let exe = std::env::current_exe()?;
+
+    for (idx, scene) in layout.scenes.iter().enumerate() {
+        info!(
+            "Scene {}: {} ({} overlays)",
+            idx + 1,
+            scene.name,
+            scene.overlays.len()
+        );
+    }
+
+    let mut running = start_scene(&exe, base, &layout.scenes[0])?;
+    let mut current = 0;
+
+    let (sender, commands) = channel();
+    let stdin_sender = sender.clone();
+    thread::spawn(move || {
+        for line in io::stdin().lines().map_while(Result::ok) {
+            if stdin_sender.send(Switch::Typed(line)).is_err() {
+                break;
+            }
+        }
+    });
+    if let Some(obs) = obs {
+        obs::watch(obs, move |scene| {
+            let _ = sender.send(Switch::Obs(scene.to_string()));
+        });
+    } else {
+        drop(sender);
+    }
+
+    for switch in commands {
+        let (command, typed) = match &switch {
+            Switch::Typed(line) => (line.trim(), true),
+            Switch::Obs(scene) => (scene.as_str(), false),
+        };
+        if command.is_empty() {
+            continue;
+        }
+        if typed && command == "quit" {
+            stop_scene(&mut running);
+            return Ok(());
+        }
+
+        let idx = match command.parse::<usize>() {
+            Ok(number) if typed => number
+                .checked_sub(1)
+                .filter(|idx| *idx < layout.scenes.len()),
+            _ => layout.scenes.iter().position(|scene| scene.name == command),
+        };
+        let Some(idx) = idx else {
+            // OBS scenes without a layout scene leave the overlays as they are
+            if typed {
+                warn!("no scene named {:?}", command);
+            }
+            continue;
+        };
+        if !typed && idx == current {
+            continue;
+        }
+
+        stop_scene(&mut running);
+        running = start_scene(&exe, base, &layout.scenes[idx])?;
+        current = idx;
+    }
+
+    for mut child in running {
+        child.wait()?;
+    }
+    Ok(())
+}
+
+/// a request to switch scenes
+enum Switch {
+    /// a line typed on stdin: a scene name or number, or `quit`
+    Typed(String),
+    /// OBS went live with the scene of this name
+    Obs(String),
+}
+
+fn start_scene(exe: &Path, base: &Path, scene: &Scene) -> io::Result<Vec<Child>> {
+    info!("Starting scene: {}", scene.name);
+
+    let mut overlays: Vec<&Overlay> = scene.overlays.iter().collect();
+    overlays.sort_by_key(|overlay| overlay.z);
+
+    overlays
+        .into_iter()
+        .map(|overlay| {
+            Command::new(exe)
+                .args(overlay.command_args(base))
+                .stdin(std::process::Stdio::null())
+                .spawn()
+        })
+        .collect()
+}
+
+fn stop_scene(running: &mut Vec<Child>) {
+    for mut child in running.drain(..) {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
@@ -0,0 +1,55 @@
+use clap::ValueEnum;
+use std::{fs::OpenOptions, path::Path, sync::Mutex};
+use tracing::{level_filters::LevelFilter, warn};
+use tracing_subscriber::fmt::format::FmtSpan;
+
+/// How much `--log-level` lets through, from nothing to every frame upload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    /// adds load timings, FPS, and memory use
+    Debug,
+    /// adds a line for every frame uploaded
+    Trace,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Off => LevelFilter::OFF,
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+/// Send log messages at `level` and above to stdout, or appended to `file`.
+/// Spans report how long they took when they close, so decoding and uploads
+/// can be timed at `debug` and `trace`.
+pub fn init(level: LogLevel, file: Option<&Path>) {
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(LevelFilter::from(level))
+        .with_target(false)
+        .with_span_events(FmtSpan::CLOSE);
+
+    let Some(path) = file else {
+        builder.init();
+        return;
+    };
+    match OpenOptions::new().create(true).append(true).open(path) {
+        Ok(file) => builder
+            .with_ansi(false)
+            .with_writer(Mutex::new(file))
+            .init(),
+        Err(err) => {
+            builder.init();
+            warn!("failed to open log file {}: {}", path.display(), err);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use crate::{budget, deliver, LoadingMessage};
+use rlottie::{Animation, Size, Surface};
+use std::{path::Path, sync::mpsc::Sender, time::Duration};
+use tracing::info;
+
+/// Rasterize every frame of a Lottie animation at `scale` times its native
+/// size, so vector content stays sharp instead of stretching a bitmap, or
+/// smaller if that would take up more than `max_memory` bytes.
+pub fn render(
+    path: &Path,
+    scale: f32,
+    sender: &Sender<LoadingMessage>,
+    max_memory: Option<usize>,
+) -> Result<(), String> {
+    let mut animation =
+        Animation::from_file(path).ok_or_else(|| "failed to load Lottie animation".to_string())?;
+
+    let native = animation.size();
+    let total_frames = animation.totalframe();
+    let size_at = |scale| budget::shrunk_size([native.width, native.height], scale);
+    // render smaller rather than rasterize past the budget
+    let scale = match max_memory
+        .and_then(|budget| budget::shrink_factor(budget, size_at(scale), total_frames))
+    {
+        Some(factor) => scale * factor,
+        None => scale,
+    };
+    let [width, height] = size_at(scale);
+    let size = Size { width, height };
+    let delay = Duration::from_secs_f64(1.0 / animation.framerate().max(1.0));
+
+    info!(
+        "Lottie animation: {} frames, rendering at {}x{}",
+        total_frames, size.width, size.height
+    );
+
+    deliver(sender, LoadingMessage::RenderScale(scale))?;
+    let mut surface = Surface::new(size);
+
+    for idx in 0..total_frames {
+        animation.render(idx, &mut surface);
+
+        // rlottie renders premultiplied BGRA, the texture upload expects straight RGBA
+        let pixels: Vec<u8> = surface
+            .data()
+            .iter()
+            .flat_map(|p| {
+                let unpremultiply = |c: u8| {
+                    if p.a == 0 {
+                        0
+                    } else {
+                        ((c as u32 * 255 + p.a as u32 / 2) / p.a as u32).min(255) as u8
+                    }
+                };
+                [
+                    unpremultiply(p.r),
+                    unpremultiply(p.g),
+                    unpremultiply(p.b),
+                    p.a,
+                ]
+            })
+            .collect();
+
+        deliver(
+            sender,
+            LoadingMessage::FrameReady(idx, pixels, [size.width, size.height], delay),
+        )?;
+    }
+
+    deliver(sender, LoadingMessage::LoadingComplete(total_frames))
+}
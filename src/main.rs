@@ -1,29 +1,135 @@
-use clap::Parser;
+use clap::{
+    error::ErrorKind, parser::ValueSource, CommandFactory, FromArgMatches, Parser, ValueEnum,
+};
 use eframe::{egui, NativeOptions};
-use image::{codecs::gif::GifDecoder, AnimationDecoder};
+use image::{
+    codecs::{png::PngDecoder, webp::WebPDecoder},
+    AnimationDecoder, DynamicImage,
+};
+use rand::Rng;
 use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
     fs::File,
-    path::PathBuf,
+    io::{self, BufRead, BufReader, Read},
+    net::IpAddr,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    rc::Rc,
     sync::{
-        mpsc::{channel, Receiver},
-        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Condvar, Mutex, PoisonError,
     },
     thread,
     time::{Duration, Instant},
 };
+use tracing::{debug, error, info, warn};
+
+mod atlas;
+mod audio;
+mod bench;
+mod budget;
+mod cache;
+mod clock;
+mod color;
+mod compose;
+mod control;
+mod dbus;
+mod filters;
+mod follow;
+mod fullscreen;
+mod gamepad;
+mod hooks;
+mod hotkey;
+mod http;
+mod hud;
+mod info;
+mod ipc;
+mod layout;
+mod logging;
+#[cfg(feature = "lottie")]
+mod lottie;
+mod midi;
+mod mqtt;
+mod obs;
+mod osc;
+mod particles;
+mod pet;
+mod placement;
+mod plugin;
+mod remote;
+mod ring;
+mod schedule;
+mod script;
+#[cfg(feature = "shader")]
+mod shader;
+mod sound;
+mod state;
+mod status_json;
+mod svg;
+mod taskbar;
+mod transform;
+mod transition;
+mod tray;
+mod twitch;
+mod validate;
+mod video;
+mod webhook;
+mod websocket;
 
 macro_rules! log_time {
     ($start:expr, $msg:expr) => {
-        println!("{}: {:.2?}", $msg, $start.elapsed());
+        tracing::debug!("{}: {:.2?}", $msg, $start.elapsed());
     };
 }
 
 /// simple GIF overlay viewer
 #[derive(Parser)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, subcommand_negates_reqs = true)]
 struct Args {
-    #[arg(short, long)]
-    gif: PathBuf,
+    #[command(subcommand)]
+    action: Option<Action>,
+
+    /// animation, image, or image sequence directory to display (`-` reads stdin, http(s) URLs are downloaded);
+    /// repeat to play several inputs back to back
+    #[arg(
+        short,
+        long,
+        visible_alias = "gif",
+        short_alias = 'g',
+        required_unless_present_any = ["playlist", "idle", "dir", "layout", "particles"]
+    )]
+    input: Vec<PathBuf>,
+
+    /// text file listing inputs to play in order, one per line
+    #[arg(long)]
+    playlist: Option<PathBuf>,
+
+    /// folder of animations to pick from at random, one per loop
+    #[arg(long)]
+    dir: Option<PathBuf>,
+
+    /// switch to the next playlist entry after this many seconds, even mid-loop
+    #[arg(long)]
+    interval: Option<f32>,
+
+    /// TOML file describing scenes of several overlays to open at once; type a
+    /// scene's name or number on stdin to switch to it
+    #[arg(long, conflicts_with_all = ["input", "playlist", "dir", "idle"])]
+    layout: Option<PathBuf>,
+
+    /// animation looped while nothing is triggered
+    #[arg(long, requires = "active", conflicts_with_all = ["input", "playlist", "dir"])]
+    idle: Option<PathBuf>,
+
+    /// animation played once per trigger (`T` key or `trigger` on stdin), then back to idle
+    #[arg(long, requires = "idle")]
+    active: Option<PathBuf>,
+
+    /// blend between playlist entries over this many milliseconds
+    #[arg(long)]
+    crossfade: Option<u64>,
 
     #[arg(short, long, default_value_t = 1.0)]
     scale: f32,
@@ -31,32 +137,1281 @@ struct Args {
     #[arg(short, long, default_value_t = 1.0)]
     opacity: f32,
 
-    #[arg(long, default_value_t = 200)]
-    width: u32,
+    /// make pixels of this color transparent, e.g. "#00FF00" for a green screen
+    #[arg(long, value_parser = color::parse_color)]
+    chroma_key: Option<egui::Color32>,
+
+    /// how far a pixel may be from the `--chroma-key` color and still be
+    /// keyed out, from 0.0 (exact match) to 1.0
+    #[arg(long, default_value_t = 0.1)]
+    chroma_tolerance: f32,
+
+    /// multiply the animation by this color, e.g. "#FF8000" to turn a white
+    /// asset orange
+    #[arg(long, value_parser = color::parse_color)]
+    tint: Option<egui::Color32>,
+
+    /// rotate the hue of every frame by this many degrees
+    #[arg(long, allow_hyphen_values = true)]
+    hue_shift: Option<f32>,
+
+    /// restyle the animation (cycle through the filters at runtime with `F`)
+    #[arg(long, value_enum, default_value_t = filters::Filter::None)]
+    filter: filters::Filter,
+
+    /// brighten (up to 1.0) or darken (down to -1.0) the animation
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    brightness: f32,
+
+    /// spread colors further apart (above 1.0) or closer together (below)
+    #[arg(long, default_value_t = 1.0)]
+    contrast: f32,
+
+    /// brighten (above 1.0) or darken (below) the midtones
+    #[arg(long, default_value_t = 1.0)]
+    gamma: f32,
+
+    /// only show this part of each frame, as `X,Y,WIDTH,HEIGHT` in pixels,
+    /// e.g. to cut off padding or a watermark
+    #[arg(long, value_parser = transform::parse_crop)]
+    crop: Option<egui::Rect>,
+
+    /// clip the animation to a shape: `circle`, or `rounded:<RADIUS>` for
+    /// corners rounded off by RADIUS points
+    #[arg(long, default_value = "none", value_parser = transform::parse_mask)]
+    mask: transform::Mask,
+
+    /// draw a drop shadow below and to the right of the animation
+    #[arg(long)]
+    shadow: bool,
+
+    /// draw an outline around the animation's opaque pixels, as
+    /// `COLOR,WIDTH` with the width in pixels, e.g. "#000000,2"
+    #[arg(long, value_parser = filters::parse_outline)]
+    outline: Option<filters::Outline>,
+
+    /// draw frames through the fragment shader in this WGSL file (needs a
+    /// build with `--features shader`)
+    #[arg(long)]
+    shader: Option<PathBuf>,
+
+    /// turn the animation clockwise by this many degrees
+    #[arg(long, value_enum, default_value_t = transform::Rotation::None)]
+    rotate: transform::Rotation,
+
+    /// mirror the animation left to right
+    #[arg(long)]
+    flip_h: bool,
+
+    /// mirror the animation top to bottom
+    #[arg(long)]
+    flip_v: bool,
+
+    /// keep turning the animation around its center at this many degrees per
+    /// second (negative spins counter-clockwise), e.g. for loading spinners
+    #[arg(long, allow_hyphen_values = true)]
+    spin: Option<f32>,
+
+    /// slowly pan and zoom across still images, taking this many seconds to
+    /// get from `--ken-burns-from` to `--ken-burns-to` and as long to go back
+    #[arg(long)]
+    ken_burns: Option<f32>,
+
+    /// where `--ken-burns` starts, as `X,Y,ZOOM` with the point from 0 to 1
+    /// across and down the image
+    #[arg(long, default_value = "0.5,0.5,1", value_parser = transform::parse_focus)]
+    ken_burns_from: transform::Focus,
+
+    /// where `--ken-burns` ends up, as `X,Y,ZOOM`
+    #[arg(long, default_value = "0.5,0.5,1.3", value_parser = transform::parse_focus)]
+    ken_burns_to: transform::Focus,
+
+    /// draw a rounded backdrop of this color behind the animation, e.g.
+    /// "#202020" or "#00000080" for a translucent one
+    #[arg(long, value_parser = color::parse_color)]
+    background: Option<egui::Color32>,
+
+    /// draw falling snow, confetti, or sparks across the window, on top of the
+    /// animation or on their own when there's no input
+    #[arg(long, value_enum)]
+    particles: Option<particles::Style>,
+
+    /// how many `--particles` to spawn, relative to the default of 1
+    #[arg(long, default_value_t = 1.0)]
+    particle_density: f32,
+
+    /// sound to play along with the animation, e.g. an alert chime (WAV, OGG,
+    /// FLAC or MP3)
+    #[arg(long)]
+    sound: Option<PathBuf>,
+
+    /// when the `--sound` plays
+    #[arg(long, value_enum, default_value_t = sound::PlayOn::Start)]
+    sound_on: sound::PlayOn,
+
+    /// `--sound` volume, where 1 plays the file as is
+    #[arg(long, default_value_t = 1.0)]
+    volume: f32,
+
+    /// make the animation bounce bigger or fade back and forth with the
+    /// loudness of the system audio
+    #[arg(long, value_enum)]
+    audio_react: Option<audio::Target>,
+
+    /// how strongly `--audio-react` follows the sound, from 0 to 1: the most the
+    /// animation grows by, or how far it fades while quiet
+    #[arg(long, default_value_t = 0.3)]
+    audio_amount: f32,
+
+    /// amplify the captured audio before `--audio-react` uses it; raise it for
+    /// quiet sources
+    #[arg(long, default_value_t = 4.0)]
+    audio_gain: f32,
+
+    /// audio input to listen to for `--audio-react` instead of the default,
+    /// e.g. a microphone or a PulseAudio monitor (a wrong name lists them)
+    #[arg(long)]
+    audio_device: Option<String>,
+
+    /// show a countdown under the animation, as `MM:SS` or `HH:MM:SS`, e.g.
+    /// "10:00" for a "starting soon" scene
+    #[arg(long, value_parser = clock::parse_timer)]
+    timer: Option<Duration>,
+
+    /// show the time of day under the animation
+    #[arg(long, conflicts_with = "timer")]
+    clock: bool,
+
+    /// strftime format for `--clock`, e.g. "%H:%M:%S" or "%I:%M %p"
+    #[arg(long, default_value = "%H:%M", value_parser = clock::parse_format)]
+    clock_format: String,
+
+    /// text size of the `--timer` or `--clock`, in points
+    #[arg(long, default_value_t = 32.0)]
+    clock_size: f32,
+
+    /// text color of the `--timer` or `--clock`
+    #[arg(long, default_value = "#FFFFFF", value_parser = color::parse_color)]
+    clock_color: egui::Color32,
+
+    /// text size of alert captions, e.g. from `--webhook-rules`, in points
+    #[arg(long, default_value_t = 24.0)]
+    caption_size: f32,
+
+    /// text color of alert captions
+    #[arg(long, default_value = "#FFFFFF", value_parser = color::parse_color)]
+    caption_color: egui::Color32,
+
+    /// fade in over this many milliseconds on start and whenever the overlay is
+    /// shown, and fade out when it's hidden or closed (0 pops instantly); also
+    /// sets how long `--enter`/`--exit` take
+    #[arg(long, default_value_t = 0)]
+    fade: u64,
+
+    /// how the animation comes in on start and whenever the overlay is shown
+    #[arg(long, value_enum)]
+    enter: Option<transition::Transition>,
+
+    /// how the animation goes away when the overlay is hidden or closed
+    #[arg(long, value_enum)]
+    exit: Option<transition::Transition>,
+
+    /// texture filtering when the animation is scaled; `nearest` keeps pixel
+    /// art crisp
+    #[arg(long, value_enum, default_value_t = Sampling::Linear)]
+    sampling: Sampling,
+
+    /// key that must be held for the scroll wheel to resize the overlay
+    #[arg(long, value_enum, default_value_t = ZoomModifier::None)]
+    zoom_modifier: ZoomModifier,
+
+    /// window width; without `--width`/`--height` the window is sized to the animation
+    #[arg(long)]
+    width: Option<u32>,
+
+    /// window height
+    #[arg(long)]
+    height: Option<u32>,
+
+    /// display to open the overlay on, by index or name (listed at startup)
+    #[arg(long)]
+    monitor: Option<String>,
+
+    /// horizontal window position in points from the left of the screen
+    #[arg(long, requires = "y")]
+    x: Option<f32>,
+
+    /// vertical window position in points from the top of the screen
+    #[arg(long, requires = "x")]
+    y: Option<f32>,
+
+    /// place the overlay at a corner or the center of the screen
+    #[arg(long, value_enum, conflicts_with_all = ["x", "y"])]
+    position: Option<placement::Anchor>,
+
+    /// distance in points between the overlay and the screen edge for `--position`
+    #[arg(long, default_value_t = 16.0)]
+    margin: f32,
+
+    /// don't restore the window position, scale and opacity from the last run
+    #[arg(long)]
+    no_restore: bool,
+
+    /// move the overlay along with the mouse cursor, like a desktop pet
+    #[arg(long, conflicts_with_all = ["fullscreen", "position", "x", "y"])]
+    follow_cursor: bool,
+
+    /// where the overlay sits relative to the cursor in `--follow-cursor`
+    /// mode, as `X,Y` in points
+    #[arg(long, default_value = "24,24", value_parser = follow::parse_offset, allow_hyphen_values = true)]
+    follow_offset: egui::Vec2,
+
+    /// how far the overlay trails behind the cursor, from 0 (stuck to it) to
+    /// 0.99 (very lazy)
+    #[arg(long, default_value_t = 0.85)]
+    follow_smoothing: f32,
+
+    /// wander back and forth along the bottom of the screen, like a desktop pet
+    #[arg(long, conflicts_with_all = ["follow_cursor", "fullscreen", "position", "x", "y"])]
+    pet: bool,
+
+    /// walking speed in `--pet` mode, in points per second
+    #[arg(long, default_value_t = 60.0)]
+    pet_speed: f32,
+
+    /// longest rest between walks in `--pet` mode, in seconds (0 never stops)
+    #[arg(long, default_value_t = 3.0)]
+    pet_pause: f32,
+
+    /// height above the bottom of the screen the pet walks at, e.g. the taskbar height
+    #[arg(long, default_value_t = 48.0)]
+    pet_floor: f32,
+
+    /// start with the window locked in place so it can't be dragged by accident
+    /// (toggle at runtime with `L`)
+    #[arg(long)]
+    locked: bool,
+
+    /// snap to screen edges and corners when a dragged window is dropped within
+    /// this many points of them (0 disables snapping)
+    #[arg(long, default_value_t = 20.0)]
+    snap: f32,
+
+    /// cover the whole monitor, with the animation scaled to fit and centered;
+    /// clicks pass through to whatever is underneath
+    #[arg(long, conflicts_with_all = ["x", "y", "position", "width", "height"])]
+    fullscreen: bool,
+
+    /// keep the overlay out of the taskbar/dock and the Alt-Tab list
+    #[arg(long)]
+    skip_taskbar: bool,
+
+    /// hide while another application is fullscreen (a game, a video) and come
+    /// back once it leaves fullscreen or loses focus; Windows and X11 only
+    #[arg(long, conflicts_with = "fullscreen")]
+    hide_on_fullscreen: bool,
+
+    /// system-wide shortcut that hides/shows the overlay even when it isn't
+    /// focused, e.g. "ctrl+shift+KeyH"
+    #[arg(long)]
+    hide_hotkey: Option<String>,
+
+    /// TOML file whose `[hotkeys]` section binds system-wide key combos to
+    /// toggle, next, opacity-up, opacity-down, pause, trigger, hud, or quit
+    #[arg(long, value_name = "FILE")]
+    hotkeys: Option<PathBuf>,
+
+    /// serve an HTTP control API on this local port, e.g. for a Stream Deck:
+    /// `/show`, `/hide`, `/trigger`, `/play?file=PATH`, `/opacity?value=0.5`,
+    /// `/status`
+    #[arg(long)]
+    http_port: Option<u16>,
+
+    /// TOML rules turning JSON payloads POSTed to the `--http-port` API's
+    /// `/webhook` (e.g. from Ko-fi or GitHub) into alerts with captions
+    #[arg(long, value_name = "FILE", requires = "http_port")]
+    webhook_rules: Option<PathBuf>,
+
+    /// listen for commands from `overlay_app ctl` on a local socket (a named
+    /// pipe on Windows), optionally under a name of its own so several
+    /// overlays can be told apart
+    #[arg(long, value_name = "NAME", num_args = 0..=1, default_missing_value = "overlay_app")]
+    ipc: Option<String>,
+
+    /// if an overlay with the same `--ipc` name is already running, hand it
+    /// this `--input`, `--opacity`, `--scale`, and `--x`/`--y` instead of
+    /// opening another window
+    #[arg(long)]
+    single_instance: bool,
+
+    /// accept the `--ipc` JSON commands over a WebSocket on this local port,
+    /// e.g. `{"cmd":"play","file":"alert.gif","x":100,"y":200}` from a web
+    /// dashboard or chat bot
+    #[arg(long)]
+    websocket_port: Option<u16>,
+
+    /// MQTT broker to take commands from, as `[USER:PASSWORD@]HOST[:PORT]`
+    #[arg(long, value_parser = mqtt::parse_broker)]
+    mqtt_broker: Option<mqtt::Broker>,
+
+    /// MQTT topic to subscribe to: messages `show`/`on`, `hide`/`off`,
+    /// `trigger`, `pause`, `resume`, a file or URL to switch to, or an `--ipc`
+    /// JSON command
+    #[arg(long, default_value = "overlay_app/command")]
+    mqtt_topic: String,
+
+    /// listen for OSC messages on this UDP port, e.g. from TouchOSC or
+    /// Ableton: `/opacity`, `/scale`, `/position X Y`, `/play`, `/pause`,
+    /// `/seek`, `/trigger`, `/load PATH`, `/show`, `/hide`
+    #[arg(long)]
+    osc_port: Option<u16>,
+
+    /// address to listen for OSC on; `0.0.0.0` lets other machines on the
+    /// network, like a tablet running TouchOSC, reach it
+    #[arg(long, default_value = "127.0.0.1", requires = "osc_port")]
+    osc_bind: IpAddr,
+
+    /// offer a D-Bus service on the session bus (Linux only) with `Show`,
+    /// `Hide`, `Load`, `SetOpacity` and more, plus the overlay's state as
+    /// properties
+    #[arg(long)]
+    dbus: bool,
+
+    /// TOML file mapping a MIDI controller's notes to actions or alerts and
+    /// its faders and knobs to opacity or scale
+    #[arg(long, value_name = "FILE")]
+    midi: Option<PathBuf>,
+
+    /// Lua script whose `on_start`, `on_frame`, `on_loop`, and `on_trigger`
+    /// callbacks can move the window, change opacity and scale, and swap
+    /// animations through the `overlay` table
+    #[arg(long, value_name = "FILE")]
+    script: Option<PathBuf>,
+
+    /// shared library adding a frame filter or event hooks through the
+    /// overlay's C plugin interface; repeat for several
+    #[arg(long = "plugin", value_name = "FILE")]
+    plugins: Vec<PathBuf>,
+
+    /// TOML file mapping gamepad buttons to animations played once as alerts,
+    /// or to actions
+    #[arg(long, value_name = "FILE")]
+    gamepad: Option<PathBuf>,
+
+    /// TOML file mapping Twitch events (follows, subs, gifts, raids, cheers,
+    /// channel point redemptions, chat commands) to animations played once
+    /// as alerts
+    #[arg(long, value_name = "FILE")]
+    twitch: Option<PathBuf>,
+
+    /// follow OBS's program scene over obs-websocket 5 at this address: the
+    /// overlay is only shown during `--obs-scenes`, and a `--layout` switches
+    /// to the scene of the same name
+    #[arg(long, value_name = "URL", num_args = 0..=1, default_missing_value = "ws://127.0.0.1:4455")]
+    obs: Option<String>,
+
+    /// obs-websocket server password
+    #[arg(long, requires = "obs")]
+    obs_password: Option<String>,
+
+    /// OBS scenes to show the overlay in, e.g. "Gameplay,Just Chatting"
+    #[arg(long, requires = "obs", value_delimiter = ',')]
+    obs_scenes: Vec<String>,
+
+    /// don't put an icon in the system tray
+    #[arg(long)]
+    no_tray: bool,
+
+    /// keep the overlay above other windows (toggle at runtime with `P`)
+    #[arg(long)]
+    always_on_top: bool,
+
+    /// treat the input as a sprite sheet with this many columns
+    #[arg(long)]
+    columns: Option<u32>,
+
+    /// number of rows in the sprite sheet
+    #[arg(long, default_value_t = 1, requires = "columns")]
+    rows: u32,
+
+    /// playback rate for sprite sheets and image sequence directories
+    #[arg(long, default_value_t = 12.0)]
+    fps: f32,
+
+    /// direction frames are played in
+    #[arg(long, value_enum, default_value_t = Playback::Forward)]
+    playback: Playback,
+
+    /// how long each frame is held in shuffle mode, and how long still images
+    /// stay up in a playlist, in milliseconds
+    #[arg(long)]
+    hold: Option<u64>,
+
+    /// delay in milliseconds for frames that report none (0ms), which many
+    /// GIF encoders emit
+    #[arg(long)]
+    force_delay: Option<u64>,
+
+    /// what to do with frames that fail to decode, as damaged GIFs from
+    /// screen recorders often have
+    #[arg(long, value_enum, default_value_t = BadFrames::Skip)]
+    bad_frames: BadFrames,
+
+    /// shrink an input's frames while decoding if they'd take up more than
+    /// this many megabytes
+    #[arg(long, value_name = "MB")]
+    max_memory: Option<u64>,
+
+    /// decode frames at their full size even when they're shown smaller
+    /// (`--scale` below 1, or `--fullscreen` on a smaller screen), so zooming
+    /// in later stays sharp
+    #[arg(long)]
+    full_size: bool,
+
+    /// decode GIF, WebP, and APNG frames as they play, keeping only this many
+    /// decoded ahead, for animations too big to hold in memory; seeking and
+    /// reverse playback don't apply to them
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with_all = ["start_frame", "end_frame"]
+    )]
+    stream_frames: Option<u32>,
+
+    /// what decodes GIFs, videos, and AVIF sequences
+    #[arg(long, value_enum, default_value_t = Decoder::Builtin)]
+    decoder: Decoder,
+
+    /// keep decoded frames on disk, so the next launch with the same input
+    /// and options skips decoding
+    #[arg(long)]
+    cache: bool,
+
+    /// only show the overlay during these daily windows, e.g. "09:00-17:00"
+    /// (comma-separate several windows)
+    #[arg(long)]
+    schedule: Option<schedule::Schedule>,
+
+    /// play the animation this many times, then exit (or whatever
+    /// `--on-complete` says)
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    loops: Option<u32>,
+
+    /// what to do once playback finishes; implies `--loops 1` when given alone
+    #[arg(long, value_enum)]
+    on_complete: Option<OnComplete>,
+
+    /// run a shell command each time playback reaches a frame, as
+    /// `FRAME:COMMAND`; repeat for several frames
+    #[arg(long, value_name = "FRAME:COMMAND")]
+    on_frame_cmd: Vec<hooks::FrameHook>,
+
+    /// show the frame rate, loading progress, memory use, and a frame timing
+    /// graph over the animation from the start; `H` toggles it
+    #[arg(long)]
+    hud: bool,
+
+    /// print loading progress, FPS, memory use, the current frame, and state
+    /// changes as JSON lines for wrapper tools
+    #[arg(long)]
+    status_json: bool,
+
+    /// least important log messages to show; `debug` adds load timings
+    #[arg(long, value_enum, default_value_t = logging::LogLevel::Info)]
+    log_level: logging::LogLevel,
+
+    /// append log messages to this file instead of printing them
+    #[arg(long, value_name = "FILE")]
+    log_file: Option<PathBuf>,
+
+    /// first frame of the looped range (0-based)
+    #[arg(long, default_value_t = 0)]
+    start_frame: usize,
+
+    /// last frame of the looped range, inclusive
+    #[arg(long)]
+    end_frame: Option<usize>,
+}
+
+#[derive(clap::Subcommand)]
+enum Action {
+    /// send a command to an overlay started with `--ipc`, e.g. `pause`,
+    /// `seek 10`, `play alert.gif`, `move 100 200`, or `quit`, and print its
+    /// reply
+    Ctl {
+        /// the `--ipc` name of the overlay
+        #[arg(long, default_value = "overlay_app")]
+        name: String,
+
+        /// command and its arguments, or a JSON request like `{"cmd":"pause"}`
+        #[arg(required = true, num_args = 1.., allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+    /// decode inputs without opening a window and print their frame count,
+    /// size, duration, and decoded memory use, exiting with an error if any
+    /// can't be played
+    Info {
+        /// files to check; `--columns`, `--fps`, `--force-delay`, and
+        /// `--bad-frames` given before `info` apply
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+
+        /// print one JSON object per input instead
+        #[arg(long)]
+        json: bool,
+    },
+    /// decode inputs without opening a window, several times over, and print
+    /// how long they took per frame, throughput, and decoded memory use
+    Bench {
+        /// files to time; loading options given before `bench` apply
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+
+        /// times to decode each input, reporting the median run
+        #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+        runs: u32,
+
+        /// also time turning the frames into textures, with the color
+        /// effects given before `bench`, in a context with no window
+        #[arg(long)]
+        upload: bool,
+
+        /// print one JSON object per input instead
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Playback {
+    Forward,
+    Reverse,
+    /// play forward then backward, bouncing at both ends
+    Pingpong,
+    /// jump to a random frame each step
+    Shuffle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum BadFrames {
+    /// leave them out and play the rest
+    Skip,
+    /// show the frame before in their place, keeping the timing intact
+    Repeat,
+    /// stop loading at the first one, playing what came before
+    Stop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Decoder {
+    /// the `image` crate, decompressing GIF frames in parallel; videos and
+    /// AVIF through ffmpeg in software
+    Builtin,
+    /// ffmpeg for GIFs as well, and hardware decoding of videos and AVIF
+    /// where the GPU supports it
+    Ffmpeg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OnComplete {
+    /// close the overlay
+    Exit,
+    /// stop drawing and let clicks through, but keep running
+    Hide,
+    /// stay on the final frame
+    HoldLast,
+    /// start over as if no limit was set
+    Loop,
+}
+
+/// How textures are filtered when drawn larger or smaller than they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Sampling {
+    /// smooth, the default
+    Linear,
+    /// blocky, for pixel art
+    Nearest,
+}
+
+impl Sampling {
+    fn texture_options(self) -> egui::TextureOptions {
+        match self {
+            Sampling::Linear => egui::TextureOptions::LINEAR,
+            Sampling::Nearest => egui::TextureOptions::NEAREST,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ZoomModifier {
+    /// any scroll over the overlay resizes it
+    None,
+    Ctrl,
+    Shift,
+    Alt,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SpriteSheet {
+    columns: u32,
+    rows: u32,
+}
+
+/// settings shared by every loader thread
+#[derive(Debug, Clone, Copy)]
+struct LoaderOptions {
+    scale: f32,
+    sprite_sheet: Option<SpriteSheet>,
+    // delay for sources without their own timing (sprite sheets, sequences)
+    frame_delay: Duration,
+    // replaces the 0ms delays some encoders write into every frame
+    zero_delay: Option<Duration>,
+    bad_frames: BadFrames,
+    // bytes the decoded frames of one input may take up
+    max_memory: Option<usize>,
+    // frames decoded ahead of playback instead of all of them
+    stream_frames: Option<usize>,
+    downscale: Downscale,
+    decoder: Decoder,
+    // `--cache` decoded frames on disk
+    cache: bool,
+}
+
+/// How frames shown smaller than they are get shrunk while decoding, saving
+/// texture memory and upload time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Downscale {
+    /// `--full-size`, or zoomed into by `--ken-burns` or a fullscreen `--crop`
+    Off,
+    /// to `--scale` times their size
+    ToScale,
+    /// to fit the `--fullscreen` screen, if it's known yet, turned a quarter
+    /// if `rotated`
+    ToScreen {
+        screen: Option<egui::Vec2>,
+        rotated: bool,
+    },
+}
+
+impl LoaderOptions {
+    fn new(args: &Args, monitor: Option<&placement::Monitor>) -> Self {
+        let downscale = if args.full_size || args.ken_burns.is_some() {
+            Downscale::Off
+        } else if !args.fullscreen {
+            Downscale::ToScale
+        } else if args.crop.is_some() {
+            Downscale::Off
+        } else {
+            Downscale::ToScreen {
+                screen: monitor.map(|monitor| monitor.rect.size()),
+                rotated: args.rotate.degrees() % 180 == 90,
+            }
+        };
+        Self {
+            // ensure scale is positive
+            scale: args.scale.max(0.1),
+            sprite_sheet: args.columns.map(|columns| SpriteSheet {
+                columns,
+                rows: args.rows,
+            }),
+            frame_delay: Duration::from_secs_f32(1.0 / args.fps.max(0.1)),
+            zero_delay: args.force_delay.map(Duration::from_millis),
+            bad_frames: args.bad_frames,
+            max_memory: args.max_memory.map(|mb| mb as usize * 1024 * 1024),
+            stream_frames: args.stream_frames.map(|frames| frames as usize),
+            downscale,
+            decoder: args.decoder,
+            cache: args.cache,
+        }
+    }
+
+    /// What frames of `size` pixels can be shrunk by while decoding and still
+    /// have a pixel for every one they cover on screen, or `None` if nothing.
+    fn downscale_factor(&self, size: [usize; 2], ctx: &egui::Context) -> Option<f32> {
+        let pixels_per_point = ctx.pixels_per_point();
+        let factor = match self.downscale {
+            Downscale::Off => return None,
+            Downscale::ToScale => self.scale * pixels_per_point,
+            Downscale::ToScreen { screen, rotated } => {
+                // without `--monitor`, the screen is known once the window is open
+                let screen = screen.or_else(|| ctx.input(|i| i.viewport().monitor_size))?;
+                let [width, height] = if rotated { [size[1], size[0]] } else { size };
+                let screen = screen * pixels_per_point;
+                (screen.x / width as f32).min(screen.y / height as f32)
+            }
+        };
+        (factor < 1.0).then_some(factor)
+    }
+}
+
+/// file extensions picked up by `--dir`
+const ANIMATION_EXTENSIONS: &[&str] = &[
+    "gif", "webp", "png", "apng", "avif", "mp4", "m4v", "mov", "webm", "mkv", "svg", "json",
+];
+
+/// list the animations in `dir`, sorted so the log output is stable
+fn read_animation_dir(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| {
+                    ANIMATION_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str())
+                })
+        })
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// playlist entry to play after `current`: the next one in order, or a random
+/// different one when shuffling
+fn pick_next_index(len: usize, current: usize, shuffle: bool) -> usize {
+    if shuffle && len > 1 {
+        // skip over `current` so the same entry never plays twice in a row
+        let pick = rand::thread_rng().gen_range(0..len - 1);
+        if pick >= current {
+            pick + 1
+        } else {
+            pick
+        }
+    } else {
+        (current + 1) % len.max(1)
+    }
+}
+
+/// The frame to play after `current` in `playback` order, and whether
+/// playback is heading backward once there, or `None` to hold on `current`.
+/// While `frames` are still loading, the frame after the newest one, or a gap
+/// ahead of playback, is waited for rather than looping back early; frames
+/// behind playback, and any missing once loading is over, are skipped.
+fn pick_next_frame<T>(
+    frames: &[Option<T>],
+    loading_complete: bool,
+    current: usize,
+    playback: Playback,
+    backward: bool,
+) -> Option<(usize, bool)> {
+    let len = frames.len();
+    if len == 0 {
+        return None;
+    }
+    let loaded = |idx: usize| frames.get(idx).is_some_and(Option::is_some);
+
+    if playback == Playback::Shuffle {
+        let loaded: Vec<usize> = (0..len)
+            .filter(|&idx| idx != current && loaded(idx))
+            .collect();
+        if loaded.is_empty() {
+            return Some((current, backward));
+        }
+        let pick = loaded[rand::thread_rng().gen_range(0..loaded.len())];
+        return Some((pick, backward));
+    }
+
+    let mut next = current;
+    let mut backward = backward;
+    // ping-pong may pass over every frame twice
+    for _ in 0..2 * len {
+        // the last frame is only known to be last once loading is over
+        let at_end = next + 1 >= len && loading_complete;
+        (next, backward) = match playback {
+            Playback::Forward if at_end => (0, false),
+            Playback::Forward => (next + 1, false),
+            // reverse playback can't wait for the end, so while loading it
+            // wraps around to the last frame loaded so far
+            Playback::Reverse => (next.checked_sub(1).unwrap_or(len - 1), true),
+            Playback::Pingpong if len == 1 && loading_complete => (0, false),
+            Playback::Pingpong => {
+                let backward = next > 0 && (at_end || backward);
+                if backward {
+                    (next - 1, true)
+                } else {
+                    (next + 1, false)
+                }
+            }
+            Playback::Shuffle => unreachable!("picked above"),
+        };
+        if loaded(next) {
+            return Some((next, backward));
+        }
+        if !loading_complete && !backward {
+            return None;
+        }
+    }
+    None
+}
+
+/// read a playlist file: one path or URL per line, `#` starts a comment
+fn read_playlist(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let base = path.parent().unwrap_or(Path::new(""));
+    Ok(std::fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let entry = PathBuf::from(line);
+            // relative entries are resolved against the playlist's directory
+            if entry.is_relative() && !is_stream(&entry) {
+                base.join(entry)
+            } else {
+                entry
+            }
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputFormat {
+    Gif,
+    WebP,
+    Png,
+    Video,
+    /// AVIF image sequence, decoded through ffmpeg to keep per-frame timing
+    Avif,
+    /// Lottie JSON exported from After Effects
+    Lottie,
+    /// vector image, re-rasterized whenever the scale changes
+    Svg,
+    /// any other format `image` can decode, shown as a single still frame
+    Image,
+}
+
+impl InputFormat {
+    /// pick a decoder from the file signature, falling back to the extension
+    fn detect(path: &Path, header: &[u8]) -> Self {
+        Self::recognize(path, header).unwrap_or(Self::Gif)
+    }
+
+    /// like `detect`, but `None` for files that neither the signature nor
+    /// the extension says the overlay can play
+    fn recognize(path: &Path, header: &[u8]) -> Option<Self> {
+        Self::from_signature(header).or_else(|| Self::from_extension(path))
+    }
+
+    fn from_signature(header: &[u8]) -> Option<Self> {
+        if header.starts_with(b"GIF87a") || header.starts_with(b"GIF89a") {
+            return Some(Self::Gif);
+        }
+        if header.len() >= 12 && &header[0..4] == b"RIFF" && &header[8..12] == b"WEBP" {
+            return Some(Self::WebP);
+        }
+        if header.starts_with(b"\x89PNG\r\n\x1a\n") {
+            return Some(Self::Png);
+        }
+        if header.len() >= 12
+            && &header[4..8] == b"ftyp"
+            && matches!(&header[8..12], b"avis" | b"avif")
+        {
+            return Some(Self::Avif);
+        }
+        // mp4/mov `ftyp` box, or the EBML header shared by webm/mkv
+        if (header.len() >= 8 && &header[4..8] == b"ftyp")
+            || header.starts_with(&[0x1a, 0x45, 0xdf, 0xa3])
+        {
+            return Some(Self::Video);
+        }
+        if header.starts_with(b"<svg") {
+            return Some(Self::Svg);
+        }
+        if image::guess_format(header).is_ok() {
+            return Some(Self::Image);
+        }
+        None
+    }
+
+    fn from_extension(path: &Path) -> Option<Self> {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("gif") => Some(Self::Gif),
+            Some("webp") => Some(Self::WebP),
+            Some("png" | "apng") => Some(Self::Png),
+            Some("mp4" | "m4v" | "mov" | "webm" | "mkv") => Some(Self::Video),
+            Some("avif") => Some(Self::Avif),
+            Some("json") => Some(Self::Lottie),
+            Some("svg" | "svgz") => Some(Self::Svg),
+            Some(ext) if image::ImageFormat::from_extension(ext).is_some() => Some(Self::Image),
+            _ => None,
+        }
+    }
+
+    /// formats decoded by external tools or libraries that open the file themselves
+    fn needs_path(self) -> bool {
+        matches!(self, Self::Video | Self::Avif | Self::Lottie | Self::Svg)
+    }
+}
+
+/// window size used until the first frame arrives, or when only one of
+/// `--width`/`--height` is given
+const DEFAULT_WINDOW_SIZE: u32 = 200;
+
+/// scroll distance in points that doubles (or halves) the scale
+const ZOOM_SCROLL_PER_DOUBLING: f32 = 400.0;
+/// range the scroll wheel can resize the overlay within
+const MIN_SCALE: f32 = 0.1;
+const MAX_SCALE: f32 = 10.0;
+
+/// how far `--shadow` falls below and to the right, in points, and how dark it is
+const SHADOW_OFFSET: f32 = 4.0;
+const SHADOW_OPACITY: f32 = 0.5;
+
+/// corner radius and padding of the `--background` backdrop, in points
+const BACKDROP_ROUNDING: f32 = 8.0;
+const BACKDROP_PADDING: f32 = 8.0;
+
+/// limits of the runtime brightness/contrast/gamma controls
+const BRIGHTNESS_RANGE: RangeInclusive<f32> = -1.0..=1.0;
+const CONTRAST_RANGE: RangeInclusive<f32> = 0.0..=3.0;
+const GAMMA_RANGE: RangeInclusive<f32> = 0.1..=3.0;
+
+/// how much `-`/`+` change the opacity by, and how far down they go
+const OPACITY_STEP: f32 = 0.1;
+const MIN_OPACITY: f32 = 0.1;
+
+/// how often the window is moved in `--follow-cursor` and `--pet` modes
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(16);
+
+/// how long the window must stay put after a drag before it's snapped
+const SNAP_SETTLE_TIME: Duration = Duration::from_millis(150);
+
+/// how often `--hide-on-fullscreen` checks the focused window
+const FULLSCREEN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// how long `--enter`/`--exit` transitions take when `--fade` isn't given
+const DEFAULT_TRANSITION_TIME: Duration = Duration::from_millis(400);
+
+/// how often the window position is checked for saving
+const STATE_SAVE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// most decoded frames uploaded as textures in one UI update, and how long
+/// uploading may take, so a big GIF coming in doesn't stall drawing
+const MAX_UPLOADS_PER_UPDATE: usize = 8;
+const UPLOAD_BUDGET: Duration = Duration::from_millis(4);
+
+/// how far playback may fall behind before it starts over from the frame
+/// it's on, rather than skipping frames to catch up
+const MAX_PLAYBACK_LAG: Duration = Duration::from_secs(1);
+
+/// memory the operating system has the overlay's process holding in RAM, if
+/// it says
+fn resident_bytes() -> Option<usize> {
+    memory_stats::memory_stats().map(|stats| stats.physical_mem)
+}
+
+/// the input whose saved window state applies to this run; stdin has none
+fn state_input(args: &Args) -> Option<PathBuf> {
+    args.input
+        .first()
+        .or(args.playlist.as_ref())
+        .or(args.dir.as_ref())
+        .or(args.idle.as_ref())
+        .filter(|path| !is_stdin(path))
+        .cloned()
+}
+
+/// set once an input fails to load with nothing to fall back on, so the
+/// overlay exits with a failure status
+static LOAD_FAILED: AtomicBool = AtomicBool::new(false);
 
-    #[arg(long, default_value_t = 200)]
-    height: u32,
+/// failed frames in a row after which the rest of the file is given up on
+const MAX_FAILED_FRAMES_IN_A_ROW: usize = 10;
+
+/// set while the overlay is hidden or paused, so streamed inputs stop
+/// decoding frames nobody would see
+static STREAMS_HELD: Mutex<bool> = Mutex::new(false);
+static STREAMS_RELEASED: Condvar = Condvar::new();
+
+/// hold streamed inputs where they are, or let them play on
+fn hold_streams(held: bool) {
+    let mut current = STREAMS_HELD.lock().unwrap_or_else(PoisonError::into_inner);
+    if *current != held {
+        *current = held;
+        STREAMS_RELEASED.notify_all();
+    }
+}
+
+/// Block a streaming loader thread while streams are held, returning whether
+/// it had to wait.
+fn wait_while_held() -> bool {
+    let mut held = STREAMS_HELD.lock().unwrap_or_else(PoisonError::into_inner);
+    let waited = *held;
+    while *held {
+        held = STREAMS_RELEASED
+            .wait(held)
+            .unwrap_or_else(PoisonError::into_inner);
+    }
+    waited
+}
+
+/// delay used for still images, which are shown once and never advanced
+const STATIC_FRAME_DELAY: Duration = Duration::MAX;
+
+/// how long a still image stays up in a playlist when `--hold` isn't given
+const PLAYLIST_STILL_DURATION: Duration = Duration::from_secs(5);
+
+/// `-` as the input path means "read from stdin"
+fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+/// inputs that arrive as a byte stream rather than a seekable local file
+fn is_stream(path: &Path) -> bool {
+    is_stdin(path) || remote::is_url(path)
+}
+
+/// whether `path` plays as it's decoded, pushing frames at playback speed
+/// from the moment its loader starts, which rules out preloading it: the
+/// frames would pile up in the channel, and it would start mid-clip
+fn plays_as_decoded(path: &Path, options: LoaderOptions) -> bool {
+    // anything longer than `--stream-frames` is streamed
+    if options.stream_frames.is_some() {
+        return true;
+    }
+    // stdin and URLs can't be looked at without using them up, and videos
+    // have to be local files anyway
+    if is_stream(path) {
+        return false;
+    }
+    let mut header = Vec::new();
+    File::open(path)
+        .and_then(|file| file.take(validate::HEADER_LEN).read_to_end(&mut header))
+        .is_ok()
+        && InputFormat::detect(path, &header) == InputFormat::Video
+}
+
+/// open the input as a buffered byte stream, so decoders don't care whether
+/// it comes from a file, a pipe, or a download
+fn open_input(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    if is_stdin(path) {
+        Ok(Box::new(io::stdin().lock()))
+    } else if remote::is_url(path) {
+        let url = path.to_str().expect("URL inputs are valid UTF-8");
+        Ok(Box::new(BufReader::new(remote::open(url)?)))
+    } else {
+        Ok(Box::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+/// decode a still image from any reader; streams can't seek, so buffer it first
+fn decode_still(mut reader: impl Read) -> image::ImageResult<DynamicImage> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    image::load_from_memory(&data)
 }
 
 struct Frame {
-    texture: Arc<egui::TextureHandle>,
+    sprite: atlas::Sprite,
     delay: Duration,
+    // decoded pixels before the color filters, so they can be reapplied
+    pixels: Vec<u8>,
+    size: [usize; 2],
+}
+
+/// the outgoing playlist entry's last frame, faded out over the incoming one
+struct Crossfade {
+    sprite: atlas::Sprite,
+    size: egui::Vec2,
+    started: Instant,
+    duration: Duration,
+}
+
+impl Crossfade {
+    /// 0.0 at the start of the transition, 1.0 once it's over
+    fn progress(&self) -> f32 {
+        (self.started.elapsed().as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+    }
+}
+
+/// the playlist an alert cut into, picked up again once it's over
+struct Interrupted {
+    playlist: Vec<PathBuf>,
+    index: usize,
+    trigger_mode: bool,
+    shuffle_playlist: bool,
 }
 
 enum LoadingMessage {
     FrameReady(usize, Vec<u8>, [usize; 2], Duration),
+    /// a frame from a streamed source, replacing whatever is currently shown
+    StreamFrame(usize, Vec<u8>, [usize; 2], Duration),
     LoadingComplete(usize),
+    /// vector sources report the scale their frames were rasterized at
+    RenderScale(f32),
+    /// the input couldn't be loaded, and nothing more will arrive for it
+    Error(String),
+}
+
+/// hand `message` to the UI, failing once it has stopped listening (the
+/// window closed, or it moved on to another input)
+fn deliver(sender: &Sender<LoadingMessage>, message: LoadingMessage) -> Result<(), String> {
+    sender
+        .send(message)
+        .map_err(|_| "the overlay stopped listening".to_string())
 }
 
 struct GifOverlay {
     frames: Vec<Option<Frame>>,
     current_frame: usize,
+    // when the current frame was due on screen, which the next one's due
+    // time counts on from
     last_update: Instant,
     scale: f32,
+    // applied when drawing rather than baked into the frames, so `-`/`+`,
+    // stdin and the tray can change it while the overlay runs
     opacity: f32,
+    fade_duration: Duration,
+    enter: transition::Transition,
+    exit: transition::Transition,
+    // multiplied into the opacity, animated between 0 (faded out) and 1
+    visibility: f32,
+    // set while a fade is under way
+    last_fade_step: Option<Instant>,
+    // fading out before the window closes
+    closing: bool,
+    filters: filters::ColorFilters,
+    background: Option<egui::Color32>,
+    // multiplied into every frame when it's drawn, white for none
+    tint: egui::Color32,
+    orientation: transform::Orientation,
+    // part of each source frame to show, in pixels
+    crop: Option<egui::Rect>,
+    mask: transform::Mask,
+    shadow: bool,
+    #[cfg(feature = "shader")]
+    shader: Option<shader::ShaderState>,
+    particles: Option<particles::Emitter>,
+    audio: Option<audio::AudioReactive>,
+    sound: Option<sound::Sound>,
+    // `--timer` or `--clock` text drawn under the animation
+    clock: Option<clock::Clock>,
+    clock_font: egui::FontId,
+    clock_color: egui::Color32,
+    caption_font: egui::FontId,
+    caption_color: egui::Color32,
+    // degrees per second for `--spin`
+    spin: Option<f32>,
+    ken_burns: Option<transform::KenBurns>,
+    // scale the current clip's textures were rasterized at, 1.0 for bitmaps
+    render_scale: f32,
+    // size the window to the animation instead of `--width`/`--height`
+    auto_size: bool,
+    window_size: Option<egui::Vec2>,
+    fullscreen: bool,
+    // screen area the animation is fitted into in fullscreen mode
+    fit_area: Option<egui::Vec2>,
     frame_receiver: Receiver<LoadingMessage>,
+    // vector sources re-rasterize on the loader thread when the scale changes
+    rescale_sender: Sender<f32>,
+    rendered_scale: f32,
+    zoom_modifier: ZoomModifier,
+    texture_options: egui::TextureOptions,
+    // shared textures the frames are packed into
+    atlas: atlas::Atlas,
+    playlist: Vec<PathBuf>,
+    playlist_index: usize,
+    // entry being preloaded, played once the current one finishes
+    next_index: usize,
+    // loader for the next playlist entry, decoding while the current one plays
+    preloaded: Option<(Receiver<LoadingMessage>, Sender<f32>)>,
+    shuffle_playlist: bool,
+    switch_interval: Option<Duration>,
+    clip_started: Instant,
+    loader_options: LoaderOptions,
+    // idle/active mode: entry 0 loops until triggered, entry 1 plays once
+    trigger_mode: bool,
+    // `Command::Alert` animations waiting their turn
+    alerts: VecDeque<(PathBuf, Option<String>)>,
+    // set while an alert plays
+    interrupted: Option<Interrupted>,
+    // text shown under the alert that's playing
+    caption: Option<String>,
+    stdin_commands: Option<Receiver<StdinCommand>>,
+    crossfade_duration: Option<Duration>,
+    crossfade: Option<Crossfade>,
+    paused: bool,
+    // when false, playback stops on the last frame instead of wrapping
+    looping: bool,
+    controls_visible: bool,
+    // the performance HUD, while it's shown
+    hud: Option<hud::Hud>,
+    always_on_top: bool,
+    // `--position` is applied once the monitor size is known
+    pending_anchor: Option<placement::Anchor>,
+    margin: f32,
+    monitor: Option<placement::Monitor>,
+    state_input: Option<PathBuf>,
+    saved_state: Option<state::WindowState>,
+    last_state_check: Instant,
+    snap_threshold: f32,
+    // set when the user starts dragging, cleared once the window settles
+    window_dragged: bool,
+    last_outer_pos: Option<egui::Pos2>,
+    moved_at: Option<Instant>,
+    locked: bool,
+    follower: Option<follow::CursorFollower>,
+    pet: Option<pet::Pet>,
+    playback: Playback,
+    // ping-pong direction, flipped at either end of the animation
+    playing_backward: bool,
+    shuffle_hold: Option<Duration>,
+    loop_limit: Option<u32>,
+    loops_completed: u32,
+    on_complete: OnComplete,
+    frame_hooks: Vec<hooks::FrameHook>,
+    // frame the hooks last ran for, so each frame fires once per visit
+    hooked_frame: Option<usize>,
+    script: Option<script::Script>,
+    script_started: bool,
+    // frame `on_frame` was last called for, like `hooked_frame`
+    scripted_frame: Option<usize>,
+    // what the script asked for, carried out with the next control commands
+    script_commands: Vec<control::Command>,
+    // `--plugin`s, shared with `filters` for their frame filters
+    plugins: Rc<[Box<dyn plugin::Plugin>]>,
+    status_json: bool,
+    // the last `--status-json` state line, so only changes are printed
+    reported_state: Option<status_json::OverlayState>,
+    // set once `--on-complete hide` has kicked in
+    completion_hidden: bool,
+    schedule: Option<schedule::Schedule>,
+    // outside the scheduled windows nothing is drawn and clicks pass through
+    schedule_hidden: bool,
+    tray: Option<tray::Tray>,
+    // cleared once the taskbar hints have been applied to the live window
+    skip_taskbar: bool,
+    // hidden from the tray menu or the global hotkey
+    user_hidden: bool,
+    hotkeys: Option<hotkey::GlobalHotkeys>,
+    // requests from the `--http-port` API, `--ipc`/`--websocket-port` clients,
+    // MQTT, OSC, D-Bus, and Twitch
+    control_commands: Receiver<control::Command>,
+    // keeps the `--dbus` service up
+    _dbus: Option<dbus::Service>,
+    _midi: Option<midi::Connection>,
+    fullscreen_watcher: Option<fullscreen::FullscreenWatcher>,
+    // another application is fullscreen, so stay out of its way
+    fullscreen_app_hidden: bool,
+    last_fullscreen_check: Instant,
+    // OBS's program scene whenever it changes, for `--obs-scenes`
+    obs_scene: Option<Receiver<String>>,
+    obs_scenes: Vec<String>,
+    // OBS is on a scene the overlay isn't part of
+    obs_hidden: bool,
+    // source frames outside this inclusive range are dropped on arrival
+    start_frame: usize,
+    end_frame: Option<usize>,
     loading_complete: bool,
     first_frame_loaded: bool,
+    // why the current input couldn't be loaded, shown instead of the spinner
+    load_error: Option<String>,
+    // inputs that failed in a row, so a playlist of broken files stops skipping
+    failed_loads: usize,
     // performance metric
     start_time: Instant,
     total_frame: usize,
@@ -66,148 +1421,2588 @@ struct GifOverlay {
     last_memory_check: Instant,
 }
 
-impl GifOverlay {
-    fn new(ctx: &egui::Context, gif_path: PathBuf, scale: f32, opacity: f32) -> Self {
-        let start_time = Instant::now();
-        println!("Starting GIF overlay application...");
-        println!("Loading GIF from: {}", gif_path.display());
+/// send a still image as the only frame of the "animation"
+fn send_static_image(mut output: FrameSender, image: DynamicImage) -> Result<(), String> {
+    let buffer = image.into_rgba8();
+    let size = [buffer.width() as usize, buffer.height() as usize];
+
+    output.fit(size, 1)?;
+    output.frame(buffer.into_raw(), size, STATIC_FRAME_DELAY)?;
+    output.finish()
+}
+
+/// slice a sprite sheet into equally sized cells, left to right and top to bottom
+fn send_sprite_sheet(
+    mut output: FrameSender,
+    image: DynamicImage,
+    sheet: SpriteSheet,
+    delay: Duration,
+) -> Result<(), String> {
+    let buffer = image.into_rgba8();
+    let columns = sheet.columns.max(1);
+    let rows = sheet.rows.max(1);
+    let cell_width = buffer.width() / columns;
+    let cell_height = buffer.height() / rows;
+    if cell_width == 0 || cell_height == 0 {
+        return Err(format!(
+            "a {}x{} sprite sheet can't be sliced into {} columns and {} rows; \
+             check `--columns` and `--rows`",
+            buffer.width(),
+            buffer.height(),
+            columns,
+            rows
+        ));
+    }
+
+    info!(
+        "Slicing sprite sheet into {}x{} cells of {}x{}",
+        columns, rows, cell_width, cell_height
+    );
+    output.fit(
+        [cell_width as usize, cell_height as usize],
+        (columns * rows) as usize,
+    )?;
+
+    for row in 0..rows {
+        for column in 0..columns {
+            let cell = image::imageops::crop_imm(
+                &buffer,
+                column * cell_width,
+                row * cell_height,
+                cell_width,
+                cell_height,
+            )
+            .to_image();
+            let size = [cell.width() as usize, cell.height() as usize];
+            output.frame(cell.into_raw(), size, delay)?;
+        }
+    }
+
+    output.finish()
+}
+
+/// Sends an input's decoded frames to the UI, numbering them as they go out,
+/// shrinking them to the size they're shown at or to fit `--max-memory`, and
+/// applying `--bad-frames` to the ones that failed to decode.
+struct FrameSender<'a> {
+    sender: &'a Sender<LoadingMessage>,
+    ctx: &'a egui::Context,
+    options: LoaderOptions,
+    // whether the frames' size has been settled on yet
+    fitted: bool,
+    // what frames are scaled by to be shown or stay within `max_memory`
+    shrink: Option<f32>,
+    sent: usize,
+    // source indices of the frames that failed
+    failed: Vec<usize>,
+    failed_in_a_row: usize,
+    // the last good frame, kept for `--bad-frames repeat`
+    previous: Option<(Vec<u8>, [usize; 2], Duration)>,
+    // copies what's sent into the `--cache`
+    recorder: Option<cache::Recorder>,
+}
+
+impl<'a> FrameSender<'a> {
+    fn new(
+        sender: &'a Sender<LoadingMessage>,
+        options: LoaderOptions,
+        ctx: &'a egui::Context,
+    ) -> Self {
+        Self {
+            sender,
+            ctx,
+            options,
+            fitted: false,
+            shrink: None,
+            sent: 0,
+            failed: Vec::new(),
+            failed_in_a_row: 0,
+            previous: None,
+            recorder: None,
+        }
+    }
+
+    fn send(&mut self, message: LoadingMessage) -> Result<(), String> {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(&message);
+        }
+        deliver(self.sender, message)
+    }
+
+    /// Shrink every frame from here on to the size `size` frames are shown
+    /// at, or further if `frames` of them would take up more than
+    /// `--max-memory`. Only the first call counts.
+    fn fit(&mut self, size: [usize; 2], frames: usize) -> Result<(), String> {
+        if self.fitted {
+            return Ok(());
+        }
+        self.fitted = true;
+        let shown = self.options.downscale_factor(size, self.ctx);
+        let budget = self
+            .options
+            .max_memory
+            .and_then(|budget| budget::shrink_factor(budget, size, frames));
+        let Some(factor) = shown.into_iter().chain(budget).reduce(f32::min) else {
+            return Ok(());
+        };
+        let shrunk = budget::shrunk_size(size, factor);
+        if budget == Some(factor) {
+            info!(
+                "{} frames at {}x{} won't fit in --max-memory, shrinking them to {}x{}",
+                frames, size[0], size[1], shrunk[0], shrunk[1]
+            );
+        } else {
+            info!(
+                "Frames at {}x{} are shown smaller, shrinking them to {}x{}",
+                size[0], size[1], shrunk[0], shrunk[1]
+            );
+        }
+        self.shrink = Some(factor);
+        // the UI still shows them at the original size
+        self.send(LoadingMessage::RenderScale(factor))
+    }
+
+    fn frame(&mut self, pixels: Vec<u8>, size: [usize; 2], delay: Duration) -> Result<(), String> {
+        self.failed_in_a_row = 0;
+        // inputs with no frame count up front are sized by their first frame
+        self.fit(size, 1)?;
+        let (pixels, size) = match self.shrink {
+            Some(factor) => budget::shrink_frame(pixels, size, factor),
+            None => (pixels, size),
+        };
+        if self.options.bad_frames == BadFrames::Repeat {
+            self.previous = Some((pixels.clone(), size, delay));
+        }
+        self.send(LoadingMessage::FrameReady(self.sent, pixels, size, delay))?;
+        self.sent += 1;
+        Ok(())
+    }
+
+    /// Deal with source frame `idx` failing to decode, returning whether
+    /// there's any point reading on.
+    fn failed(&mut self, idx: usize, err: impl fmt::Display) -> Result<bool, String> {
+        if self.options.bad_frames == BadFrames::Stop {
+            return Err(format!("failed to decode frame {}: {}", idx, err));
+        }
+        debug!("failed to decode frame {}: {}", idx, err);
+        self.failed.push(idx);
+        self.failed_in_a_row += 1;
+        // past a point it's a truncated file, not a damaged frame
+        if self.failed_in_a_row >= MAX_FAILED_FRAMES_IN_A_ROW {
+            warn!(
+                "{} frames in a row failed to decode, stopping at frame {}",
+                self.failed_in_a_row,
+                idx + 1 - self.failed_in_a_row
+            );
+            return Ok(false);
+        }
+        if let Some((pixels, size, delay)) = self.previous.clone() {
+            self.send(LoadingMessage::FrameReady(self.sent, pixels, size, delay))?;
+            self.sent += 1;
+        }
+        Ok(true)
+    }
+
+    /// summarize the frames that failed and tell the UI how many it got
+    fn finish(mut self) -> Result<(), String> {
+        if self.sent == 0 {
+            return Err(if self.failed.is_empty() {
+                "the file has no frames".to_string()
+            } else {
+                "no frame could be decoded".to_string()
+            });
+        }
+        if !self.failed.is_empty() {
+            let indices: Vec<String> = self.failed.iter().map(usize::to_string).collect();
+            warn!(
+                "{} {} frames that failed to decode: {}",
+                match self.options.bad_frames {
+                    BadFrames::Repeat => "Repeated the frame before",
+                    _ => "Skipped",
+                },
+                self.failed.len(),
+                indices.join(", ")
+            );
+        }
+        self.send(LoadingMessage::LoadingComplete(self.sent))?;
+        if let Some(recorder) = self.recorder.take() {
+            recorder.save();
+        }
+        Ok(())
+    }
+}
+
+/// Sort key that orders `frame_2.png` before `frame_10.png`, so sequences
+/// without zero padding still play in the right order.
+fn sequence_sort_key(path: &Path) -> (String, u64, String) {
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
+    let prefix = stem.trim_end_matches(|c: char| c.is_ascii_digit());
+    let number = stem[prefix.len()..].parse().unwrap_or(0);
+    (prefix.to_string(), number, stem.to_string())
+}
+
+/// decode every image in `dir`, in numbered order, as frames of one animation
+fn send_image_sequence(mut output: FrameSender, dir: &Path, delay: Duration) -> Result<(), String> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|err| format!("failed to read image sequence folder: {}", err))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .and_then(image::ImageFormat::from_extension)
+                .is_some()
+        })
+        .collect();
+    paths.sort_by_cached_key(|path| sequence_sort_key(path));
+
+    info!("Image sequence: {} files", paths.len());
+    if paths.is_empty() {
+        return Err("no images in the folder".to_string());
+    }
+
+    if let Ok((width, height)) = image::image_dimensions(&paths[0]) {
+        output.fit([width as usize, height as usize], paths.len())?;
+    }
+    for (idx, path) in paths.iter().enumerate() {
+        let buffer = match image::open(path) {
+            Ok(image) => image.into_rgba8(),
+            Err(err) => {
+                if output.failed(idx, format_args!("{}: {}", path.display(), err))? {
+                    continue;
+                }
+                break;
+            }
+        };
+        let size = [buffer.width() as usize, buffer.height() as usize];
+        output.frame(buffer.into_raw(), size, delay)?;
+    }
+    output.finish()
+}
+
+/// Decode `path` on the current thread, streaming frames to `sender`. If it
+/// can't be loaded, the UI gets a `LoadingMessage::Error` saying why.
+fn load_input(
+    path: &Path,
+    options: LoaderOptions,
+    sender: Sender<LoadingMessage>,
+    rescale_receiver: Receiver<f32>,
+    ctx: egui::Context,
+) {
+    if let Err(err) = decode_input(path, options, &sender, &rescale_receiver, &ctx) {
+        // nobody to tell if the UI has already moved on
+        if sender.send(LoadingMessage::Error(err)).is_ok() {
+            ctx.request_repaint();
+        }
+    }
+}
+
+/// the part of `load_input` that can fail
+fn decode_input(
+    path: &Path,
+    options: LoaderOptions,
+    sender: &Sender<LoadingMessage>,
+    rescale_receiver: &Receiver<f32>,
+    ctx: &egui::Context,
+) -> Result<(), String> {
+    let LoaderOptions {
+        scale,
+        sprite_sheet,
+        frame_delay,
+        zero_delay,
+        max_memory,
+        stream_frames,
+        ..
+    } = options;
+    let load_start = Instant::now();
+
+    if !is_stream(path) && path.is_dir() {
+        return send_image_sequence(FrameSender::new(sender, options, ctx), path, frame_delay);
+    }
+
+    let mut reader = open_input(path).map_err(|err| format!("failed to open: {}", err))?;
+
+    debug!("File opened in: {:.2?}", load_start.elapsed());
+
+    if let Some(sheet) = sprite_sheet {
+        let image = decode_still(reader)
+            .map_err(|err| format!("failed to decode sprite sheet: {}", err))?;
+        return send_sprite_sheet(
+            FrameSender::new(sender, options, ctx),
+            image,
+            sheet,
+            frame_delay,
+        );
+    }
+
+    let header = reader
+        .fill_buf()
+        .map_err(|err| format!("failed to read file header: {}", err))?;
+    let format = InputFormat::detect(path, header);
+    debug!("Detected format: {:?}", format);
+
+    if format.needs_path() && is_stream(path) {
+        return Err(format!(
+            "{:?} input must be a local file, not stdin or a URL",
+            format
+        ));
+    }
+
+    if format == InputFormat::Video {
+        drop(reader);
+        return video::stream(path, options, sender, ctx);
+    }
+
+    if format == InputFormat::Avif {
+        drop(reader);
+        return video::decode_sequence(path, options, sender, ctx);
+    }
+
+    // ffmpeg opens the file itself, so GIFs from stdin or a URL stay with
+    // the built-in decoder, as do streamed ones and those relying on
+    // `--force-delay`, `--bad-frames`, or `--cache`, which only it handles
+    if options.decoder == Decoder::Ffmpeg
+        && format == InputFormat::Gif
+        && !is_stream(path)
+        && stream_frames.is_none()
+        && zero_delay.is_none()
+        && options.bad_frames == BadFrames::Skip
+        && !options.cache
+    {
+        drop(reader);
+        return video::decode_sequence(path, options, sender, ctx);
+    }
+
+    if format == InputFormat::Svg {
+        drop(reader);
+        return svg::serve(path, scale, sender, rescale_receiver, ctx);
+    }
+
+    if format == InputFormat::Lottie {
+        drop(reader);
+        #[cfg(feature = "lottie")]
+        return lottie::render(path, scale, sender, max_memory);
+        #[cfg(not(feature = "lottie"))]
+        return Err("Lottie input requires building with `--features lottie`".to_string());
+    }
+
+    let mut output = FrameSender::new(sender, options, ctx);
+    // streamed animations are never all decoded at once to be kept
+    if options.cache && !is_stream(path) && stream_frames.is_none() {
+        if let Some(entry) = cache::entry(path, &options, ctx) {
+            if cache::replay(&entry, sender)? {
+                debug!("Cached frames loaded in: {:.2?}", load_start.elapsed());
+                return Ok(());
+            }
+            output.recorder = cache::Recorder::create(entry);
+        }
+    }
+    // `--max-memory` and `--stream-frames` need the frame count up front, so
+    // read the whole (still compressed) file first
+    if (max_memory.is_some() || stream_frames.is_some()) && format != InputFormat::Image {
+        let mut data = Vec::new();
+        reader
+            .read_to_end(&mut data)
+            .map_err(|err| format!("failed to read: {}", err))?;
+        let estimate = budget::estimate(format, &data);
+        if let (Some(capacity), Some((_, frames))) = (stream_frames, estimate) {
+            // shorter animations are simply decoded in full
+            if frames > capacity {
+                return ring::play(&data, format, options, capacity, sender, ctx);
+            }
+        }
+        if let Some((size, frames)) = estimate {
+            output.fit(size, frames)?;
+        }
+        reader = Box::new(io::Cursor::new(data));
+    }
+
+    let frames = match format {
+        InputFormat::Gif => compose::ComposedGifFrames::new(reader)
+            .map_err(|err| format!("failed to read GIF: {}", err))?
+            .into_frames(),
+        InputFormat::WebP => WebPDecoder::new(reader)
+            .map_err(|err| format!("failed to read WebP: {}", err))?
+            .into_frames(),
+        InputFormat::Png => {
+            let decoder =
+                PngDecoder::new(reader).map_err(|err| format!("failed to read PNG: {}", err))?;
+            if !decoder.is_apng() {
+                info!("PNG has no animation control chunk, showing it as a still image");
+                let image = DynamicImage::from_decoder(decoder)
+                    .map_err(|err| format!("failed to decode PNG: {}", err))?;
+                return send_static_image(output, image);
+            }
+            decoder.apng().into_frames()
+        }
+        InputFormat::Image => {
+            let image =
+                decode_still(reader).map_err(|err| format!("failed to decode image: {}", err))?;
+            return send_static_image(output, image);
+        }
+        InputFormat::Video | InputFormat::Avif | InputFormat::Lottie | InputFormat::Svg => {
+            unreachable!("handled before frame decoding")
+        }
+    };
+
+    let process_start = Instant::now();
+
+    for (idx, frame) in frames.enumerate() {
+        let frame_start = Instant::now();
+
+        let frame = match frame {
+            Ok(frame) => frame,
+            Err(err) => {
+                if output.failed(idx, err)? {
+                    continue;
+                }
+                break;
+            }
+        };
+        let delay = match (Duration::from(frame.delay()), zero_delay) {
+            (delay, Some(zero_delay)) if delay.is_zero() => zero_delay,
+            (delay, _) => delay,
+        };
+        let buffer = frame.into_buffer();
+        let size = [buffer.width() as _, buffer.height() as _];
+
+        let pixels = buffer.into_raw();
+
+        output.frame(pixels, [size[0], size[1]], delay)?;
+    }
+
+    output.finish()
+}
+
+/// A line read from the overlay's stdin.
+enum StdinCommand {
+    /// `trigger`, or an empty line, plays the `--active` animation
+    Trigger,
+    /// `opacity 0.5` sets a value, `opacity +0.1`/`opacity -0.1` nudge it
+    Set {
+        setting: Setting,
+        value: f32,
+        relative: bool,
+    },
+    /// `show`, `hide`, `pause`, `resume`, `seek 10`, `load /path/x.gif`,
+    /// `alert /path/x.gif`, `scale 2`, `pos 100 200`, or `quit`
+    Control(control::Command),
+}
+
+/// a value that can be changed from stdin while the overlay runs
+#[derive(Debug, Clone, Copy)]
+enum Setting {
+    Opacity,
+    Brightness,
+    Contrast,
+    Gamma,
+}
+
+impl StdinCommand {
+    /// the command on `line`, or `None` if it isn't a valid one
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        let (name, value) = line
+            .split_once(' ')
+            .map_or((line, ""), |(name, value)| (name, value.trim()));
+        match Self::parse_control(name, value) {
+            Ok(Some(command)) => return Some(StdinCommand::Control(command)),
+            Ok(None) => {}
+            Err(err) => {
+                warn!("invalid {} {:?}: {}", name, value, err);
+                return None;
+            }
+        }
+
+        let setting = match name {
+            "" | "trigger" if value.is_empty() => return Some(StdinCommand::Trigger),
+            "opacity" => Setting::Opacity,
+            "brightness" => Setting::Brightness,
+            "contrast" => Setting::Contrast,
+            "gamma" => Setting::Gamma,
+            _ => {
+                warn!("unknown command on stdin: {:?}", line);
+                return None;
+            }
+        };
+        match value.parse::<f32>() {
+            Ok(parsed) => Some(StdinCommand::Set {
+                setting,
+                value: parsed,
+                relative: value.starts_with(['+', '-']),
+            }),
+            Err(err) => {
+                warn!("invalid {} {:?}: {}", name, value, err);
+                None
+            }
+        }
+    }
+
+    /// the command `name` with the rest of the line as `value`, if it is one
+    fn parse_control(name: &str, value: &str) -> Result<Option<control::Command>, String> {
+        let number = |value: &str| value.parse::<f32>().map_err(|err| err.to_string());
+        let command = match (name, value) {
+            ("show", "") => control::Command::Show,
+            ("hide", "") => control::Command::Hide,
+            ("pause", "") => control::Command::Pause,
+            ("resume", "") => control::Command::Resume,
+            ("quit", "") => control::Command::Quit,
+            ("seek", frame) => {
+                control::Command::Seek(frame.parse().map_err(|err| err.to_string())?)
+            }
+            // stdin is taken by these commands, so it can't be switched to
+            ("load" | "alert", "" | "-") => return Err("expected a file or URL".to_string()),
+            ("load", path) => control::Command::Play(PathBuf::from(path)),
+            ("alert", path) => control::Command::Alert(PathBuf::from(path), None),
+            ("scale", scale) => control::Command::Scale(number(scale)?),
+            ("pos" | "move", position) => {
+                let (x, y) = position
+                    .split_once(char::is_whitespace)
+                    .ok_or("expected X Y")?;
+                control::Command::Move(egui::pos2(number(x)?, number(y.trim())?))
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(command))
+    }
+}
+
+/// Read stdin line by line, turning each line into a command and skipping
+/// those that aren't one.
+fn spawn_stdin_commands(ctx: &egui::Context) -> Receiver<StdinCommand> {
+    let (sender, receiver) = channel();
+    let ctx = ctx.clone();
+
+    thread::spawn(move || {
+        for line in io::stdin().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let Some(command) = StdinCommand::parse(&line) else {
+                continue;
+            };
+            if sender.send(command).is_err() {
+                break;
+            }
+            ctx.request_repaint();
+        }
+    });
+
+    receiver
+}
+
+/// the color effects `args` ask for, followed by the `--plugin` filters
+fn color_filters(args: &Args, plugins: Rc<[Box<dyn plugin::Plugin>]>) -> filters::ColorFilters {
+    filters::ColorFilters {
+        chroma_key: args
+            .chroma_key
+            .map(|color| filters::ChromaKey::new(color, args.chroma_tolerance)),
+        hue_shift: args.hue_shift.map(filters::HueShift::new),
+        levels: filters::Levels {
+            brightness: args.brightness,
+            contrast: args.contrast,
+            gamma: args.gamma,
+        },
+        filter: args.filter,
+        outline: args.outline,
+        plugins,
+    }
+}
+
+/// Spawn a background thread decoding `path`. Frames queue up in the returned
+/// receiver until the UI drains them, which is also how the next playlist
+/// entry is preloaded while the current one plays.
+fn spawn_loader(
+    ctx: &egui::Context,
+    path: PathBuf,
+    options: LoaderOptions,
+) -> (Receiver<LoadingMessage>, Sender<f32>) {
+    let (sender, receiver) = channel();
+    let (rescale_sender, rescale_receiver) = channel();
+    let ctx = ctx.clone();
+
+    debug!(
+        "Spawning background loader thread for {}...",
+        path.display()
+    );
+
+    thread::spawn(move || {
+        let _decoding = tracing::debug_span!("decode", input = %path.display()).entered();
+        load_input(&path, options, sender, rescale_receiver, ctx)
+    });
+
+    (receiver, rescale_sender)
+}
+
+impl GifOverlay {
+    fn new(ctx: &egui::Context, args: Args, monitor: Option<placement::Monitor>) -> Self {
+        let loader_options = LoaderOptions::new(&args, monitor.as_ref());
+        let state_input = state_input(&args);
+        let obs_connection = obs_connection(&args);
+        let plugins: Rc<[Box<dyn plugin::Plugin>]> = args
+            .plugins
+            .iter()
+            .filter_map(|path| {
+                plugin::load(path)
+                    .map_err(|err| warn!("failed to load plugin {}: {}", path.display(), err))
+                    .ok()
+            })
+            .collect();
+        let mut playlist = args.input;
+        if let Some(playlist_file) = &args.playlist {
+            playlist.extend(read_playlist(playlist_file).unwrap_or_else(|err| {
+                error!(
+                    "failed to read playlist {}: {}",
+                    playlist_file.display(),
+                    err
+                );
+                std::process::exit(1);
+            }));
+        }
+        let shuffle_playlist = args.dir.is_some();
+        if let Some(dir) = &args.dir {
+            playlist.extend(read_animation_dir(dir).unwrap_or_else(|err| {
+                error!("failed to read {}: {}", dir.display(), err);
+                std::process::exit(1);
+            }));
+        }
+        // an idle/active pair is a two-entry playlist that only advances on triggers
+        let trigger_mode = args.idle.is_some();
+        playlist.extend(args.idle);
+        playlist.extend(args.active);
+        if playlist.is_empty() && args.particles.is_none() {
+            error!("no input given, pass --input, --playlist, or a --dir with animations in it");
+            std::process::exit(1);
+        }
+        let scale = args.scale;
+        let opacity = args.opacity;
+        let playback = args.playback;
+
+        let start_time = Instant::now();
+        info!("Starting GIF overlay application...");
+        for (idx, path) in playlist.iter().enumerate() {
+            info!("Playlist {}: {}", idx + 1, path.display());
+        }
+
+        // validate opacity
+        let opacity = opacity.clamp(0.0, 1.0);
+        // ensure scale is positive
+        let scale = scale.max(0.1);
+        let fade_duration = match args.fade {
+            0 if args.enter.is_some() || args.exit.is_some() => DEFAULT_TRANSITION_TIME,
+            fade => Duration::from_millis(fade),
+        };
+
+        let playlist_index = if shuffle_playlist && !playlist.is_empty() {
+            rand::thread_rng().gen_range(0..playlist.len())
+        } else {
+            0
+        };
+        let next_index = pick_next_index(playlist.len(), playlist_index, shuffle_playlist);
+
+        let (receiver, rescale_sender) = match playlist.get(playlist_index) {
+            Some(path) => spawn_loader(ctx, path.clone(), loader_options),
+            // `--particles` on their own have nothing to decode
+            None => (channel().1, channel().0),
+        };
+        // start decoding the next entry right away so switching is instant
+        let preloaded = playlist
+            .get(next_index)
+            .filter(|path| playlist.len() > 1 && !plays_as_decoded(path, loader_options))
+            .map(|path| spawn_loader(ctx, path.clone(), loader_options));
+
+        // stdin is free for triggers unless it's also an input
+        let stdin_commands =
+            (!playlist.iter().any(|path| is_stdin(path))).then(|| spawn_stdin_commands(ctx));
+
+        let tray = if args.no_tray {
+            None
+        } else {
+            tray::Tray::new(ctx)
+                .map_err(|err| warn!("failed to create tray icon: {}", err))
+                .ok()
+        };
+
+        let mut hotkey_bindings: Vec<(String, hotkey::Action)> = args
+            .hotkeys
+            .as_deref()
+            .and_then(|path| {
+                hotkey::load(path)
+                    .map_err(|err| warn!("failed to read {}: {}", path.display(), err))
+                    .ok()
+            })
+            .unwrap_or_default();
+        if let Some(keys) = &args.hide_hotkey {
+            hotkey_bindings.push((keys.clone(), hotkey::Action::Toggle));
+        }
+        // the event handler is process-wide, so every binding is registered at once
+        let hotkeys = if hotkey_bindings.is_empty() {
+            None
+        } else {
+            hotkey::GlobalHotkeys::register(&hotkey_bindings, ctx)
+                .map_err(|err| warn!("failed to register hotkeys: {}", err))
+                .ok()
+        };
+
+        let (control_sender, control_commands) = channel();
+        if let Some(port) = args.http_port {
+            let webhooks = args.webhook_rules.as_deref().and_then(|path| {
+                webhook::load(path)
+                    .map_err(|err| warn!("failed to read {}: {}", path.display(), err))
+                    .ok()
+            });
+            if let Err(err) = http::serve(port, webhooks, control_sender.clone(), ctx) {
+                warn!("failed to start control API on port {}: {}", port, err);
+            }
+        }
+        if let Some(name) = &args.ipc {
+            if let Err(err) = ipc::listen(name, control_sender.clone(), ctx) {
+                warn!("failed to listen for commands as {:?}: {}", name, err);
+            }
+        }
+        if let Some(port) = args.websocket_port {
+            if let Err(err) = websocket::serve(port, control_sender.clone(), ctx) {
+                warn!("failed to start WebSocket on port {}: {}", port, err);
+            }
+        }
+        if let Some(broker) = &args.mqtt_broker {
+            mqtt::subscribe(broker, &args.mqtt_topic, control_sender.clone(), ctx);
+        }
+        if let Some(port) = args.osc_port {
+            if let Err(err) = osc::listen(args.osc_bind, port, control_sender.clone(), ctx) {
+                warn!("failed to listen for OSC on port {}: {}", port, err);
+            }
+        }
+        if let Some(path) = &args.twitch {
+            match twitch::load(path) {
+                Ok(config) => twitch::connect(config, control_sender.clone(), ctx),
+                Err(err) => warn!("failed to read {}: {}", path.display(), err),
+            }
+        }
+        let obs_scene = obs_connection.and_then(|connection| {
+            if args.obs_scenes.is_empty() {
+                warn!("--obs needs --obs-scenes to know when to show the overlay");
+                return None;
+            }
+            let (sender, receiver) = channel();
+            let ctx = ctx.clone();
+            obs::watch(connection, move |scene| {
+                let _ = sender.send(scene.to_string());
+                ctx.request_repaint();
+            });
+            Some(receiver)
+        });
+        let midi = args.midi.as_deref().and_then(|path| {
+            midi::load(path)
+                .map_err(|err| err.to_string())
+                .and_then(|config| midi::connect(&config, control_sender.clone(), ctx))
+                .map_err(|err| warn!("failed to set up MIDI from {}: {}", path.display(), err))
+                .ok()
+        });
+        if let Some(path) = &args.gamepad {
+            let result = gamepad::load(path)
+                .map_err(|err| err.to_string())
+                .and_then(|config| gamepad::listen(&config, control_sender.clone(), ctx));
+            if let Err(err) = result {
+                warn!("failed to set up gamepad from {}: {}", path.display(), err);
+            }
+        }
+        let dbus = if args.dbus {
+            dbus::serve(control_sender, ctx)
+                .map_err(|err| warn!("failed to start D-Bus service: {}", err))
+                .ok()
+        } else {
+            None
+        };
+
+        Self {
+            frames: Vec::new(),
+            current_frame: 0,
+            last_update: Instant::now(),
+            scale,
+            opacity,
+            fade_duration,
+            enter: args.enter.unwrap_or_default(),
+            exit: args.exit.unwrap_or_default(),
+            visibility: if fade_duration.is_zero() { 1.0 } else { 0.0 },
+            last_fade_step: None,
+            closing: false,
+            filters: color_filters(&args, plugins.clone()),
+            background: args.background,
+            tint: args.tint.unwrap_or(egui::Color32::WHITE),
+            orientation: transform::Orientation {
+                rotation: args.rotate,
+                flip_h: args.flip_h,
+                flip_v: args.flip_v,
+                angle: 0.0,
+            },
+            spin: args.spin.filter(|&speed| speed != 0.0),
+            ken_burns: args.ken_burns.map(|duration| transform::KenBurns {
+                duration,
+                from: args.ken_burns_from,
+                to: args.ken_burns_to,
+            }),
+            crop: args.crop,
+            mask: args.mask,
+            shadow: args.shadow,
+            #[cfg(feature = "shader")]
+            shader: None,
+            particles: args
+                .particles
+                .map(|style| particles::Emitter::new(style, args.particle_density)),
+            audio: args.audio_react.and_then(|target| {
+                audio::AudioReactive::start(
+                    args.audio_device.as_deref(),
+                    target,
+                    args.audio_amount,
+                    args.audio_gain,
+                )
+                .map_err(|err| warn!("failed to capture audio: {}", err))
+                .ok()
+            }),
+            sound: args.sound.as_deref().and_then(|path| {
+                sound::Sound::load(path, args.sound_on, args.volume)
+                    .map_err(|err| warn!("failed to load sound {}: {}", path.display(), err))
+                    .ok()
+            }),
+            clock: match args.timer {
+                Some(duration) => Some(clock::Clock::timer(duration)),
+                None if args.clock => Some(clock::Clock::wall_clock(args.clock_format.clone())),
+                None => None,
+            },
+            clock_font: egui::FontId::proportional(args.clock_size),
+            clock_color: args.clock_color,
+            caption_font: egui::FontId::proportional(args.caption_size),
+            caption_color: args.caption_color,
+            render_scale: 1.0,
+            auto_size: args.width.is_none() && args.height.is_none() && !args.fullscreen,
+            window_size: None,
+            fullscreen: args.fullscreen,
+            fit_area: None,
+            frame_receiver: receiver,
+            rescale_sender,
+            playlist,
+            playlist_index,
+            next_index,
+            preloaded,
+            shuffle_playlist,
+            switch_interval: args
+                .interval
+                .filter(|secs| *secs > 0.0)
+                .map(Duration::from_secs_f32),
+            clip_started: Instant::now(),
+            loader_options,
+            trigger_mode,
+            alerts: VecDeque::new(),
+            interrupted: None,
+            caption: None,
+            stdin_commands,
+            crossfade_duration: args
+                .crossfade
+                .map(Duration::from_millis)
+                .filter(|duration| !duration.is_zero()),
+            crossfade: None,
+            rendered_scale: scale,
+            zoom_modifier: args.zoom_modifier,
+            texture_options: args.sampling.texture_options(),
+            atlas: atlas::Atlas::default(),
+            paused: false,
+            looping: true,
+            controls_visible: false,
+            hud: args.hud.then(hud::Hud::default),
+            always_on_top: args.always_on_top,
+            // a monitor without an explicit spot gets the overlay centered on it
+            pending_anchor: args.position.or_else(|| {
+                (monitor.is_some() && args.x.is_none() && !args.fullscreen)
+                    .then_some(placement::Anchor::Center)
+            }),
+            margin: args.margin,
+            monitor,
+            locked: args.locked,
+            follower: args
+                .follow_cursor
+                .then(|| follow::CursorFollower::new(args.follow_offset, args.follow_smoothing)),
+            pet: args.pet.then(|| {
+                pet::Pet::new(
+                    args.pet_speed,
+                    Duration::from_secs_f32(args.pet_pause.max(0.0)),
+                    args.pet_floor,
+                )
+            }),
+            snap_threshold: args.snap,
+            window_dragged: false,
+            last_outer_pos: None,
+            moved_at: None,
+            state_input,
+            saved_state: None,
+            last_state_check: Instant::now(),
+            playback,
+            playing_backward: playback == Playback::Reverse,
+            shuffle_hold: args.hold.map(Duration::from_millis),
+            loop_limit: args.loops.or(args.on_complete.map(|_| 1)),
+            loops_completed: 0,
+            on_complete: args.on_complete.unwrap_or(OnComplete::Exit),
+            completion_hidden: false,
+            frame_hooks: args.on_frame_cmd,
+            hooked_frame: None,
+            script: args.script.as_deref().and_then(|path| {
+                script::Script::load(path)
+                    .map_err(|err| warn!("failed to load script {}: {}", path.display(), err))
+                    .ok()
+            }),
+            script_started: false,
+            scripted_frame: None,
+            script_commands: Vec::new(),
+            plugins,
+            status_json: args.status_json,
+            reported_state: None,
+            schedule: args.schedule,
+            schedule_hidden: false,
+            tray,
+            skip_taskbar: args.skip_taskbar,
+            user_hidden: false,
+            hotkeys,
+            control_commands,
+            _dbus: dbus,
+            _midi: midi,
+            fullscreen_watcher: args
+                .hide_on_fullscreen
+                .then(fullscreen::FullscreenWatcher::new),
+            fullscreen_app_hidden: false,
+            last_fullscreen_check: Instant::now(),
+            obs_scene,
+            obs_scenes: args.obs_scenes,
+            obs_hidden: false,
+            start_frame: args.start_frame,
+            end_frame: args.end_frame,
+            loading_complete: false,
+            first_frame_loaded: false,
+            load_error: None,
+            failed_loads: 0,
+            start_time,
+            total_frame: 0,
+            frames_loaded: 0,
+            last_fps_update: Instant::now(),
+            frame_count: 0,
+            last_memory_check: Instant::now(),
+        }
+    }
+
+    /// set up `--shader` once the renderer is running, falling back to plain
+    /// drawing if it can't be loaded
+    #[cfg(feature = "shader")]
+    fn with_shader(mut self, cc: &eframe::CreationContext<'_>, path: Option<PathBuf>) -> Self {
+        if let Some(path) = path {
+            match shader::install(cc, &path, self.texture_options) {
+                Ok(()) => self.shader = Some(shader::ShaderState::new()),
+                Err(err) => warn!("failed to load shader {}: {}", path.display(), err),
+            }
+        }
+        self
+    }
+
+    /// let `--shader` know the frame it last uploaded may be stale
+    fn textures_changed(&mut self) {
+        #[cfg(feature = "shader")]
+        if let Some(shader) = &mut self.shader {
+            shader.invalidate();
+        }
+    }
+
+    /// re-run the color filters over every loaded frame after they've changed
+    fn refilter_frames(&mut self) {
+        self.textures_changed();
+        for frame in self.frames.iter().flatten() {
+            let image = self.filters.image(&frame.pixels, frame.size);
+            frame.sprite.write(image, self.texture_options);
+        }
+    }
+
+    fn process_incoming_frames(&mut self, ctx: &egui::Context) {
+        let started = Instant::now();
+        let mut uploads = 0;
+        loop {
+            // the rest wait in the channel for the next update
+            if uploads >= MAX_UPLOADS_PER_UPDATE
+                || (uploads > 0 && started.elapsed() >= UPLOAD_BUDGET)
+            {
+                ctx.request_repaint();
+                return;
+            }
+            let Ok(message) = self.frame_receiver.try_recv() else {
+                return;
+            };
+            match message {
+                LoadingMessage::FrameReady(idx, pixels, size, delay) => {
+                    let Some(idx) = self.range_index(idx) else {
+                        continue;
+                    };
+
+                    while self.frames.len() <= idx {
+                        self.frames.push(None);
+                    }
+
+                    let _upload = tracing::trace_span!("upload", frame = idx).entered();
+                    let color_image = self.filters.image(&pixels, size);
+                    // animation frames share atlas pages; a still gets a texture of its own
+                    let sprite = if delay == STATIC_FRAME_DELAY {
+                        atlas::Sprite::single(ctx.load_texture(
+                            format!("gif_frame_{}", idx),
+                            color_image,
+                            self.texture_options,
+                        ))
+                    } else {
+                        self.atlas.insert(ctx, color_image, self.texture_options)
+                    };
+
+                    self.frames[idx] = Some(Frame {
+                        sprite,
+                        delay,
+                        pixels,
+                        size,
+                    });
+                    uploads += 1;
+
+                    self.frames_loaded += 1;
+                    self.textures_changed();
+
+                    if !self.first_frame_loaded {
+                        self.first_frame_loaded = true;
+                        self.failed_loads = 0;
+                        self.report_first_frame();
+                        self.cue_sound(sound::PlayOn::Start);
+                        self.notify_plugins(plugin::Event::Clip(self.current_input()));
+                    }
+
+                    if self.total_frame > 0 {
+                        if self.status_json {
+                            status_json::StatusLine::Progress {
+                                loaded: self.frames_loaded,
+                                total: self.total_frame,
+                            }
+                            .print();
+                        } else {
+                            debug!(
+                                "loading progress: {}/{} frames ({:.1}%)",
+                                self.frames_loaded,
+                                self.total_frame,
+                                (self.frames_loaded as f32 / self.total_frame as f32) * 100.0
+                            );
+                        }
+                    }
+                }
+                LoadingMessage::StreamFrame(idx, pixels, size, delay) => {
+                    // a paused video holds its frame; one-off renders (SVG) always apply
+                    if self.paused && self.first_frame_loaded && delay != STATIC_FRAME_DELAY {
+                        continue;
+                    }
+                    // a streamed clip counts its frames from 0 again each time
+                    // it starts over, which is where a loop is counted
+                    if self.first_frame_loaded
+                        && delay != STATIC_FRAME_DELAY
+                        && idx < self.frames_loaded
+                        && self.finish_loop(ctx)
+                    {
+                        continue;
+                    }
+
+                    let _upload = tracing::trace_span!("upload", frame = idx).entered();
+                    let color_image = self.filters.image(&pixels, size);
+
+                    // reuse a single texture so memory stays flat for long clips
+                    match self.frames.first_mut().and_then(Option::as_mut) {
+                        Some(frame) => {
+                            // through a shared handle, so it still updates while a
+                            // crossfade holds on to the frame too
+                            frame.sprite.write(color_image, self.texture_options);
+                            frame.sprite.size = frame.sprite.texture.size_vec2();
+                            frame.delay = delay;
+                            frame.pixels = pixels;
+                            frame.size = size;
+                        }
+                        None => {
+                            let texture =
+                                ctx.load_texture("video_frame", color_image, self.texture_options);
+                            self.frames = vec![Some(Frame {
+                                sprite: atlas::Sprite::single(texture),
+                                delay,
+                                pixels,
+                                size,
+                            })];
+                        }
+                    }
+
+                    self.current_frame = 0;
+                    self.last_update = Instant::now();
+                    self.frames_loaded = idx + 1;
+                    uploads += 1;
+                    self.textures_changed();
+                    // a one-off render is the whole still, so it can be held
+                    // and moved on from like any other
+                    if delay == STATIC_FRAME_DELAY {
+                        self.loading_complete = true;
+                        self.total_frame = 1;
+                    }
+
+                    if !self.first_frame_loaded {
+                        self.first_frame_loaded = true;
+                        self.failed_loads = 0;
+                        self.report_first_frame();
+                        self.cue_sound(sound::PlayOn::Start);
+                        self.notify_plugins(plugin::Event::Clip(self.current_input()));
+                    }
+                }
+                LoadingMessage::LoadingComplete(total_frames) => {
+                    if total_frames > 0 && self.start_frame >= total_frames {
+                        self.loading_failed(
+                            ctx,
+                            format!(
+                                "`--start-frame {}` is past the last frame, {}",
+                                self.start_frame,
+                                total_frames - 1
+                            ),
+                        );
+                        continue;
+                    }
+                    let end = self
+                        .end_frame
+                        .map_or(total_frames, |end| total_frames.min(end + 1));
+                    self.loading_complete = true;
+                    self.total_frame = end.saturating_sub(self.start_frame);
+                    if self.status_json {
+                        status_json::StatusLine::Loaded {
+                            frames: self.total_frame,
+                            elapsed_ms: self.start_time.elapsed().as_millis(),
+                        }
+                        .print();
+                    } else {
+                        log_time!(self.start_time, "all frame loaded");
+                    }
+                }
+                LoadingMessage::RenderScale(scale) => self.render_scale = scale,
+                LoadingMessage::Error(err) => self.loading_failed(ctx, err),
+            }
+        }
+    }
+
+    /// Deal with the current input failing to load: keep what's already
+    /// decoded, or move past it, or show `err` in place of the animation.
+    fn loading_failed(&mut self, ctx: &egui::Context, err: String) {
+        if self.status_json {
+            status_json::StatusLine::LoadError {
+                input: self.current_input(),
+                error: err.clone(),
+            }
+            .print();
+        }
+        error!("failed to load {}: {}", self.current_input().display(), err);
+
+        if self.first_frame_loaded {
+            // play the frames that made it
+            let total_frames = self.frames.len();
+            self.loading_complete = true;
+            self.total_frame = total_frames;
+            return;
+        }
+
+        self.failed_loads += 1;
+        if self.interrupted.is_some() {
+            self.start_next_alert(ctx);
+        } else if self.playlist.len() > 1 && self.failed_loads < self.playlist.len() {
+            self.start_next_clip(ctx);
+        } else {
+            LOAD_FAILED.store(true, Ordering::Relaxed);
+            self.load_error = Some(err);
+            // a one-shot run has nothing left to wait for
+            if self.loop_limit.is_some() && self.on_complete == OnComplete::Exit {
+                self.close(ctx);
+            }
+        }
+    }
+
+    fn report_first_frame(&self) {
+        if self.status_json {
+            status_json::StatusLine::FirstFrame {
+                input: self.current_input(),
+                elapsed_ms: self.start_time.elapsed().as_millis(),
+            }
+            .print();
+        } else {
+            log_time!(self.start_time, "First frame ready");
+        }
+    }
+
+    /// map a source frame index into the selected frame range
+    fn range_index(&self, idx: usize) -> Option<usize> {
+        if idx < self.start_frame || self.end_frame.is_some_and(|end| idx > end) {
+            return None;
+        }
+        Some(idx - self.start_frame)
+    }
+
+    /// play the `--sound` if it's set to go off on `event`
+    fn cue_sound(&mut self, event: sound::PlayOn) {
+        if let Some(sound) = &mut self.sound {
+            sound.cue(event);
+        }
+    }
+
+    fn toggle_hud(&mut self) {
+        self.hud = match self.hud.take() {
+            Some(_) => None,
+            None => Some(hud::Hud::default()),
+        };
+    }
+
+    /// what the performance HUD shows about playback
+    fn hud_stats(&self) -> hud::Stats {
+        hud::Stats {
+            frame: self.current_frame,
+            frames: self.frames.len(),
+            loaded: self.frames_loaded,
+            total: self.loading_complete.then_some(self.total_frame),
+            decoded_bytes: self.decoded_bytes(),
+            texture_bytes: self.texture_bytes(),
+            resident_bytes: resident_bytes(),
+        }
+    }
+
+    /// RGBA bytes of the decoded frames kept for playback and refiltering
+    fn decoded_bytes(&self) -> usize {
+        self.frames
+            .iter()
+            .flatten()
+            .map(|frame| frame.pixels.len())
+            .sum()
+    }
+
+    /// GPU memory the frames' textures take up, estimated from their size at
+    /// four bytes a pixel, counting each shared atlas page once
+    fn texture_bytes(&self) -> usize {
+        let mut counted = HashSet::new();
+        self.frames
+            .iter()
+            .flatten()
+            .filter(|frame| counted.insert(frame.sprite.texture.id()))
+            .map(|frame| {
+                let [width, height] = frame.sprite.texture.size();
+                width * height * 4
+            })
+            .sum()
+    }
+
+    fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        // restart the current frame's timer so resuming doesn't jump ahead
+        self.last_update = Instant::now();
+        info!(
+            "Playback {}",
+            if self.paused { "paused" } else { "resumed" }
+        );
+    }
+
+    /// jump to `idx` if that frame has been loaded
+    fn seek(&mut self, idx: usize) {
+        if self.frames.get(idx).is_some_and(Option::is_some) {
+            self.current_frame = idx;
+            self.last_update = Instant::now();
+        }
+    }
+
+    /// move one frame forward or backward, wrapping at either end
+    fn step(&mut self, forward: bool) {
+        let len = self.frames.len();
+        if len == 0 {
+            return;
+        }
+
+        let idx = if forward {
+            (self.current_frame + 1) % len
+        } else {
+            (self.current_frame + len - 1) % len
+        };
+        self.seek(idx);
+    }
+
+    /// switch from the idle animation to the active one, and play a
+    /// `--sound-on trigger` sound
+    fn trigger_active(&mut self, ctx: &egui::Context) {
+        self.cue_sound(sound::PlayOn::Trigger);
+        self.run_script(ctx, "on_trigger", None);
+        self.notify_plugins(plugin::Event::Trigger);
+        if self.trigger_mode && self.playlist_index == 0 {
+            info!("Triggered active animation");
+            self.start_next_clip(ctx);
+        }
+    }
+
+    fn handle_input(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| i.key_pressed(egui::Key::Space)) {
+            self.toggle_pause();
+        }
+        self.handle_zoom(ctx);
+        let commands: Vec<StdinCommand> = self
+            .stdin_commands
+            .as_ref()
+            .map_or_else(Vec::new, |receiver| receiver.try_iter().collect());
+        let mut triggered = ctx.input(|i| i.key_pressed(egui::Key::T));
+        for command in commands {
+            match command {
+                StdinCommand::Trigger => triggered = true,
+                StdinCommand::Set {
+                    setting,
+                    value,
+                    relative,
+                } => self.apply_setting(setting, value, relative),
+                StdinCommand::Control(command) => self.run_command(ctx, command),
+            }
+        }
+        if triggered {
+            self.trigger_active(ctx);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::F)) {
+            self.filters.filter = self.filters.filter.next();
+            info!("Filter: {:?}", self.filters.filter);
+            self.refilter_frames();
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::Minus)) {
+            self.set_opacity(self.opacity - OPACITY_STEP);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::PlusEquals)) {
+            self.set_opacity(self.opacity + OPACITY_STEP);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::C)) {
+            self.controls_visible = !self.controls_visible;
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::H)) {
+            self.toggle_hud();
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::L)) {
+            self.locked = !self.locked;
+            info!("Window {}", if self.locked { "locked" } else { "unlocked" });
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::P)) {
+            self.always_on_top = !self.always_on_top;
+            info!(
+                "Always on top {}",
+                if self.always_on_top { "on" } else { "off" }
+            );
+            ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(if self.always_on_top {
+                egui::viewport::WindowLevel::AlwaysOnTop
+            } else {
+                egui::viewport::WindowLevel::Normal
+            }));
+        }
+
+        if self.paused {
+            // egui has no key codes for `,`/`.`, so match the typed text instead
+            let (back, forward) = ctx.input(|i| {
+                let typed = |c: &str| {
+                    i.events
+                        .iter()
+                        .any(|event| matches!(event, egui::Event::Text(text) if text == c))
+                };
+                (
+                    i.key_pressed(egui::Key::ArrowLeft) || typed(","),
+                    i.key_pressed(egui::Key::ArrowRight) || typed("."),
+                )
+            });
+            if back {
+                self.step(false);
+            }
+            if forward {
+                self.step(true);
+            }
+        }
+    }
+
+    /// resize the overlay with the scroll wheel while the pointer is over it
+    fn handle_zoom(&mut self, ctx: &egui::Context) {
+        let factor = ctx.input(|i| {
+            if i.pointer.hover_pos().is_none() {
+                return 1.0;
+            }
+            // egui turns ctrl+scroll into a zoom gesture, and some platforms
+            // turn shift+scroll into horizontal scrolling
+            let scroll = i.scroll_delta.x + i.scroll_delta.y;
+            let held = match self.zoom_modifier {
+                ZoomModifier::None => true,
+                ZoomModifier::Ctrl => i.modifiers.ctrl || i.modifiers.command,
+                ZoomModifier::Shift => i.modifiers.shift,
+                ZoomModifier::Alt => i.modifiers.alt,
+            };
+            if !held {
+                return 1.0;
+            }
+            i.zoom_delta() * 2f32.powf(scroll / ZOOM_SCROLL_PER_DOUBLING)
+        });
+
+        if factor != 1.0 && !self.fullscreen {
+            self.scale = (self.scale * factor).clamp(MIN_SCALE, MAX_SCALE);
+        }
+    }
+
+    /// scrub slider, play/pause and loop toggle, drawn under the animation
+    fn show_controls(&mut self, ui: &mut egui::Ui) {
+        egui::Frame::none()
+            .fill(ui.visuals().window_fill())
+            .rounding(4.0)
+            .inner_margin(4.0)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let label = if self.paused { "▶" } else { "⏸" };
+                    if ui.button(label).clicked() {
+                        self.toggle_pause();
+                    }
+
+                    let last = self.frames.len().saturating_sub(1);
+                    let mut frame = self.current_frame;
+                    if ui
+                        .add(egui::Slider::new(&mut frame, 0..=last).text("frame"))
+                        .changed()
+                    {
+                        self.seek(frame);
+                    }
+
+                    ui.checkbox(&mut self.looping, "loop");
+                });
+
+                let levels = self.filters.levels;
+                let mut adjusted = levels;
+                let mut released = false;
+                ui.horizontal(|ui| {
+                    for (value, range, label) in [
+                        (&mut adjusted.brightness, BRIGHTNESS_RANGE, "brightness"),
+                        (&mut adjusted.contrast, CONTRAST_RANGE, "contrast"),
+                        (&mut adjusted.gamma, GAMMA_RANGE, "gamma"),
+                    ] {
+                        let response = ui.add(egui::Slider::new(value, range).text(label));
+                        // refiltering every frame is slow, so wait until the drag ends
+                        released |=
+                            response.drag_released() || (response.changed() && !response.dragged());
+                    }
+                });
+                self.filters.levels = adjusted;
+                if released && adjusted != levels {
+                    self.refilter_frames();
+                }
+            });
+    }
+
+    /// The frame to show after the current one, or `None` to hold on the
+    /// current one; see `pick_next_frame`.
+    fn get_next_available_frame(&mut self) -> Option<usize> {
+        let (next, backward) = pick_next_frame(
+            &self.frames,
+            self.loading_complete,
+            self.current_frame,
+            self.playback,
+            self.playing_backward,
+        )?;
+        self.playing_backward = backward;
+        Some(next)
+    }
+
+    /// whether stepping to `next` starts the animation over
+    fn wraps_to_start(&self, next: usize) -> bool {
+        match self.playback {
+            Playback::Forward => next <= self.current_frame,
+            Playback::Reverse => next >= self.current_frame,
+            Playback::Pingpong => self.frames.len() == 1 || (next == 0 && self.current_frame != 0),
+            // random order has no natural end, so it never counts as a loop
+            Playback::Shuffle => false,
+        }
+    }
+
+    /// how long the current frame stays on screen, `None` before it's loaded
+    fn current_delay(&self) -> Option<Duration> {
+        let delay = self.frames.get(self.current_frame)?.as_ref()?.delay;
+        if delay == STATIC_FRAME_DELAY {
+            // stills in a playlist, or shown as an alert, make way for the
+            // next entry after a while
+            return Some(if self.playlist.len() > 1 || self.interrupted.is_some() {
+                self.shuffle_hold.unwrap_or(PLAYLIST_STILL_DURATION)
+            } else {
+                delay
+            });
+        }
+        match self.shuffle_hold {
+            Some(hold) if self.playback == Playback::Shuffle => Some(hold),
+            _ => Some(delay),
+        }
+    }
+
+    /// swap in the preloaded next playlist entry and start preloading the one after
+    fn start_next_clip(&mut self, ctx: &egui::Context) {
+        self.playlist_index = self.next_index;
+        self.next_index = pick_next_index(
+            self.playlist.len(),
+            self.playlist_index,
+            self.shuffle_playlist,
+        );
+
+        let (receiver, rescale_sender) = self.preloaded.take().unwrap_or_else(|| {
+            spawn_loader(
+                ctx,
+                self.playlist[self.playlist_index].clone(),
+                self.loader_options,
+            )
+        });
+        self.preloaded = self
+            .playlist
+            .get(self.next_index)
+            .filter(|path| self.playlist.len() > 1 && !plays_as_decoded(path, self.loader_options))
+            .map(|path| spawn_loader(ctx, path.clone(), self.loader_options));
+
+        debug!(
+            "Switching to playlist entry {}: {}",
+            self.playlist_index + 1,
+            self.playlist[self.playlist_index].display()
+        );
+
+        if let Some(duration) = self.crossfade_duration {
+            self.crossfade = self
+                .frames
+                .get(self.current_frame)
+                .and_then(Option::as_ref)
+                .map(|frame| Crossfade {
+                    sprite: frame.sprite.clone(),
+                    size: self.display_size(frame.sprite.size),
+                    started: Instant::now(),
+                    duration,
+                });
+        }
+
+        self.frame_receiver = receiver;
+        self.rescale_sender = rescale_sender;
+        self.rendered_scale = self.loader_options.scale;
+        self.render_scale = 1.0;
+        self.clip_started = Instant::now();
+        self.frames.clear();
+        self.atlas.recycle();
+        self.current_frame = 0;
+        self.hooked_frame = None;
+        self.scripted_frame = None;
+        self.last_update = Instant::now();
+        self.playing_backward = self.playback == Playback::Reverse;
+        self.loading_complete = false;
+        self.first_frame_loaded = false;
+        self.load_error = None;
+        self.total_frame = 0;
+        self.frames_loaded = 0;
+
+        // upload whatever the preload has decoded so far, avoiding a spinner flash
+        self.process_incoming_frames(ctx);
+    }
+
+    /// whether `--interval` says it's time to move on to the next entry
+    fn interval_elapsed(&self) -> bool {
+        self.playlist.len() > 1
+            && !self.trigger_mode
+            && self
+                .switch_interval
+                .is_some_and(|interval| self.clip_started.elapsed() >= interval)
+    }
+
+    fn advance_frame(&mut self, ctx: &egui::Context) {
+        if !self.first_frame_loaded || self.paused {
+            return;
+        }
+
+        if self.interval_elapsed() {
+            self.start_next_clip(ctx);
+            return;
+        }
+
+        // frames are due a fixed time after the one before was due, not after
+        // it was shown, so late repaints don't add up over a long animation;
+        // frames whose time has already passed are skipped
+        let now = Instant::now();
+        loop {
+            let Some(delay) = self.current_delay() else {
+                return;
+            };
+            let Some(due) = self.last_update.checked_add(delay) else {
+                return;
+            };
+            if due > now {
+                return;
+            }
+            let Some(next_frame) = self.get_next_available_frame() else {
+                return;
+            };
+            // loops can only be counted once the full length is known
+            if self.loading_complete && self.wraps_to_start(next_frame) && self.finish_loop(ctx) {
+                return;
+            }
+
+            self.current_frame = next_frame;
+            // a stall (a slow load, or the machine sleeping) isn't caught up on
+            self.last_update = if now.duration_since(due) > MAX_PLAYBACK_LAG {
+                now
+            } else {
+                due
+            };
+            // with no delay, the timeline would never move on
+            if delay.is_zero() {
+                return;
+            }
+        }
+    }
+
+    /// Deal with the current clip having played through once, returning
+    /// whether it stops here: for an alert, at the end of `--loops`, or to
+    /// move on to the next playlist entry.
+    fn finish_loop(&mut self, ctx: &egui::Context) -> bool {
+        // an alert plays once, then the next one or the playlist
+        if self.interrupted.is_some() {
+            self.start_next_alert(ctx);
+            return true;
+        }
+        if !self.looping {
+            // hold on the final frame until looping is re-enabled
+            self.paused = true;
+            return true;
+        }
+
+        // with a playlist, a loop is one pass over every entry; a
+        // shuffled folder has no end, so every entry counts
+        if self.shuffle_playlist || self.playlist_index + 1 >= self.playlist.len() {
+            self.loops_completed += 1;
+            if self
+                .loop_limit
+                .is_some_and(|limit| self.loops_completed >= limit)
+                && self.complete_playback(ctx)
+            {
+                return true;
+            }
+        }
+
+        self.cue_sound(sound::PlayOn::Loop);
+        self.run_script(ctx, "on_loop", Some(self.loops_completed as usize));
+        self.notify_plugins(plugin::Event::Loop(self.loops_completed as usize));
+
+        // the idle half of an idle/active pair keeps looping until triggered
+        let holding_idle = self.trigger_mode && self.playlist_index == 0;
+        if self.playlist.len() > 1 && !holding_idle {
+            self.start_next_clip(ctx);
+            return true;
+        }
+        false
+    }
+
+    /// run the `--on-frame-cmd` hooks when a new frame comes on screen
+    fn run_frame_hooks(&mut self) {
+        if self.frame_hooks.is_empty()
+            || !self.first_frame_loaded
+            || self.hooked_frame == Some(self.current_frame)
+        {
+            return;
+        }
+        self.hooked_frame = Some(self.current_frame);
+
+        // hooks refer to frames of the source, not of the `--start-frame` range
+        let frame = self.current_frame + self.start_frame;
+        let input = &self.playlist[self.playlist_index];
+        for hook in self.frame_hooks.iter().filter(|hook| hook.frame == frame) {
+            debug!("Frame {} reached, running hook", frame);
+            hook.run(input);
+        }
+    }
+
+    /// call the `--script`'s `on_start` once the first frame is up, then its
+    /// `on_frame` whenever a new frame comes on screen
+    fn step_script(&mut self, ctx: &egui::Context) {
+        if self.script.is_none() || !self.first_frame_loaded {
+            return;
+        }
+        if !self.script_started {
+            self.script_started = true;
+            self.run_script(ctx, "on_start", None);
+        }
+        if self.scripted_frame != Some(self.current_frame) {
+            self.scripted_frame = Some(self.current_frame);
+            self.run_script(ctx, "on_frame", Some(self.current_frame));
+        }
+    }
+
+    /// call one of the `--script`'s callbacks, queueing whatever it asks for
+    fn run_script(&mut self, ctx: &egui::Context, callback: &str, arg: Option<usize>) {
+        let Some(script) = &self.script else {
+            return;
+        };
+        let status = self.status();
+        let state = script::State {
+            position: ctx.input(|i| i.viewport().outer_rect.map(|rect| rect.min)),
+            screen: ctx.input(|i| i.viewport().monitor_size),
+            visible: status.visible,
+            paused: status.paused,
+            opacity: status.opacity,
+            scale: status.scale,
+            input: status.input,
+            frame: status.frame,
+            frames: status.frames,
+        };
+        let commands = script.call(callback, arg, state);
+        if !commands.is_empty() {
+            self.script_commands.extend(commands);
+            ctx.request_repaint();
+        }
+    }
+
+    fn notify_plugins(&self, event: plugin::Event) {
+        for plugin in self.plugins.iter() {
+            plugin.on_event(&event);
+        }
+    }
+
+    /// Apply `--on-complete` once the loop limit is reached, returning whether
+    /// playback should stop here.
+    fn complete_playback(&mut self, ctx: &egui::Context) -> bool {
+        info!(
+            "Finished {} loops, on complete: {:?}",
+            self.loops_completed, self.on_complete
+        );
+
+        match self.on_complete {
+            OnComplete::Exit => self.close(ctx),
+            OnComplete::Hide => {
+                self.completion_hidden = true;
+                self.sync_mouse_passthrough(ctx);
+            }
+            OnComplete::HoldLast => self.paused = true,
+            OnComplete::Loop => {
+                self.loops_completed = 0;
+                return false;
+            }
+        }
+        true
+    }
+
+    /// draw the current frame, blended with the outgoing entry during a crossfade
+    fn paint_frame(&mut self, ui: &mut egui::Ui) {
+        let Some(current) = self.frames[self.current_frame].as_ref() else {
+            return;
+        };
+        let size = self.display_size(current.sprite.size);
+        let mut orientation = self.orientation;
+        // a walking pet is mirrored while it heads left
+        if self.pet.as_ref().is_some_and(pet::Pet::facing_left) {
+            orientation.flip_h = !orientation.flip_h;
+        }
+        if let Some(speed) = self.spin {
+            let degrees = self.start_time.elapsed().as_secs_f32() * speed;
+            orientation.angle = degrees.rem_euclid(360.0).to_radians();
+            ui.ctx().request_repaint();
+        }
+        let shadow = if self.shadow {
+            egui::Vec2::splat(SHADOW_OFFSET)
+        } else {
+            egui::Vec2::ZERO
+        };
+        let (bounce, max_bounce, loudness) = self.audio.as_ref().map_or((1.0, 1.0, 1.0), |audio| {
+            (audio.scale(), audio.max_scale(), audio.opacity())
+        });
+        // a spinning corner may point anywhere, so leave room for the diagonal
+        let room = |size: egui::Vec2| {
+            let size = match self.spin {
+                Some(_) => egui::Vec2::splat(size.length()),
+                None => size,
+            };
+            size * max_bounce + shadow
+        };
+        // move the frame along while the overlay is coming in or going away,
+        // and with the music for `--audio-react`
+        let transition = if self.is_hidden() {
+            self.exit
+        } else {
+            self.enter
+        };
+        let bounds = ui.ctx().screen_rect();
+        let place = |rect: egui::Rect| {
+            let rect = egui::Rect::from_center_size(rect.center(), rect.size() * bounce);
+            let (rect, alpha) = transition.apply(self.visibility, rect, bounds);
+            (rect, alpha * loudness)
+        };
+
+        let mut fade_finished = false;
+        let response = match &self.crossfade {
+            None => {
+                let (rect, response) = ui.allocate_exact_size(room(size), egui::Sense::drag());
+                let (image_rect, alpha) = place(egui::Rect::from_center_size(
+                    rect.center() - shadow / 2.0,
+                    size,
+                ));
+
+                #[cfg(feature = "shader")]
+                let drawn = match &mut self.shader {
+                    Some(shader) => {
+                        let filters = &self.filters;
+                        ui.painter().add(shader.callback(
+                            image_rect,
+                            self.current_frame,
+                            self.opacity * alpha,
+                            || filters.image(&current.pixels, current.size),
+                        ));
+                        // shaders usually animate over time
+                        ui.ctx().request_repaint();
+                        true
+                    }
+                    None => false,
+                };
+                #[cfg(not(feature = "shader"))]
+                let drawn = false;
+
+                if !drawn {
+                    self.draw_texture(
+                        ui.painter(),
+                        &orientation,
+                        &current.sprite,
+                        self.frame_uv(current),
+                        image_rect,
+                        alpha,
+                    );
+                }
+                response
+            }
+            Some(fade) => {
+                let progress = fade.progress();
+                let (rect, response) =
+                    ui.allocate_exact_size(room(size.max(fade.size)), egui::Sense::drag());
+                let center = rect.center() - shadow / 2.0;
+
+                for (sprite, uv, size, alpha) in [
+                    (
+                        &fade.sprite,
+                        self.crop_uv(fade.sprite.size),
+                        fade.size,
+                        1.0 - progress,
+                    ),
+                    (&current.sprite, self.frame_uv(current), size, progress),
+                ] {
+                    let (rect, transition_alpha) =
+                        place(egui::Rect::from_center_size(center, size));
+                    self.draw_texture(
+                        ui.painter(),
+                        &orientation,
+                        sprite,
+                        uv,
+                        rect,
+                        alpha * transition_alpha,
+                    );
+                }
+
+                if progress >= 1.0 {
+                    fade_finished = true;
+                } else {
+                    ui.ctx().request_repaint();
+                }
+                response
+            }
+        };
+
+        if fade_finished {
+            self.crossfade = None;
+        }
+        if self.ken_burns.is_some() && current.delay == STATIC_FRAME_DELAY {
+            ui.ctx().request_repaint();
+        }
+        // dragging the animation moves the whole overlay window
+        if response.drag_started()
+            && !self.fullscreen
+            && !self.locked
+            && self.follower.is_none()
+            && self.pet.is_none()
+        {
+            ui.ctx().send_viewport_cmd(egui::ViewportCommand::StartDrag);
+            // remember where the drag began so a plain click doesn't count as a move
+            self.window_dragged = true;
+            self.last_outer_pos = ui.ctx().input(|i| i.viewport().outer_rect.map(|r| r.min));
+            self.moved_at = None;
+        }
+    }
+
+    /// draw the `--timer` or `--clock` centered below the animation
+    fn paint_clock(&self, ui: &mut egui::Ui) {
+        let Some(clock) = &self.clock else {
+            return;
+        };
+        self.paint_text(
+            ui,
+            clock.text(),
+            &self.clock_font,
+            self.clock_color,
+            f32::INFINITY,
+        );
+        ui.ctx().request_repaint_after(clock.until_next_tick());
+    }
+
+    /// the playing alert's caption, wrapped to the animation's width
+    fn paint_caption(&self, ui: &mut egui::Ui) {
+        if let Some(caption) = &self.caption {
+            let width = ui.min_rect().width().max(1.0);
+            self.paint_text(
+                ui,
+                caption.clone(),
+                &self.caption_font,
+                self.caption_color,
+                width,
+            );
+        }
+    }
+
+    /// text centered under what's been drawn so far, wrapped at
+    /// `wrap_width` and shadowed along with the frame
+    fn paint_text(
+        &self,
+        ui: &mut egui::Ui,
+        text: String,
+        font: &egui::FontId,
+        color: egui::Color32,
+        wrap_width: f32,
+    ) {
+        let opacity = self.opacity * self.visibility;
+        let painter = ui.painter().clone();
+        let layout =
+            |color: egui::Color32| painter.layout(text.clone(), font.clone(), color, wrap_width);
+        let galley = layout(color.gamma_multiply(opacity));
+        let size = galley.size();
+        let width = size.x.max(ui.min_rect().width());
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(width, size.y), egui::Sense::hover());
+        let pos = rect.center_top() - egui::vec2(size.x / 2.0, 0.0);
+
+        if self.shadow {
+            let shadow_color = egui::Color32::BLACK.gamma_multiply(SHADOW_OPACITY * opacity);
+            painter.galley(pos + egui::Vec2::splat(SHADOW_OFFSET), layout(shadow_color));
+        }
+        painter.galley(pos, galley);
+    }
+
+    /// draw the `uv` part of `sprite`'s frame into `rect` at `alpha` times
+    /// the opacity, on top of its drop shadow if there is one
+    fn draw_texture(
+        &self,
+        painter: &egui::Painter,
+        orientation: &transform::Orientation,
+        sprite: &atlas::Sprite,
+        uv: egui::Rect,
+        rect: egui::Rect,
+        alpha: f32,
+    ) {
+        let opacity = self.opacity * alpha;
+        let texture = sprite.texture.id();
+        let uv = sprite.map(uv);
+
+        if self.shadow {
+            // the frame's own alpha, tinted black, gives the shadow its shape
+            let shadow_color = egui::Color32::BLACK.gamma_multiply(SHADOW_OPACITY * opacity);
+            let mut mesh = orientation.mesh(texture, uv, rect, self.mask, shadow_color);
+            mesh.translate(egui::Vec2::splat(SHADOW_OFFSET));
+            painter.add(mesh);
+        }
+        painter.add(orientation.mesh(
+            texture,
+            uv,
+            rect,
+            self.mask,
+            self.tint.gamma_multiply(opacity),
+        ));
+    }
+
+    /// part of `frame` on screen right now: the `--crop`, panned and zoomed
+    /// over by `--ken-burns` if it's a still
+    fn frame_uv(&self, frame: &Frame) -> egui::Rect {
+        let uv = self.crop_uv(frame.sprite.size);
+        match self.ken_burns {
+            Some(ken_burns) if frame.delay == STATIC_FRAME_DELAY => {
+                ken_burns.view(uv, self.clip_started.elapsed().as_secs_f32())
+            }
+            _ => uv,
+        }
+    }
+
+    /// part of a frame of `size` pixels left after `--crop`, as 0.0..=1.0
+    /// across it
+    fn crop_uv(&self, size: egui::Vec2) -> egui::Rect {
+        let full = egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0));
+        let Some(crop) = self.crop else {
+            return full;
+        };
+        // vector sources are rasterized at `render_scale` times their size
+        let size = size / self.render_scale;
+        let uv = egui::Rect::from_min_max(
+            (crop.min.to_vec2() / size).to_pos2(),
+            (crop.max.to_vec2() / size).to_pos2(),
+        );
+        let uv = uv.intersect(full);
+        // a crop that misses the frame entirely shows all of it
+        if uv.is_positive() {
+            uv
+        } else {
+            full
+        }
+    }
+
+    /// on-screen size of a frame of `size` pixels at the current `--scale`
+    /// once cropped and rotated, or fitted to the screen in fullscreen mode
+    fn display_size(&self, size: egui::Vec2) -> egui::Vec2 {
+        let cropped = self.crop_uv(size).size() * size;
+        let size = self.orientation.rotated_size(cropped);
+        match self.fit_area {
+            Some(area) => size * (area.x / size.x).min(area.y / size.y),
+            None => size * (self.scale / self.render_scale),
+        }
+    }
+
+    /// shrink or grow the window to fit what was drawn, when auto-sizing
+    fn fit_window(&mut self, ctx: &egui::Context, content: egui::Rect) {
+        if !self.auto_size || !self.first_frame_loaded {
+            return;
+        }
+
+        let size = content.size().round();
+        if self.window_size != Some(size) {
+            debug!("Resizing window to {}x{}", size.x, size.y);
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
+            self.window_size = Some(size);
+        }
+    }
+
+    /// The screen the overlay is placed on: the `--monitor` if one was picked,
+    /// otherwise the current monitor, assumed to sit at the desktop origin.
+    fn screen(&self, ctx: &egui::Context) -> Option<egui::Rect> {
+        self.monitor
+            .as_ref()
+            .map(|monitor| monitor.rect)
+            .or_else(|| {
+                ctx.input(|i| i.viewport().monitor_size)
+                    .map(|size| egui::Rect::from_min_size(egui::Pos2::ZERO, size))
+            })
+    }
+
+    /// walk the window along the bottom of the screen in `--pet` mode
+    fn walk_pet(&mut self, ctx: &egui::Context) {
+        if self.pet.is_none() || self.is_hidden() {
+            return;
+        }
+
+        let outer = ctx.input(|i| i.viewport().outer_rect);
+        let (Some(screen), Some(outer)) = (self.screen(ctx), outer) else {
+            ctx.request_repaint();
+            return;
+        };
+        let Some(pet) = &mut self.pet else {
+            return;
+        };
+
+        let position = pet.step(outer.min, screen, outer.size());
+        if position.distance(outer.min) >= 0.5 {
+            ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(position));
+        }
+        ctx.request_repaint_after(FOLLOW_POLL_INTERVAL);
+    }
+
+    /// move the window to its `--position` anchor once the screen size is known
+    fn apply_anchor(&mut self, ctx: &egui::Context) {
+        let Some(anchor) = self.pending_anchor else {
+            return;
+        };
+
+        // an auto-sized window isn't placed until it has its final size
+        let window = if self.auto_size {
+            self.window_size
+        } else {
+            ctx.input(|i| i.viewport().outer_rect.map(|rect| rect.size()))
+        };
+        let (Some(screen), Some(window)) = (self.screen(ctx), window) else {
+            // the backend fills these in after the first frame
+            ctx.request_repaint_after(Duration::from_millis(100));
+            return;
+        };
+
+        let position = anchor.position(screen, window, self.margin);
+        info!(
+            "Placing overlay at {:?}: ({:.0}, {:.0})",
+            anchor, position.x, position.y
+        );
+        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(position));
+        self.pending_anchor = None;
+    }
+
+    /// keep the window trailing after the mouse cursor in `--follow-cursor` mode
+    fn follow_cursor(&mut self, ctx: &egui::Context) {
+        // no polling the cursor while there's nothing to move
+        if self.is_hidden() {
+            return;
+        }
+        let Some(follower) = &mut self.follower else {
+            return;
+        };
+
+        let (outer, pixels_per_point) = ctx.input(|i| {
+            let viewport = i.viewport();
+            (viewport.outer_rect, viewport.native_pixels_per_point)
+        });
+        let Some(outer) = outer else {
+            ctx.request_repaint();
+            return;
+        };
+
+        let position = follower.step(outer.min, pixels_per_point.unwrap_or(1.0));
+        if position.distance(outer.min) >= 0.5 {
+            ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(position));
+        }
+        // the cursor can move anywhere on screen without egui hearing about it
+        ctx.request_repaint_after(FOLLOW_POLL_INTERVAL);
+    }
+
+    /// Snap a dragged window to nearby screen edges once it's been dropped.
+    ///
+    /// The OS moves the window during the drag without telling egui when it
+    /// ends, so the drop is taken to be the moment the position stops changing.
+    fn snap_to_edges(&mut self, ctx: &egui::Context) {
+        if self.snap_threshold <= 0.0 || !self.window_dragged {
+            return;
+        }
+
+        let Some(outer) = ctx.input(|i| i.viewport().outer_rect) else {
+            return;
+        };
+
+        if self.last_outer_pos != Some(outer.min) {
+            self.last_outer_pos = Some(outer.min);
+            self.moved_at = Some(Instant::now());
+        }
+        let Some(moved_at) = self.moved_at else {
+            return;
+        };
+        let settled = moved_at.elapsed();
+        if settled < SNAP_SETTLE_TIME {
+            ctx.request_repaint_after(SNAP_SETTLE_TIME - settled);
+            return;
+        }
+        self.moved_at = None;
+        self.window_dragged = false;
+
+        let Some(position) = self
+            .screen(ctx)
+            .and_then(|screen| placement::snap(outer, screen, self.snap_threshold))
+        else {
+            return;
+        };
+
+        debug!("Snapping window to ({:.0}, {:.0})", position.x, position.y);
+        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(position));
+        self.last_outer_pos = Some(position);
+    }
+
+    /// save the window position, scale and opacity for the next run once they change
+    fn persist_window_state(&mut self, ctx: &egui::Context) {
+        let Some(input) = self.state_input.as_ref().filter(|_| !self.fullscreen) else {
+            return;
+        };
+        if self.last_state_check.elapsed() < STATE_SAVE_INTERVAL {
+            return;
+        }
+        self.last_state_check = Instant::now();
+
+        let window_state = state::WindowState {
+            position: ctx.input(|i| i.viewport().outer_rect.map(|rect| rect.min)),
+            scale: self.scale,
+            opacity: self.opacity,
+        };
+        if self.saved_state == Some(window_state) {
+            return;
+        }
+
+        match state::save(input, &window_state) {
+            Ok(()) => self.saved_state = Some(window_state),
+            Err(err) => {
+                warn!("failed to save window state: {}", err);
+                // don't retry every couple of seconds
+                self.state_input = None;
+            }
+        }
+    }
+
+    /// show or hide the overlay according to `--schedule`
+    fn apply_schedule(&mut self, ctx: &egui::Context) {
+        let Some(schedule) = &self.schedule else {
+            return;
+        };
+
+        let now = chrono::Local::now().time();
+        let hidden = !schedule.is_active(now);
+        // wake at the next boundary, and at least once a minute in case the clock jumps
+        ctx.request_repaint_after(schedule.until_next_change(now).min(Duration::from_secs(60)));
 
-        // validate opacity
-        let opacity = opacity.clamp(0.0, 1.0);
-        // ensure scale is positive
-        let scale = scale.max(0.1);
+        if hidden != self.schedule_hidden {
+            self.schedule_hidden = hidden;
+            info!(
+                "Schedule: overlay {}",
+                if hidden { "hidden" } else { "shown" }
+            );
+            self.sync_mouse_passthrough(ctx);
+            // don't fast-forward through the frames missed while hidden
+            self.last_update = Instant::now();
+        }
+    }
 
-        let (sender, receiver) = channel();
-        let gif_path_clone = gif_path.clone();
+    /// hide while the focused window of another application is fullscreen
+    fn watch_fullscreen_apps(&mut self, ctx: &egui::Context) {
+        let Some(watcher) = &self.fullscreen_watcher else {
+            return;
+        };
+        // keep polling even while hidden, when nothing else asks for repaints
+        ctx.request_repaint_after(FULLSCREEN_POLL_INTERVAL);
+        if self.last_fullscreen_check.elapsed() < FULLSCREEN_POLL_INTERVAL {
+            return;
+        }
+        self.last_fullscreen_check = Instant::now();
 
-        println!("Spawning background loader thread...");
+        let hidden = watcher.foreground_is_fullscreen();
+        if hidden != self.fullscreen_app_hidden {
+            self.fullscreen_app_hidden = hidden;
+            info!(
+                "Fullscreen application {}: overlay {}",
+                if hidden { "focused" } else { "gone" },
+                if hidden { "hidden" } else { "shown" }
+            );
+            self.sync_mouse_passthrough(ctx);
+            self.last_update = Instant::now();
+        }
+    }
 
-        thread::spawn(move || {
-            let load_start = Instant::now();
-            let file = File::open(gif_path_clone).expect("failed to open GIF file");
+    /// hide while OBS is live on a scene that isn't one of `--obs-scenes`
+    fn follow_obs_scene(&mut self, ctx: &egui::Context) {
+        let Some(scene) = self
+            .obs_scene
+            .as_ref()
+            .and_then(|receiver| receiver.try_iter().last())
+        else {
+            return;
+        };
 
-            println!("File opened in: {:.2?}", load_start.elapsed());
+        let hidden = !self.obs_scenes.contains(&scene);
+        if hidden != self.obs_hidden {
+            self.obs_hidden = hidden;
+            info!(
+                "OBS scene {:?}: overlay {}",
+                scene,
+                if hidden { "hidden" } else { "shown" }
+            );
+            self.sync_mouse_passthrough(ctx);
+            self.last_update = Instant::now();
+        }
+    }
 
-            let decoder = GifDecoder::new(file).expect("failed to create GIF decoder");
-            let frames = decoder.into_frames();
+    /// whether anything currently keeps the overlay off screen
+    fn is_hidden(&self) -> bool {
+        self.closing
+            || self.schedule_hidden
+            || self.completion_hidden
+            || self.user_hidden
+            || self.fullscreen_app_hidden
+            || self.obs_hidden
+    }
 
-            let mut frame_count = 0;
-            let process_start = Instant::now();
+    /// change a setting from stdin, by `value` if `relative` and to it otherwise
+    fn apply_setting(&mut self, setting: Setting, value: f32, relative: bool) {
+        let levels = &mut self.filters.levels;
+        let (current, range) = match setting {
+            Setting::Opacity => (&mut self.opacity, MIN_OPACITY..=1.0),
+            Setting::Brightness => (&mut levels.brightness, BRIGHTNESS_RANGE),
+            Setting::Contrast => (&mut levels.contrast, CONTRAST_RANGE),
+            Setting::Gamma => (&mut levels.gamma, GAMMA_RANGE),
+        };
+        let value = if relative { *current + value } else { value };
+        *current = value.clamp(*range.start(), *range.end());
+        info!("{:?} set to {:.2}", setting, current);
 
-            for (idx, frame) in frames.enumerate() {
-                frame_count = idx + 1;
-                let frame_start = Instant::now();
+        if !matches!(setting, Setting::Opacity) {
+            self.refilter_frames();
+        }
+    }
 
-                let frame = frame.expect("failed to get frame");
-                let delay = Duration::from(frame.delay());
-                let buffer = frame.into_buffer();
-                let size = [buffer.width() as _, buffer.height() as _];
+    /// change the opacity at runtime, keeping the overlay faintly visible
+    fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity.clamp(MIN_OPACITY, 1.0);
+        info!("Opacity set to {:.2}", self.opacity);
+    }
 
-                let pixels: Vec<u8> = buffer
-                    .pixels()
-                    .flat_map(|p| {
-                        let alpha = (p[3] as f32 * opacity) as u8;
-                        vec![p[0], p[1], p[2], alpha]
-                    })
-                    .collect();
+    /// close the window, playing the `--exit` transition first if there is one
+    fn close(&mut self, ctx: &egui::Context) {
+        if !self.closing {
+            self.notify_plugins(plugin::Event::Quit);
+        }
+        if self.fade_duration.is_zero() || self.visibility <= 0.0 {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        } else if !self.closing {
+            self.closing = true;
+            self.sync_mouse_passthrough(ctx);
+        }
+    }
 
-                sender
-                    .send(LoadingMessage::FrameReady(
-                        idx,
-                        pixels,
-                        [size[0], size[1]],
-                        delay,
-                    ))
-                    .expect("failed to send frame");
-            }
+    /// Move `visibility` towards 0 while hidden and 1 otherwise, returning
+    /// whether there's anything left to draw.
+    fn step_fade(&mut self, ctx: &egui::Context) -> bool {
+        // closing the window some other way (e.g. Alt+F4) fades out too
+        let close_requested = ctx.input(|i| i.viewport().close_requested());
+        if close_requested && !self.fade_duration.is_zero() && self.visibility > 0.0 {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.close(ctx);
+        }
 
-            sender
-                .send(LoadingMessage::LoadingComplete(frame_count))
-                .expect("failed to send completion message");
-        });
+        let target = if self.is_hidden() { 0.0 } else { 1.0 };
+        if self.fade_duration.is_zero() {
+            self.visibility = target;
+        } else if self.visibility == target {
+            self.last_fade_step = None;
+        } else {
+            // the first step of a fade starts from now rather than the last repaint
+            let elapsed = self
+                .last_fade_step
+                .replace(Instant::now())
+                .map_or(Duration::ZERO, |last| last.elapsed());
+            let step = elapsed.as_secs_f32() / self.fade_duration.as_secs_f32();
+            self.visibility = if target > self.visibility {
+                (self.visibility + step).min(target)
+            } else {
+                (self.visibility - step).max(target)
+            };
+            ctx.request_repaint();
+        }
 
-        Self {
-            frames: Vec::new(),
-            current_frame: 0,
-            last_update: Instant::now(),
-            scale,
-            opacity,
-            frame_receiver: receiver,
-            loading_complete: false,
-            first_frame_loaded: false,
-            start_time,
-            total_frame: 0,
-            frames_loaded: 0,
-            last_fps_update: Instant::now(),
-            frame_count: 0,
-            last_memory_check: Instant::now(),
+        if self.closing && self.visibility <= 0.0 {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
         }
+        self.visibility > 0.0
     }
 
-    fn process_incoming_frames(&mut self, ctx: &egui::Context) {
-        while let Ok(message) = self.frame_receiver.try_recv() {
-            match message {
-                LoadingMessage::FrameReady(idx, pixels, size, delay) => {
-                    while self.frames.len() <= idx {
-                        self.frames.push(None);
-                    }
+    /// let clicks through while hidden, and always in fullscreen mode
+    fn sync_mouse_passthrough(&self, ctx: &egui::Context) {
+        ctx.send_viewport_cmd(egui::ViewportCommand::MousePassthrough(
+            self.is_hidden() || self.fullscreen,
+        ));
+    }
 
-                    let color_image =
-                        egui::ColorImage::from_rgba_unmultiplied([size[0], size[1]], &pixels);
-                    let texture = ctx.load_texture(
-                        format!("gif_frame_{}", idx),
-                        color_image,
-                        egui::TextureOptions::default(),
-                    );
+    /// hide or show the overlay on request from the tray or the global hotkey
+    fn toggle_visible(&mut self, ctx: &egui::Context) {
+        self.user_hidden = !self.user_hidden;
+        info!(
+            "Overlay {}",
+            if self.user_hidden { "hidden" } else { "shown" }
+        );
+        self.sync_mouse_passthrough(ctx);
+        self.last_update = Instant::now();
+    }
 
-                    self.frames[idx] = Some(Frame {
-                        texture: Arc::new(texture),
-                        delay,
-                    });
+    /// carry out presses of the `--hide-hotkey` and `--hotkeys` bindings
+    fn handle_hotkey(&mut self, ctx: &egui::Context) {
+        let Some(hotkeys) = &self.hotkeys else {
+            return;
+        };
+        let actions: Vec<hotkey::Action> = hotkeys.presses.try_iter().collect();
+        for action in actions {
+            match action {
+                hotkey::Action::Toggle => self.toggle_visible(ctx),
+                hotkey::Action::Next => self.skip_to_next(ctx),
+                hotkey::Action::OpacityUp => self.set_opacity(self.opacity + OPACITY_STEP),
+                hotkey::Action::OpacityDown => self.set_opacity(self.opacity - OPACITY_STEP),
+                hotkey::Action::Pause => self.toggle_pause(),
+                hotkey::Action::Trigger => self.trigger_active(ctx),
+                hotkey::Action::Hud => self.toggle_hud(),
+                hotkey::Action::Quit => self.close(ctx),
+            }
+        }
+    }
 
-                    self.frames_loaded += 1;
+    /// cut the playing alert or playlist entry short
+    fn skip_to_next(&mut self, ctx: &egui::Context) {
+        if self.interrupted.is_some() {
+            self.start_next_alert(ctx);
+        } else if self.playlist.len() > 1 {
+            info!("Skipping to the next playlist entry");
+            self.start_next_clip(ctx);
+        }
+    }
 
-                    if !self.first_frame_loaded {
-                        self.first_frame_loaded = true;
-                        log_time!(self.start_time, "First frame ready");
-                    }
+    /// carry out requests from the `--http-port` API, `--ipc`/`--websocket-port`
+    /// clients, MQTT, OSC, D-Bus, Twitch, MIDI, and gamepads, then the `--script`
+    fn handle_control(&mut self, ctx: &egui::Context) {
+        let commands: Vec<control::Command> = self.control_commands.try_iter().collect();
+        for command in commands {
+            self.run_command(ctx, command);
+        }
+        for command in std::mem::take(&mut self.script_commands) {
+            match command {
+                // a walking script moves the window every frame, too often to log
+                control::Command::Move(pos) => {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(pos));
+                }
+                command => self.run_command(ctx, command),
+            }
+        }
+    }
 
-                    if self.total_frame > 0 {
-                        println!(
-                            "loading progress: {}/{} frames ({:.1}%)",
-                            self.frames_loaded,
-                            self.total_frame,
-                            (self.frames_loaded as f32 / self.total_frame as f32) * 100.0
-                        );
-                    }
+    /// carry out one command from the outside, whichever way it came in
+    fn run_command(&mut self, ctx: &egui::Context, command: control::Command) {
+        match command {
+            control::Command::Show | control::Command::Hide => {
+                let hide = matches!(command, control::Command::Hide);
+                if self.user_hidden != hide {
+                    self.toggle_visible(ctx);
                 }
-                LoadingMessage::LoadingComplete(total_frames) => {
-                    self.loading_complete = true;
-                    self.total_frame = total_frames;
-                    log_time!(self.start_time, "all frame loaded");
+            }
+            control::Command::Trigger => self.trigger_active(ctx),
+            control::Command::Pause | control::Command::Resume => {
+                let pause = matches!(command, control::Command::Pause);
+                if self.paused != pause {
+                    self.toggle_pause();
+                }
+            }
+            control::Command::Seek(frame) => self.seek(frame),
+            control::Command::Play(path) => self.play_input(ctx, path),
+            control::Command::Alert(path, caption) => self.play_alert(ctx, path, caption),
+            control::Command::Opacity(value) => self.apply_setting(Setting::Opacity, value, false),
+            control::Command::Scale(value) => {
+                if !self.fullscreen {
+                    self.scale = value.clamp(MIN_SCALE, MAX_SCALE);
+                    info!("Scale set to {:.2}", self.scale);
                 }
             }
+            control::Command::Move(pos) => {
+                debug!("Moving window to ({}, {})", pos.x, pos.y);
+                ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(pos));
+            }
+            control::Command::Quit => self.close(ctx),
+            control::Command::Status(reply) => {
+                let _ = reply.send(self.status());
+            }
         }
     }
 
-    fn get_next_available_frame(&self) -> Option<usize> {
-        if self.frames.is_empty() {
-            return None;
+    fn current_input(&self) -> PathBuf {
+        self.playlist
+            .get(self.playlist_index)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// the overlay's current state, for `/status`
+    fn status(&self) -> control::Status {
+        control::Status {
+            visible: !self.is_hidden(),
+            paused: self.paused,
+            opacity: self.opacity,
+            scale: self.scale,
+            input: self.current_input(),
+            frame: self.current_frame,
+            frames: self.frames.len(),
         }
+    }
 
-        let mut next = (self.current_frame + 1) % self.frames.len();
-        let start = next;
-        if next == start {
-            return None;
+    /// drop the playlist and play `path` instead
+    fn play_input(&mut self, ctx: &egui::Context, path: PathBuf) {
+        info!("Playing {}", path.display());
+        self.alerts.clear();
+        self.interrupted = None;
+        self.caption = None;
+        self.playlist = vec![path];
+        self.next_index = 0;
+        self.preloaded = None;
+        self.trigger_mode = false;
+        self.shuffle_playlist = false;
+        self.start_next_clip(ctx);
+    }
+
+    /// play `path` once, with `caption` under it, and go back to the
+    /// playlist, after any alerts already waiting
+    fn play_alert(&mut self, ctx: &egui::Context, path: PathBuf, caption: Option<String>) {
+        self.alerts.push_back((path, caption));
+        if self.interrupted.is_none() {
+            self.start_next_alert(ctx);
         }
+    }
 
-        Some(next)
+    /// move on to the next queued alert, or back to the interrupted playlist
+    /// if there are none left
+    fn start_next_alert(&mut self, ctx: &egui::Context) {
+        let Some((path, caption)) = self.alerts.pop_front() else {
+            self.caption = None;
+            if let Some(interrupted) = self.interrupted.take() {
+                self.playlist = interrupted.playlist;
+                self.next_index = interrupted.index;
+                self.trigger_mode = interrupted.trigger_mode;
+                self.shuffle_playlist = interrupted.shuffle_playlist;
+                self.preloaded = None;
+                if self.playlist.is_empty() {
+                    // `--particles` on their own: back to drawing just them
+                    self.frame_receiver = channel().1;
+                    self.frames.clear();
+                    self.atlas.recycle();
+                    self.first_frame_loaded = false;
+                } else {
+                    self.start_next_clip(ctx);
+                }
+            }
+            return;
+        };
+
+        info!("Playing alert {}", path.display());
+        if self.interrupted.is_none() {
+            self.interrupted = Some(Interrupted {
+                playlist: std::mem::take(&mut self.playlist),
+                index: self.playlist_index,
+                trigger_mode: self.trigger_mode,
+                shuffle_playlist: self.shuffle_playlist,
+            });
+        }
+        self.caption = caption;
+        self.playlist = vec![path];
+        self.next_index = 0;
+        self.preloaded = None;
+        self.trigger_mode = false;
+        self.shuffle_playlist = false;
+        self.start_next_clip(ctx);
+    }
+
+    /// apply whatever was picked from the tray menu
+    fn handle_tray(&mut self, ctx: &egui::Context) {
+        let Some(tray) = &self.tray else {
+            return;
+        };
+
+        let commands: Vec<tray::TrayCommand> = tray.commands.try_iter().collect();
+        for command in commands {
+            match command {
+                tray::TrayCommand::ToggleVisible => self.toggle_visible(ctx),
+                tray::TrayCommand::TogglePause => self.toggle_pause(),
+                tray::TrayCommand::Opacity(opacity) => self.set_opacity(opacity),
+                tray::TrayCommand::Quit => self.close(ctx),
+            }
+        }
     }
 
     fn update_performance_metrics(&mut self) {
-        if self.last_fps_update.elapsed() >= Duration::from_secs(1) {
-            let fps = self.frame_count as f32 / self.last_fps_update.elapsed().as_secs_f32();
-            println!("FPS: {:.1}", fps);
+        let elapsed = self.last_fps_update.elapsed();
+        // after sitting idle, with no repaints at all, there's no frame rate
+        // worth reporting
+        if elapsed >= Duration::from_secs(2) {
+            self.frame_count = 0;
+            self.last_fps_update = Instant::now();
+        } else if elapsed >= Duration::from_secs(1) {
+            // the HUD shows the same on screen
+            if self.status_json {
+                status_json::StatusLine::Stats {
+                    fps: self.frame_count as f32 / elapsed.as_secs_f32(),
+                    frame: self.current_frame,
+                    frames: self.frames.len(),
+                }
+                .print();
+            }
             self.frame_count = 0;
             self.last_fps_update = Instant::now();
         }
@@ -215,93 +4010,808 @@ impl GifOverlay {
         self.frame_count += 1;
 
         // check memory usage every 10s
-        if self.last_memory_check.elapsed() >= Duration::from_secs(10) {
-            if let Ok(memory) = sys_info::mem_info() {
-                println!(
-                    "Memory usage: {:.1}MB free out of {:.1}MB total",
-                    memory.free as f64 / 1024.0,
-                    memory.total as f64 / 1024.0,
-                );
+        if self.status_json && self.last_memory_check.elapsed() >= Duration::from_secs(10) {
+            let mb = |bytes: usize| bytes as f64 / (1024.0 * 1024.0);
+            status_json::StatusLine::Memory {
+                rss_mb: resident_bytes().map(mb),
+                decoded_mb: mb(self.decoded_bytes()),
+                texture_mb: mb(self.texture_bytes()),
             }
+            .print();
             self.last_memory_check = Instant::now();
         }
     }
+
+    /// print a `--status-json` state line when visibility, pause, opacity,
+    /// scale, or the input have changed
+    fn report_state(&mut self) {
+        if !self.status_json {
+            return;
+        }
+        let status = self.status();
+        let state = status_json::OverlayState {
+            visible: status.visible,
+            paused: status.paused,
+            opacity: status.opacity,
+            scale: status.scale,
+            input: status.input,
+        };
+        if self.reported_state.as_ref() != Some(&state) {
+            status_json::StatusLine::State(state.clone()).print();
+            self.reported_state = Some(state);
+        }
+    }
 }
 
 impl eframe::App for GifOverlay {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        if self.skip_taskbar {
+            taskbar::hide_from_taskbar(frame);
+            self.skip_taskbar = false;
+        }
         self.process_incoming_frames(ctx);
+        self.apply_anchor(ctx);
+        self.snap_to_edges(ctx);
+        self.follow_cursor(ctx);
+        self.walk_pet(ctx);
+        self.persist_window_state(ctx);
+        self.handle_tray(ctx);
+        self.handle_hotkey(ctx);
+        self.handle_control(ctx);
+        self.watch_fullscreen_apps(ctx);
+        self.follow_obs_scene(ctx);
+        self.apply_schedule(ctx);
+        self.report_state();
+        // keep drawing while fading out, but stop taking input
+        let visible = self.step_fade(ctx);
+        hold_streams(!visible || self.paused);
+        if !visible {
+            return;
+        }
+        if !self.is_hidden() {
+            self.handle_input(ctx);
+        }
+        self.advance_frame(ctx);
+        self.run_frame_hooks();
+        self.step_script(ctx);
         self.update_performance_metrics();
+        if let Some(hud) = &mut self.hud {
+            hud.tick();
+        }
 
-        egui::Window::new("GIF overlay")
-            .frame(egui::Frame::none())
-            .title_bar(false)
-            .resizable(false)
-            .movable(true)
-            .show(ctx, |ui| {
-                if self.first_frame_loaded {
-                    let now = Instant::now();
-                    if let Some(current_frame) = self.frames[self.current_frame].as_ref() {
-                        if now.duration_since(self.last_update) >= current_frame.delay {
-                            if let Some(next_frame) = self.get_next_available_frame() {
-                                self.current_frame = next_frame;
-                                self.last_update = now;
-                            }
-                        }
+        if self.scale != self.rendered_scale {
+            // only the SVG loader listens; other sources have already hung up
+            let _ = self.rescale_sender.send(self.scale);
+            self.rendered_scale = self.scale;
+            // later playlist entries start out at the new scale
+            self.loader_options.scale = self.scale;
+        }
 
-                        ui.image(current_frame.texture.as_ref());
-                    }
-                } else {
-                    ui.spinner();
+        if self.fullscreen {
+            self.fit_area = Some(ctx.screen_rect().size());
+        }
+        if let Some(audio) = &mut self.audio {
+            audio.step();
+            ctx.request_repaint();
+        }
+
+        let backdrop = match self.background {
+            Some(color) => egui::Frame::none()
+                .fill(color.gamma_multiply(self.opacity * self.visibility))
+                .rounding(BACKDROP_ROUNDING)
+                .inner_margin(BACKDROP_PADDING),
+            None => egui::Frame::none(),
+        };
+        let window = egui::Window::new("GIF overlay")
+            .frame(backdrop)
+            .title_bar(false)
+            .resizable(false);
+        let window = if self.fullscreen {
+            window.anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        } else {
+            window.fixed_pos(egui::Pos2::ZERO)
+        };
+        let window = window.show(ctx, |ui| {
+            if self.first_frame_loaded {
+                self.paint_frame(ui);
+                self.paint_clock(ui);
+                self.paint_caption(ui);
+                if self.controls_visible {
+                    self.show_controls(ui);
                 }
-            });
+            } else if let Some(err) = &self.load_error {
+                ui.colored_label(egui::Color32::LIGHT_RED, err);
+            } else if !self.playlist.is_empty() {
+                ui.spinner();
+            }
+        });
+        if let Some(window) = window {
+            self.fit_window(ctx, window.response.rect);
+        }
+        if let Some(hud) = &self.hud {
+            hud.show(ctx, &self.hud_stats());
+        }
+        if let Some(particles) = &mut self.particles {
+            particles.step(ctx.screen_rect());
+            let layer = egui::LayerId::new(egui::Order::Foreground, egui::Id::new("particles"));
+            particles.paint(&ctx.layer_painter(layer), self.opacity * self.visibility);
+            ctx.request_repaint();
+        }
 
-        if self.first_frame_loaded {
-            if let Some(current_frame) = self.frames[self.current_frame].as_ref() {
-                let time_until_next_frame = current_frame
-                    .delay
-                    .saturating_sub(Instant::now().duration_since(self.last_update));
+        if self.first_frame_loaded && !self.paused {
+            if let Some(delay) = self
+                .current_delay()
+                .filter(|&delay| delay != STATIC_FRAME_DELAY)
+            {
+                let time_until_next_frame =
+                    delay.saturating_sub(Instant::now().duration_since(self.last_update));
 
                 if !time_until_next_frame.is_zero() {
                     ctx.request_repaint_after(time_until_next_frame);
                 }
             }
+
+            // wake up for `--interval` even if the current frame is a still
+            if let Some(interval) = self.switch_interval.filter(|_| self.playlist.len() > 1) {
+                ctx.request_repaint_after(interval.saturating_sub(self.clip_started.elapsed()));
+            }
         }
     }
 }
 
+/// where `--obs` and `--obs-password` say OBS is, if anywhere
+fn obs_connection(args: &Args) -> Option<obs::Connection> {
+    args.obs.clone().map(|url| obs::Connection {
+        url,
+        password: args.obs_password.clone(),
+    })
+}
+
+/// what `--single-instance` tells an overlay that's already running: the
+/// options given on this command line, then to show itself
+fn forwarded_requests(args: &Args, matches: &clap::ArgMatches) -> Vec<control::Request> {
+    let explicit = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+    let mut requests = Vec::new();
+    match args.input.first() {
+        Some(path) if is_stdin(path) => warn!("stdin can't be handed to another overlay"),
+        Some(path) => match ipc::input_path(path) {
+            Ok(file) => requests.push(control::Request::Play {
+                file,
+                x: None,
+                y: None,
+            }),
+            Err(err) => warn!("failed to resolve {}: {}", path.display(), err),
+        },
+        None => {}
+    }
+    if let (Some(x), Some(y)) = (args.x, args.y) {
+        requests.push(control::Request::Move { x, y });
+    }
+    if explicit("opacity") {
+        requests.push(control::Request::Opacity {
+            value: args.opacity,
+        });
+    }
+    if explicit("scale") {
+        requests.push(control::Request::Scale { value: args.scale });
+    }
+    requests.push(control::Request::Show);
+    requests
+}
+
 fn main() -> Result<(), eframe::Error> {
     let start_time = Instant::now();
-    let args = Args::parse();
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    // `info` and `bench` output is for reading, so only problems are logged
+    // unless asked
+    let log_level = match args.action {
+        Some(Action::Info { .. } | Action::Bench { .. })
+            if matches.value_source("log_level") != Some(ValueSource::CommandLine) =>
+        {
+            logging::LogLevel::Warn
+        }
+        _ => args.log_level,
+    };
+    logging::init(log_level, args.log_file.as_deref());
+
+    if args.decoder == Decoder::Ffmpeg && !video::tools_available() {
+        warn!("--decoder ffmpeg needs `ffmpeg` and `ffprobe` on the PATH, using the built-in decoders");
+        args.decoder = Decoder::Builtin;
+    }
+
+    match &args.action {
+        Some(Action::Ctl { name, command }) => {
+            if let Err(err) = ipc::run_client(name, command) {
+                error!("{}", err);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Action::Info { inputs, json }) => {
+            if !info::run(inputs, LoaderOptions::new(&args, None), *json) {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Action::Bench {
+            inputs,
+            runs,
+            upload,
+            json,
+        }) => {
+            let upload = upload.then(|| bench::Upload {
+                filters: color_filters(&args, Rc::from(Vec::new())),
+                texture_options: args.sampling.texture_options(),
+            });
+            let options = LoaderOptions::new(&args, None);
+            if !bench::run(inputs, options, *runs as usize, upload.as_ref(), *json) {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        None => {}
+    }
+
+    // catch missing and unplayable files here rather than in an empty window
+    if let Err(err) = validate::args(&args) {
+        Args::command()
+            .error(ErrorKind::ValueValidation, err)
+            .exit();
+    }
+
+    if args.single_instance {
+        let name = args
+            .ipc
+            .get_or_insert_with(|| "overlay_app".to_string())
+            .clone();
+        match ipc::forward(&name, &forwarded_requests(&args, &matches)) {
+            Ok(true) => {
+                info!("Handed over to the overlay already running as {:?}", name);
+                return Ok(());
+            }
+            Ok(false) => {}
+            Err(err) => {
+                error!("{}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // a layout only starts other overlay processes and never opens a window itself
+    if let Some(path) = &args.layout {
+        if let Err(err) = layout::run(path, obs_connection(&args)) {
+            error!("failed to run layout {}: {}", path.display(), err);
+            std::process::exit(1);
+        }
+        log_time!(start_time, "layout finished");
+        return Ok(());
+    }
+
+    // values given on the command line win over the saved ones
+    let explicit = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+    let restored = state_input(&args)
+        .filter(|_| !args.no_restore)
+        .and_then(|input| state::load(&input));
+    if let Some(restored) = restored {
+        info!("Restoring window state from the last run");
+        if !explicit("scale") {
+            args.scale = restored.scale;
+        }
+        if !explicit("opacity") {
+            args.opacity = restored.opacity;
+        }
+    }
+
+    info!("Configuration:");
+    info!("  Scale: {}", args.scale);
+    info!("  Opacity: {}", args.opacity);
+    match (args.width, args.height) {
+        (None, None) => info!("  Window size: fit to animation"),
+        (width, height) => info!(
+            "  Window size: {}x{}",
+            width.unwrap_or(DEFAULT_WINDOW_SIZE),
+            height.unwrap_or(DEFAULT_WINDOW_SIZE)
+        ),
+    }
+    info!("  Always on top: {}", args.always_on_top);
+    if args.fullscreen {
+        info!("  Fullscreen: yes");
+    }
+    if args.locked {
+        info!("  Window locked");
+    }
+    if args.follow_cursor {
+        info!(
+            "  Follow cursor: offset ({}, {}), smoothing {}",
+            args.follow_offset.x, args.follow_offset.y, args.follow_smoothing
+        );
+    }
+    if args.pet {
+        info!(
+            "  Pet mode: {} pt/s, pauses up to {}s",
+            args.pet_speed, args.pet_pause
+        );
+    }
+    if let Some(color) = args.chroma_key {
+        info!(
+            "  Chroma key: {} (tolerance {})",
+            color::format_color(color),
+            args.chroma_tolerance
+        );
+    }
+    if let Some(color) = args.tint {
+        info!("  Tint: {}", color::format_color(color));
+    }
+    if let Some(degrees) = args.hue_shift {
+        info!("  Hue shift: {}°", degrees);
+    }
+    if args.brightness != 0.0 || args.contrast != 1.0 || args.gamma != 1.0 {
+        info!(
+            "  Levels: brightness {}, contrast {}, gamma {}",
+            args.brightness, args.contrast, args.gamma
+        );
+    }
+    if args.filter != filters::Filter::None {
+        info!("  Filter: {:?}", args.filter);
+    }
+    if let Some(crop) = args.crop {
+        info!(
+            "  Crop: {}x{} at ({}, {})",
+            crop.width(),
+            crop.height(),
+            crop.min.x,
+            crop.min.y
+        );
+    }
+    if args.shadow {
+        info!("  Drop shadow: yes");
+    }
+    if let Some(outline) = &args.outline {
+        info!("  Outline: {:?}", outline);
+    }
+    if let Some(path) = &args.shader {
+        info!("  Shader: {}", path.display());
+    }
+    if args.mask != transform::Mask::None {
+        info!("  Mask: {:?}", args.mask);
+    }
+    if args.rotate != transform::Rotation::None || args.flip_h || args.flip_v {
+        info!(
+            "  Orientation: rotated {}°, flip horizontal {}, flip vertical {}",
+            args.rotate.degrees(),
+            args.flip_h,
+            args.flip_v
+        );
+    }
+    if let Some(speed) = args.spin {
+        info!("  Spin: {}°/s", speed);
+    }
+    if let Some(duration) = args.ken_burns {
+        info!(
+            "  Ken Burns: {:?} to {:?} over {}s",
+            args.ken_burns_from, args.ken_burns_to, duration
+        );
+    }
+    if let Some(color) = args.background {
+        info!("  Background: {}", color::format_color(color));
+    }
+    if let Some(port) = args.http_port {
+        info!("  Control API port: {}", port);
+    }
+    if let Some(path) = &args.webhook_rules {
+        info!("  Webhook rules: {}", path.display());
+    }
+    if let Some(name) = &args.ipc {
+        info!("  IPC name: {}", name);
+    }
+    if let Some(port) = args.websocket_port {
+        info!("  WebSocket port: {}", port);
+    }
+    if let Some(broker) = &args.mqtt_broker {
+        info!("  MQTT: {} on {}", args.mqtt_topic, broker.address());
+    }
+    if let Some(port) = args.osc_port {
+        info!("  OSC: {}:{}", args.osc_bind, port);
+    }
+    if args.dbus {
+        info!("  D-Bus: enabled");
+    }
+    if let Some(path) = &args.twitch {
+        info!("  Twitch alerts: {}", path.display());
+    }
+    if let Some(url) = &args.obs {
+        info!("  OBS: {} (scenes: {})", url, args.obs_scenes.join(", "));
+    }
+    if let Some(path) = &args.sound {
+        info!(
+            "  Sound: {} on {:?} (volume {})",
+            path.display(),
+            args.sound_on,
+            args.volume
+        );
+    }
+    if let Some(target) = args.audio_react {
+        info!(
+            "  Audio react: {:?} (amount {}, gain {})",
+            target, args.audio_amount, args.audio_gain
+        );
+    }
+    if let Some(style) = args.particles {
+        info!(
+            "  Particles: {:?} (density {})",
+            style, args.particle_density
+        );
+    }
+    if let Some(duration) = args.timer {
+        info!("  Timer: {}s", duration.as_secs());
+    } else if args.clock {
+        info!("  Clock: {}", args.clock_format);
+    }
+    if args.fade > 0 {
+        info!("  Fade: {}ms", args.fade);
+    }
+    if args.enter.is_some() || args.exit.is_some() {
+        info!(
+            "  Transitions: enter {:?}, exit {:?}",
+            args.enter.unwrap_or_default(),
+            args.exit.unwrap_or_default()
+        );
+    }
+    if args.skip_taskbar {
+        info!("  Skip taskbar: yes");
+    }
+    if args.hide_on_fullscreen {
+        info!("  Hide on fullscreen apps: yes");
+    }
+    if let (Some(x), Some(y)) = (args.x, args.y) {
+        info!("  Position: ({}, {})", x, y);
+    }
+    if let Some(anchor) = args.position {
+        info!("  Position: {:?} (margin {})", anchor, args.margin);
+    }
+    info!("  Playback: {:?}", args.playback);
+
+    if let Some(columns) = args.columns {
+        info!(
+            "  Sprite sheet: {}x{} @ {} fps",
+            columns, args.rows, args.fps
+        );
+    }
+    if args.start_frame > 0 || args.end_frame.is_some() {
+        info!(
+            "  Frame range: {}..={}",
+            args.start_frame,
+            args.end_frame
+                .map_or_else(|| "end".to_string(), |end| end.to_string())
+        );
+    }
+    if let Some(loops) = args.loops {
+        info!("  Loops: {}", loops);
+    }
+    if let Some(on_complete) = args.on_complete {
+        info!("  On complete: {:?}", on_complete);
+    }
+    for hook in &args.on_frame_cmd {
+        info!("  Hook on frame {}", hook.frame);
+    }
+    if let Some(force_delay) = args.force_delay {
+        info!("  Zero-delay frames: {}ms", force_delay);
+    }
+    info!("  Bad frames: {:?}", args.bad_frames);
+    if let Some(max_memory) = args.max_memory {
+        info!("  Max memory per input: {} MB", max_memory);
+    }
 
-    println!("Configuration:");
-    println!("  Scale: {}", args.scale);
-    println!("  Opacity: {}", args.opacity);
-    println!("  Window size: {}x{}", args.width, args.height);
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_decorations(false)
+        .with_transparent(true)
+        .with_inner_size([
+            args.width.unwrap_or(DEFAULT_WINDOW_SIZE) as f32,
+            args.height.unwrap_or(DEFAULT_WINDOW_SIZE) as f32,
+        ]);
+    if args.always_on_top {
+        viewport = viewport.with_always_on_top();
+    }
+    if args.fullscreen {
+        viewport = viewport.with_fullscreen(true).with_mouse_passthrough(true);
+    }
+    let monitor = args.monitor.as_deref().map(|selector| {
+        placement::find_monitor(selector).unwrap_or_else(|err| {
+            error!("--monitor: {}", err);
+            std::process::exit(1);
+        })
+    });
+    if let Some(monitor) = &monitor {
+        info!("  Monitor: {} at {:?}", monitor.name, monitor.rect);
+    }
+    if let (Some(x), Some(y)) = (args.x, args.y) {
+        // `--x`/`--y` are relative to the chosen monitor
+        let origin = monitor
+            .as_ref()
+            .map_or(egui::Pos2::ZERO, |monitor| monitor.rect.min);
+        viewport = viewport.with_position(origin + egui::vec2(x, y));
+    } else if args.fullscreen {
+        // borderless fullscreen covers whichever monitor the window opens on
+        if let Some(monitor) = &monitor {
+            viewport = viewport.with_position(monitor.rect.min);
+        }
+    } else if let Some(position) = restored
+        .and_then(|restored| restored.position)
+        .filter(|_| monitor.is_none() && args.position.is_none())
+    {
+        viewport = viewport.with_position(position);
+    }
 
     let options = NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_decorations(false)
-            .with_transparent(true)
-            .with_inner_size([args.width as f32, args.height as f32]),
+        viewport,
+        // custom shaders are drawn through wgpu; glow stays the default otherwise
+        #[cfg(feature = "shader")]
+        renderer: if args.shader.is_some() {
+            eframe::Renderer::Wgpu
+        } else {
+            eframe::Renderer::default()
+        },
         ..Default::default()
     };
 
-    println!("Initializing application...");
+    info!("Initializing application...");
 
     let result = eframe::run_native(
         "Gif overlay",
         options,
-        Box::new(move |_cc| {
-            Box::new(GifOverlay::new(
-                &_cc.egui_ctx,
-                args.gif,
-                args.scale,
-                args.opacity,
-            ))
+        Box::new(move |cc| {
+            #[cfg(feature = "shader")]
+            let shader_path = args.shader.clone();
+            let app = GifOverlay::new(&cc.egui_ctx, args, monitor);
+            #[cfg(feature = "shader")]
+            let app = app.with_shader(cc, shader_path);
+            Box::new(app)
         }),
     );
 
     log_time!(start_time, "application terminated");
+    if result.is_ok() && LOAD_FAILED.load(Ordering::Relaxed) {
+        std::process::exit(1);
+    }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// frames as the overlay holds them, `false` for ones not loaded
+    fn frames(loaded: &[bool]) -> Vec<Option<()>> {
+        loaded.iter().map(|&loaded| loaded.then_some(())).collect()
+    }
+
+    #[test]
+    fn forward_waits_for_frames_still_loading() {
+        let partial = frames(&[true, true]);
+        assert_eq!(
+            pick_next_frame(&partial, false, 0, Playback::Forward, false),
+            Some((1, false))
+        );
+        // the newest frame isn't the last, so there's no looping back yet
+        assert_eq!(
+            pick_next_frame(&partial, false, 1, Playback::Forward, false),
+            None
+        );
+        let gap = frames(&[true, false, true]);
+        assert_eq!(
+            pick_next_frame(&gap, false, 0, Playback::Forward, false),
+            None
+        );
+    }
+
+    #[test]
+    fn forward_skips_missing_frames_once_loaded() {
+        let gap = frames(&[true, false, true]);
+        assert_eq!(
+            pick_next_frame(&gap, true, 0, Playback::Forward, false),
+            Some((2, false))
+        );
+        assert_eq!(
+            pick_next_frame(&gap, true, 2, Playback::Forward, false),
+            Some((0, false))
+        );
+    }
+
+    #[test]
+    fn reverse_wraps_to_the_last_loaded_frame() {
+        let partial = frames(&[true, true, false]);
+        assert_eq!(
+            pick_next_frame(&partial, false, 0, Playback::Reverse, true),
+            Some((1, true))
+        );
+        let gap = frames(&[true, false, true]);
+        assert_eq!(
+            pick_next_frame(&gap, false, 2, Playback::Reverse, true),
+            Some((0, true))
+        );
+        let missing_first = frames(&[false, true, true]);
+        assert_eq!(
+            pick_next_frame(&missing_first, true, 1, Playback::Reverse, true),
+            Some((2, true))
+        );
+    }
+
+    #[test]
+    fn pingpong_only_turns_at_the_real_end() {
+        let all = frames(&[true, true, true]);
+        assert_eq!(
+            pick_next_frame(&all, false, 2, Playback::Pingpong, false),
+            None
+        );
+        assert_eq!(
+            pick_next_frame(&all, true, 2, Playback::Pingpong, false),
+            Some((1, true))
+        );
+        assert_eq!(
+            pick_next_frame(&all, true, 0, Playback::Pingpong, true),
+            Some((1, false))
+        );
+    }
+
+    #[test]
+    fn pingpong_skips_missing_frames_keeping_its_direction() {
+        let gap = frames(&[true, false, true]);
+        assert_eq!(
+            pick_next_frame(&gap, true, 2, Playback::Pingpong, false),
+            Some((0, true))
+        );
+        assert_eq!(
+            pick_next_frame(&gap, true, 0, Playback::Pingpong, true),
+            Some((2, false))
+        );
+        // behind playback, a gap while loading is never going to fill
+        let loading = frames(&[true, false, true, true]);
+        assert_eq!(
+            pick_next_frame(&loading, false, 2, Playback::Pingpong, true),
+            Some((0, true))
+        );
+        assert_eq!(
+            pick_next_frame(&loading, false, 0, Playback::Pingpong, true),
+            None
+        );
+    }
+
+    #[test]
+    fn shuffle_only_picks_other_loaded_frames() {
+        let partial = frames(&[true, false, true, false]);
+        for loading_complete in [false, true] {
+            for _ in 0..20 {
+                assert_eq!(
+                    pick_next_frame(&partial, loading_complete, 0, Playback::Shuffle, false),
+                    Some((2, false))
+                );
+            }
+        }
+        let alone = frames(&[true, false]);
+        assert_eq!(
+            pick_next_frame(&alone, false, 0, Playback::Shuffle, false),
+            Some((0, false))
+        );
+    }
+
+    #[test]
+    fn single_frame_repeats_once_loaded() {
+        let single = frames(&[true]);
+        for playback in [
+            Playback::Forward,
+            Playback::Reverse,
+            Playback::Pingpong,
+            Playback::Shuffle,
+        ] {
+            let backward = playback == Playback::Reverse;
+            assert_eq!(
+                pick_next_frame(&single, true, 0, playback, backward),
+                Some((0, backward)),
+                "{:?}",
+                playback
+            );
+        }
+        assert_eq!(
+            pick_next_frame(&single, false, 0, Playback::Forward, false),
+            None
+        );
+        assert_eq!(
+            pick_next_frame(&single, false, 0, Playback::Pingpong, false),
+            None
+        );
+    }
+
+    #[test]
+    fn nothing_loaded_holds() {
+        let none: Vec<Option<()>> = Vec::new();
+        assert_eq!(
+            pick_next_frame(&none, false, 0, Playback::Forward, false),
+            None
+        );
+    }
+
+    #[test]
+    fn trigger_and_empty_lines_trigger() {
+        for line in ["trigger", "", "  trigger  "] {
+            assert!(matches!(
+                StdinCommand::parse(line),
+                Some(StdinCommand::Trigger)
+            ));
+        }
+        // anything else is skipped rather than taken as a trigger
+        assert!(StdinCommand::parse("trigger now").is_none());
+        assert!(StdinCommand::parse("dance").is_none());
+    }
+
+    #[test]
+    fn settings_take_absolute_and_relative_values() {
+        assert!(matches!(
+            StdinCommand::parse("opacity 0.5"),
+            Some(StdinCommand::Set {
+                setting: Setting::Opacity,
+                value,
+                relative: false,
+            }) if value == 0.5
+        ));
+        assert!(matches!(
+            StdinCommand::parse("brightness +0.1"),
+            Some(StdinCommand::Set {
+                setting: Setting::Brightness,
+                value,
+                relative: true,
+            }) if value == 0.1
+        ));
+        assert!(matches!(
+            StdinCommand::parse("gamma -0.2"),
+            Some(StdinCommand::Set {
+                setting: Setting::Gamma,
+                value,
+                relative: true,
+            }) if value == -0.2
+        ));
+    }
+
+    #[test]
+    fn load_and_alert_need_a_path_other_than_stdin() {
+        for (name, value) in [("load", "-"), ("load", ""), ("alert", "-"), ("alert", "")] {
+            assert!(StdinCommand::parse_control(name, value).is_err());
+        }
+        assert!(StdinCommand::parse("load -").is_none());
+        assert!(matches!(
+            StdinCommand::parse("load /tmp/clip.gif"),
+            Some(StdinCommand::Control(control::Command::Play(path)))
+                if path == Path::new("/tmp/clip.gif")
+        ));
+    }
+
+    #[test]
+    fn pos_takes_x_and_y() {
+        for line in ["pos 100 200", "move  100   200"] {
+            assert!(matches!(
+                StdinCommand::parse(line),
+                Some(StdinCommand::Control(control::Command::Move(position)))
+                    if position == egui::pos2(100.0, 200.0)
+            ));
+        }
+        assert!(StdinCommand::parse_control("pos", "100").is_err());
+    }
+
+    #[test]
+    fn malformed_numbers_are_skipped() {
+        for line in [
+            "opacity half",
+            "seek ten",
+            "seek -1",
+            "scale x2",
+            "pos 100 y",
+        ] {
+            assert!(StdinCommand::parse(line).is_none(), "{:?}", line);
+        }
+        assert!(StdinCommand::parse_control("scale", "two").is_err());
+        assert!(matches!(
+            StdinCommand::parse_control("rotate", "90"),
+            Ok(None)
+        ));
+    }
+}
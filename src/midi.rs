@@ -0,0 +1,173 @@
+use crate::control::{self, ButtonAction, Command};
+use eframe::egui;
+use midir::{MidiInput, MidiInputConnection};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+};
+use tracing::info;
+
+const NOTE_ON: u8 = 0x90;
+const CONTROL_CHANGE: u8 = 0xB0;
+
+/// A `--midi` file: which controller to listen to, what its pads do, and
+/// which faders or knobs set a parameter.
+///
+/// ```toml
+/// port = "nanoKONTROL2"       # part of the port name, the first port if left out
+/// scale_range = [0.5, 3.0]    # what a "scale" fader spans
+///
+/// [notes]
+/// 36 = "trigger"
+/// 37 = "show"
+/// 38 = "hide"
+/// 39 = "airhorn.gif"          # anything else is played once as an alert
+///
+/// [cc]
+/// 0 = "opacity"
+/// 16 = "scale"
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    port: Option<String>,
+    #[serde(default = "default_scale_range")]
+    scale_range: [f32; 2],
+    #[serde(default)]
+    notes: HashMap<String, String>,
+    #[serde(default)]
+    cc: HashMap<String, Parameter>,
+    // the file's folder, which alerts are relative to
+    #[serde(skip)]
+    base: PathBuf,
+}
+
+fn default_scale_range() -> [f32; 2] {
+    [0.25, 4.0]
+}
+
+/// What a controller's 0-127 value sets.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Parameter {
+    Opacity,
+    Scale,
+}
+
+/// A `Config` with its note and controller numbers parsed.
+struct Mapping {
+    scale_range: [f32; 2],
+    notes: HashMap<u8, ButtonAction>,
+    cc: HashMap<u8, Parameter>,
+}
+
+/// The open MIDI input, closed again when this is dropped.
+pub struct Connection {
+    _input: MidiInputConnection<()>,
+}
+
+/// read and parse a `--midi` file, resolving alerts against its folder
+pub fn load(path: &Path) -> io::Result<Config> {
+    let contents = std::fs::read_to_string(path)?;
+    let mut config: Config =
+        toml::from_str(&contents).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    config.base = path.parent().unwrap_or(Path::new("")).to_path_buf();
+    Ok(config)
+}
+
+/// MIDI note and controller numbers run from 0 to 127
+fn number(key: &str) -> Result<u8, String> {
+    key.trim()
+        .parse::<u8>()
+        .ok()
+        .filter(|number| *number < 128)
+        .ok_or_else(|| format!("{:?} isn't a MIDI number (0-127)", key))
+}
+
+impl Config {
+    fn mapping(&self) -> Result<Mapping, String> {
+        let notes = self
+            .notes
+            .iter()
+            .map(|(note, action)| Ok((number(note)?, ButtonAction::parse(action, &self.base))))
+            .collect::<Result<_, String>>()?;
+        let cc = self
+            .cc
+            .iter()
+            .map(|(controller, parameter)| Ok((number(controller)?, *parameter)))
+            .collect::<Result<_, String>>()?;
+        Ok(Mapping {
+            scale_range: self.scale_range,
+            notes,
+            cc,
+        })
+    }
+}
+
+/// Open the MIDI input port named in `config`, passing struck notes and
+/// moved controllers on to `commands` and waking the UI through `ctx`.
+pub fn connect(
+    config: &Config,
+    commands: Sender<Command>,
+    ctx: &egui::Context,
+) -> Result<Connection, String> {
+    let mapping = config.mapping()?;
+    let input = MidiInput::new("overlay_app").map_err(|err| err.to_string())?;
+    let port = input
+        .ports()
+        .into_iter()
+        .find(|port| {
+            let name = input.port_name(port).unwrap_or_default();
+            config
+                .port
+                .as_deref()
+                .map_or(true, |wanted| name.contains(wanted))
+        })
+        .ok_or_else(|| match &config.port {
+            Some(wanted) => format!("no MIDI input port matching {:?}", wanted),
+            None => "no MIDI input ports".to_string(),
+        })?;
+    let name = input.port_name(&port).map_err(|err| err.to_string())?;
+
+    let ctx = ctx.clone();
+    let connection = input
+        .connect(
+            &port,
+            "overlay_app",
+            move |_, message, _| {
+                if let Some(command) = mapping.command(message) {
+                    let _ = control::send(&commands, &ctx, command);
+                }
+            },
+            (),
+        )
+        .map_err(|err| err.to_string())?;
+    info!("Listening for MIDI on {}", name);
+    Ok(Connection { _input: connection })
+}
+
+impl Mapping {
+    /// the command a MIDI message stands for, on any channel
+    fn command(&self, message: &[u8]) -> Option<Command> {
+        let [status, key, value] = *message else {
+            return None;
+        };
+        match status & 0xF0 {
+            // a note on with no velocity is how many controllers send note off
+            NOTE_ON if value > 0 => self.notes.get(&key).map(ButtonAction::command),
+            CONTROL_CHANGE => {
+                let amount = value as f32 / 127.0;
+                Some(match self.cc.get(&key)? {
+                    Parameter::Opacity => Command::Opacity(amount),
+                    Parameter::Scale => {
+                        let [min, max] = self.scale_range;
+                        Command::Scale(min + (max - min) * amount)
+                    }
+                })
+            }
+            _ => None,
+        }
+    }
+}
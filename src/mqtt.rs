@@ -0,0 +1,117 @@
+use crate::control::{self, Command};
+use eframe::egui;
+use rumqttc::{Client, Event, MqttOptions, Packet, QoS};
+use std::{path::PathBuf, sync::mpsc::Sender, thread, time::Duration};
+use tracing::{info, warn};
+
+/// how long to wait before reconnecting to a broker that went away
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// An MQTT broker as given to `--mqtt-broker`.
+#[derive(Debug, Clone)]
+pub struct Broker {
+    host: String,
+    port: u16,
+    credentials: Option<(String, String)>,
+}
+
+impl Broker {
+    /// `mqtt://HOST:PORT`, leaving out the credentials
+    pub fn address(&self) -> String {
+        format!("mqtt://{}:{}", self.host, self.port)
+    }
+}
+
+/// Parse `[mqtt://][USER:PASSWORD@]HOST[:PORT]`; the port defaults to 1883.
+pub fn parse_broker(value: &str) -> Result<Broker, String> {
+    let rest = value.strip_prefix("mqtt://").unwrap_or(value);
+    let (credentials, address) = match rest.rsplit_once('@') {
+        Some((credentials, address)) => {
+            let (user, password) = credentials.split_once(':').unwrap_or((credentials, ""));
+            (Some((user.to_string(), password.to_string())), address)
+        }
+        None => (None, rest),
+    };
+    let (host, port) = match address.rsplit_once(':') {
+        Some((host, port)) => (
+            host,
+            port.parse()
+                .map_err(|err| format!("invalid port {:?}: {}", port, err))?,
+        ),
+        None => (address, 1883),
+    };
+    if host.is_empty() {
+        return Err(format!("expected HOST[:PORT], got {:?}", value));
+    }
+    Ok(Broker {
+        host: host.to_string(),
+        port,
+        credentials,
+    })
+}
+
+/// Subscribe to `topic` on `broker`, passing each message on to `commands`
+/// and waking the UI through `ctx`. Drops in the connection are retried in
+/// the background.
+pub fn subscribe(broker: &Broker, topic: &str, commands: Sender<Command>, ctx: &egui::Context) {
+    let client_id = format!("overlay_app-{}", std::process::id());
+    let mut options = MqttOptions::new(client_id, &broker.host, broker.port);
+    options.set_keep_alive(Duration::from_secs(30));
+    if let Some((user, password)) = &broker.credentials {
+        options.set_credentials(user, password);
+    }
+    let (client, mut connection) = Client::new(options, 10);
+    info!("Subscribing to {} on {}", topic, broker.address());
+
+    let topic = topic.to_string();
+    let ctx = ctx.clone();
+    thread::spawn(move || {
+        for event in connection.iter() {
+            match event {
+                // the session starts clean, so subscribe again on every connect
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    if let Err(err) = client.try_subscribe(&topic, QoS::AtLeastOnce) {
+                        warn!("failed to subscribe to {}: {}", topic, err);
+                    }
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    let message = String::from_utf8_lossy(&publish.payload);
+                    if let Err(err) = handle(message.trim(), &commands, &ctx) {
+                        warn!("ignoring MQTT message {:?}: {}", message, err);
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    warn!("MQTT connection failed: {}", err);
+                    thread::sleep(RECONNECT_DELAY);
+                }
+            }
+        }
+    });
+}
+
+/// Act on one message: a JSON command as taken by `--ipc`, `show`/`on`,
+/// `hide`/`off`, `trigger`, `pause`, `resume`, or otherwise the file or URL
+/// to switch to.
+fn handle(message: &str, commands: &Sender<Command>, ctx: &egui::Context) -> Result<(), String> {
+    if message.starts_with('{') {
+        let reply = control::handle_request(message, commands, ctx);
+        return match serde_json::from_str::<serde_json::Value>(&reply) {
+            Ok(reply) if reply["ok"] == serde_json::Value::Bool(true) => Ok(()),
+            _ => Err(reply),
+        };
+    }
+
+    let command = match message.to_ascii_lowercase().as_str() {
+        "" => return Err("empty message".to_string()),
+        // stdin is taken by the running process, so it can't be switched to
+        "-" => return Err("can't switch to stdin".to_string()),
+        "show" | "on" => Command::Show,
+        "hide" | "off" => Command::Hide,
+        "trigger" => Command::Trigger,
+        "pause" => Command::Pause,
+        "resume" => Command::Resume,
+        _ => Command::Play(PathBuf::from(message)),
+    };
+    control::send(commands, ctx, command)
+}
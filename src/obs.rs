@@ -0,0 +1,113 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sha2::{Digest, Sha256};
+use std::{net::TcpStream, thread, time::Duration};
+use tracing::{info, warn};
+use tungstenite::{stream::MaybeTlsStream, Message, WebSocket};
+
+/// how long to wait before trying OBS again after the connection drops
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+// obs-websocket 5 opcodes
+const OP_HELLO: u64 = 0;
+const OP_IDENTIFY: u64 = 1;
+const OP_IDENTIFIED: u64 = 2;
+const OP_EVENT: u64 = 5;
+const OP_REQUEST: u64 = 6;
+const OP_REQUEST_RESPONSE: u64 = 7;
+/// the `Scenes` event subscription
+const SCENE_EVENTS: u64 = 1 << 2;
+
+/// Where to find OBS, from `--obs` and `--obs-password`.
+#[derive(Debug, Clone)]
+pub struct Connection {
+    pub url: String,
+    pub password: Option<String>,
+}
+
+/// Call `on_scene` with the name of OBS's program scene once connected and
+/// every time it changes, reconnecting in the background whenever OBS goes
+/// away.
+pub fn watch(connection: Connection, mut on_scene: impl FnMut(&str) + Send + 'static) {
+    thread::spawn(move || loop {
+        if let Err(err) = follow_scenes(&connection, &mut on_scene) {
+            warn!("OBS connection to {} failed: {}", connection.url, err);
+        }
+        thread::sleep(RECONNECT_DELAY);
+    });
+}
+
+fn follow_scenes(connection: &Connection, on_scene: &mut impl FnMut(&str)) -> Result<(), String> {
+    let (mut socket, _) =
+        tungstenite::connect(connection.url.as_str()).map_err(|err| err.to_string())?;
+    loop {
+        let text = match socket.read().map_err(|err| err.to_string())? {
+            Message::Text(text) => text,
+            Message::Close(frame) => {
+                return Err(frame.map_or("closed by OBS".to_string(), |frame| {
+                    format!("closed by OBS: {}", frame.reason)
+                }));
+            }
+            _ => continue,
+        };
+        let message: serde_json::Value =
+            serde_json::from_str(&text).map_err(|err| err.to_string())?;
+        let data = &message["d"];
+
+        let scene = match message["op"].as_u64() {
+            Some(OP_HELLO) => {
+                let mut identify = serde_json::json!({
+                    "rpcVersion": 1,
+                    "eventSubscriptions": SCENE_EVENTS,
+                });
+                if let Some(auth) = data.get("authentication") {
+                    let password = connection
+                        .password
+                        .as_deref()
+                        .ok_or("OBS asks for a password, pass --obs-password")?;
+                    identify["authentication"] = authenticate(
+                        password,
+                        auth["salt"].as_str().unwrap_or(""),
+                        auth["challenge"].as_str().unwrap_or(""),
+                    )
+                    .into();
+                }
+                send(&mut socket, OP_IDENTIFY, identify)?;
+                None
+            }
+            Some(OP_IDENTIFIED) => {
+                info!("Connected to OBS at {}", connection.url);
+                let request = serde_json::json!({
+                    "requestType": "GetCurrentProgramScene",
+                    "requestId": "current-scene",
+                });
+                send(&mut socket, OP_REQUEST, request)?;
+                None
+            }
+            Some(OP_REQUEST_RESPONSE) => data["responseData"]["currentProgramSceneName"].as_str(),
+            Some(OP_EVENT) if data["eventType"] == "CurrentProgramSceneChanged" => {
+                data["eventData"]["sceneName"].as_str()
+            }
+            _ => None,
+        };
+        if let Some(scene) = scene {
+            on_scene(scene);
+        }
+    }
+}
+
+fn send(
+    socket: &mut WebSocket<MaybeTlsStream<TcpStream>>,
+    op: u64,
+    data: serde_json::Value,
+) -> Result<(), String> {
+    let message = serde_json::json!({ "op": op, "d": data });
+    socket
+        .send(Message::Text(message.to_string()))
+        .map_err(|err| err.to_string())
+}
+
+/// the obs-websocket 5 answer to a password challenge
+fn authenticate(password: &str, salt: &str, challenge: &str) -> String {
+    let secret = STANDARD.encode(Sha256::digest(format!("{}{}", password, salt)));
+    STANDARD.encode(Sha256::digest(format!("{}{}", secret, challenge)))
+}
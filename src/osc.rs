@@ -0,0 +1,100 @@
+use crate::control::{self, Command};
+use eframe::egui;
+use rosc::{OscMessage, OscPacket, OscType};
+use std::{
+    io,
+    net::{IpAddr, UdpSocket},
+    path::PathBuf,
+    sync::mpsc::Sender,
+    thread,
+};
+use tracing::{info, warn};
+
+/// Listen for OSC messages on UDP `port` at `address`, passing them on to
+/// `commands` and waking the UI through `ctx`.
+pub fn listen(
+    address: IpAddr,
+    port: u16,
+    commands: Sender<Command>,
+    ctx: &egui::Context,
+) -> io::Result<()> {
+    let socket = UdpSocket::bind((address, port))?;
+    info!("Listening for OSC on udp://{}", socket.local_addr()?);
+
+    let ctx = ctx.clone();
+    thread::spawn(move || {
+        let mut buffer = [0; rosc::decoder::MTU];
+        while let Ok(size) = socket.recv(&mut buffer) {
+            match rosc::decoder::decode_udp(&buffer[..size]) {
+                Ok((_, packet)) => handle_packet(packet, &commands, &ctx),
+                Err(err) => warn!("ignoring malformed OSC packet: {:?}", err),
+            }
+        }
+    });
+    Ok(())
+}
+
+fn handle_packet(packet: OscPacket, commands: &Sender<Command>, ctx: &egui::Context) {
+    match packet {
+        OscPacket::Message(message) => {
+            let result =
+                command(&message).and_then(|command| control::send(commands, ctx, command));
+            if let Err(err) = result {
+                warn!("ignoring OSC message {}: {}", message.addr, err);
+            }
+        }
+        // bundles are carried out straight away, whatever their time tag
+        OscPacket::Bundle(bundle) => {
+            for packet in bundle.content {
+                handle_packet(packet, commands, ctx);
+            }
+        }
+    }
+}
+
+/// Map an OSC address and its arguments to a command:
+///
+/// - `/opacity 0.5`, `/scale 2`
+/// - `/position X Y` in desktop points
+/// - `/play` and `/pause`, or `/play 0`/`/play 1` from a toggle
+/// - `/seek FRAME`, `/trigger`, `/load PATH`
+/// - `/show`, `/hide`, or `/visible 0`/`/visible 1`
+fn command(message: &OscMessage) -> Result<Command, String> {
+    let number = |index: usize| {
+        message
+            .args
+            .get(index)
+            .and_then(|arg| match arg {
+                OscType::Float(value) => Some(*value),
+                OscType::Double(value) => Some(*value as f32),
+                OscType::Int(value) => Some(*value as f32),
+                OscType::Long(value) => Some(*value as f32),
+                OscType::Bool(value) => Some(f32::from(u8::from(*value))),
+                _ => None,
+            })
+            .ok_or_else(|| format!("expected a number as argument {}", index + 1))
+    };
+    // a toggle with no argument is a plain button press
+    let switch = || message.args.is_empty() || number(0).is_ok_and(|value| value >= 0.5);
+
+    let command = match message.addr.as_str() {
+        "/opacity" => Command::Opacity(number(0)?),
+        "/scale" => Command::Scale(number(0)?),
+        "/position" => Command::Move(egui::pos2(number(0)?, number(1)?)),
+        "/play" if switch() => Command::Resume,
+        "/play" | "/pause" => Command::Pause,
+        "/seek" => Command::Seek(number(0)?.max(0.0) as usize),
+        "/trigger" => Command::Trigger,
+        "/load" => match message.args.first() {
+            Some(OscType::String(path)) if !path.is_empty() && path != "-" => {
+                Command::Play(PathBuf::from(path))
+            }
+            _ => return Err("expected a path".to_string()),
+        },
+        "/visible" if switch() => Command::Show,
+        "/show" => Command::Show,
+        "/visible" | "/hide" => Command::Hide,
+        address => return Err(format!("unknown address {}", address)),
+    };
+    Ok(command)
+}
@@ -0,0 +1,184 @@
+use clap::ValueEnum;
+use eframe::egui::{emath::Rot2, pos2, vec2, Color32, Painter, Pos2, Rect, Shape, Stroke, Vec2};
+use rand::Rng;
+use std::{f32::consts::TAU, time::Instant};
+
+/// the most particles alive at once, however large the area
+const MAX_PARTICLES: usize = 2000;
+
+/// longest simulation step, so a pause while hidden doesn't make everything jump
+const MAX_STEP_SECS: f32 = 0.1;
+
+const CONFETTI_COLORS: &[Color32] = &[
+    Color32::from_rgb(0xE5, 0x39, 0x35),
+    Color32::from_rgb(0xFD, 0xD8, 0x35),
+    Color32::from_rgb(0x43, 0xA0, 0x47),
+    Color32::from_rgb(0x1E, 0x88, 0xE5),
+    Color32::from_rgb(0x8E, 0x24, 0xAA),
+    Color32::from_rgb(0xFF, 0x70, 0x43),
+];
+
+/// Kind of particles drawn with `--particles`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Style {
+    /// flakes drifting down and swaying from side to side
+    Snow,
+    /// colored paper tumbling down
+    Confetti,
+    /// glowing embers shooting up from the bottom and falling back
+    Sparks,
+}
+
+struct Particle {
+    pos: Pos2,
+    velocity: Vec2,
+    size: f32,
+    color: Color32,
+    // radians, and radians per second, for confetti
+    angle: f32,
+    spin: f32,
+    // seconds alive, and how long it lives at most
+    age: f32,
+    lifetime: f32,
+}
+
+/// Spawns, moves, and draws the particles for `--particles` across whatever
+/// area they're given each frame.
+pub struct Emitter {
+    style: Style,
+    density: f32,
+    particles: Vec<Particle>,
+    // fractional particles owed from earlier steps
+    pending: f32,
+    last_step: Option<Instant>,
+}
+
+impl Emitter {
+    pub fn new(style: Style, density: f32) -> Self {
+        Self {
+            style,
+            density: density.max(0.0),
+            particles: Vec::new(),
+            pending: 0.0,
+            last_step: None,
+        }
+    }
+
+    /// advance the simulation to now, spawning new particles over `area`
+    pub fn step(&mut self, area: Rect) {
+        let now = Instant::now();
+        let secs = self
+            .last_step
+            .replace(now)
+            .map_or(0.0, |last| (now - last).as_secs_f32().min(MAX_STEP_SECS));
+
+        let gravity = match self.style {
+            Style::Snow => 0.0,
+            Style::Confetti => 60.0,
+            Style::Sparks => 500.0,
+        };
+        for particle in &mut self.particles {
+            particle.age += secs;
+            particle.velocity.y += gravity * secs;
+            particle.pos += particle.velocity * secs;
+            particle.angle += particle.spin * secs;
+            if self.style == Style::Snow {
+                // sway sideways, each flake on its own rhythm
+                particle.pos.x += (particle.age * 1.5 + particle.spin).sin() * 20.0 * secs;
+            }
+        }
+        let bottom = area.bottom() + 20.0;
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime && particle.pos.y < bottom);
+
+        self.pending += self.spawn_rate(area) * secs;
+        let mut rng = rand::thread_rng();
+        while self.pending >= 1.0 {
+            self.pending -= 1.0;
+            if self.particles.len() < MAX_PARTICLES {
+                let particle = self.spawn(area, &mut rng);
+                self.particles.push(particle);
+            }
+        }
+    }
+
+    /// new particles per second for an `area` this size
+    fn spawn_rate(&self, area: Rect) -> f32 {
+        let rate = match self.style {
+            Style::Snow => area.width() * 0.03,
+            Style::Confetti => area.width() * 0.05,
+            Style::Sparks => 60.0,
+        };
+        rate * self.density
+    }
+
+    fn spawn(&self, area: Rect, rng: &mut impl Rng) -> Particle {
+        let above = pos2(rng.gen_range(area.left()..=area.right()), area.top() - 10.0);
+        match self.style {
+            Style::Snow => Particle {
+                pos: above,
+                velocity: vec2(rng.gen_range(-10.0..10.0), rng.gen_range(30.0..80.0)),
+                size: rng.gen_range(1.5..4.0),
+                color: Color32::WHITE.gamma_multiply(rng.gen_range(0.6..1.0)),
+                angle: 0.0,
+                spin: rng.gen_range(0.0..TAU),
+                age: 0.0,
+                lifetime: f32::INFINITY,
+            },
+            Style::Confetti => Particle {
+                pos: above,
+                velocity: vec2(rng.gen_range(-40.0..40.0), rng.gen_range(60.0..140.0)),
+                size: rng.gen_range(5.0..10.0),
+                color: CONFETTI_COLORS[rng.gen_range(0..CONFETTI_COLORS.len())],
+                angle: rng.gen_range(0.0..TAU),
+                spin: rng.gen_range(-6.0..6.0),
+                age: 0.0,
+                lifetime: f32::INFINITY,
+            },
+            Style::Sparks => {
+                // a fountain from the middle of the bottom edge
+                let direction = Rot2::from_angle(rng.gen_range(-0.5..0.5)) * vec2(0.0, -1.0);
+                Particle {
+                    pos: pos2(area.center().x, area.bottom()),
+                    velocity: direction * rng.gen_range(0.5..1.0) * area.height().sqrt() * 25.0,
+                    size: rng.gen_range(1.0..2.5),
+                    color: Color32::from_rgb(255, rng.gen_range(120..220), 40),
+                    angle: 0.0,
+                    spin: 0.0,
+                    age: 0.0,
+                    lifetime: rng.gen_range(0.6..1.6),
+                }
+            }
+        }
+    }
+
+    /// draw every particle at `opacity`
+    pub fn paint(&self, painter: &Painter, opacity: f32) {
+        for particle in &self.particles {
+            let color = particle.color.gamma_multiply(opacity);
+            match self.style {
+                Style::Snow => painter.circle_filled(particle.pos, particle.size, color),
+                Style::Confetti => {
+                    // a strip of paper, looking thinner as it turns edge-on
+                    let rotation = Rot2::from_angle(particle.angle);
+                    let width = (particle.size * 0.4 * particle.angle.cos()).abs();
+                    let half = vec2(particle.size, width.max(0.5));
+                    let corners = [
+                        vec2(-1.0, -1.0),
+                        vec2(1.0, -1.0),
+                        vec2(1.0, 1.0),
+                        vec2(-1.0, 1.0),
+                    ]
+                    .map(|corner| particle.pos + rotation * (corner * half));
+                    painter.add(Shape::convex_polygon(corners.to_vec(), color, Stroke::NONE));
+                }
+                Style::Sparks => {
+                    // streaks along the direction of travel, dimming as they burn out
+                    let color = color.gamma_multiply(1.0 - particle.age / particle.lifetime);
+                    let tail = particle.pos - particle.velocity * 0.03;
+                    painter.line_segment([tail, particle.pos], Stroke::new(particle.size, color));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use eframe::egui::{pos2, Pos2, Rect, Vec2};
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+/// how long the pet walks before it considers stopping, in seconds
+const WALK_SECS: std::ops::Range<f32> = 2.0..8.0;
+
+/// Desktop pet that wanders back and forth along the bottom of the screen,
+/// stopping now and then and picking a new direction afterwards.
+pub struct Pet {
+    // walking speed in points per second
+    speed: f32,
+    // longest pause between walks, zero for none
+    max_pause: Duration,
+    // distance between the pet's feet and the bottom of the screen
+    floor: f32,
+    x: Option<f32>,
+    facing_left: bool,
+    paused: bool,
+    // when the current walk or pause ends
+    phase_until: Instant,
+    last_step: Instant,
+}
+
+impl Pet {
+    pub fn new(speed: f32, max_pause: Duration, floor: f32) -> Self {
+        Self {
+            speed,
+            max_pause,
+            floor,
+            x: None,
+            facing_left: rand::thread_rng().gen(),
+            paused: false,
+            phase_until: Instant::now() + random_secs(WALK_SECS),
+            last_step: Instant::now(),
+        }
+    }
+
+    /// whether the animation should be mirrored to match the walking direction
+    pub fn facing_left(&self) -> bool {
+        self.facing_left
+    }
+
+    /// Advance the walk and return where a `window`-sized overlay should be on
+    /// `screen`, starting from `current` on the first step.
+    pub fn step(&mut self, current: Pos2, screen: Rect, window: Vec2) -> Pos2 {
+        let elapsed = self.last_step.elapsed().as_secs_f32();
+        self.last_step = Instant::now();
+
+        if Instant::now() >= self.phase_until {
+            self.next_phase();
+        }
+
+        let min_x = screen.left();
+        let max_x = (screen.right() - window.x).max(min_x);
+        let mut x = self.x.unwrap_or(current.x).clamp(min_x, max_x);
+
+        if !self.paused {
+            let direction = if self.facing_left { -1.0 } else { 1.0 };
+            x += direction * self.speed * elapsed;
+            // turn around at either edge of the screen
+            if x <= min_x || x >= max_x {
+                x = x.clamp(min_x, max_x);
+                self.facing_left = x >= max_x;
+            }
+        }
+        self.x = Some(x);
+
+        pos2(x, screen.bottom() - window.y - self.floor)
+    }
+
+    fn next_phase(&mut self) {
+        let mut rng = rand::thread_rng();
+        if self.paused || self.max_pause.is_zero() {
+            self.paused = false;
+            // after a rest, wander off in a random direction
+            self.facing_left = rng.gen();
+            self.phase_until = Instant::now() + random_secs(WALK_SECS);
+        } else {
+            self.paused = true;
+            let pause = rng.gen_range(0.5..=self.max_pause.as_secs_f32().max(0.5));
+            self.phase_until = Instant::now() + Duration::from_secs_f32(pause);
+        }
+    }
+}
+
+fn random_secs(range: std::ops::Range<f32>) -> Duration {
+    Duration::from_secs_f32(rand::thread_rng().gen_range(range))
+}
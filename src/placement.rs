@@ -0,0 +1,109 @@
+use clap::ValueEnum;
+use display_info::DisplayInfo;
+use eframe::egui::{pos2, vec2, Pos2, Rect, Vec2};
+use tracing::info;
+
+/// Named spot on the monitor to place the overlay at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Anchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+impl Anchor {
+    /// Top-left corner for a `window`-sized overlay placed at this anchor on
+    /// `screen`, kept `margin` points away from the edges.
+    pub fn position(self, screen: Rect, window: Vec2, margin: f32) -> Pos2 {
+        let left = screen.left() + margin;
+        let top = screen.top() + margin;
+        let right = screen.right() - window.x - margin;
+        let bottom = screen.bottom() - window.y - margin;
+
+        match self {
+            Anchor::TopLeft => pos2(left, top),
+            Anchor::TopRight => pos2(right, top),
+            Anchor::BottomLeft => pos2(left, bottom),
+            Anchor::BottomRight => pos2(right, bottom),
+            Anchor::Center => screen.center() - window / 2.0,
+        }
+    }
+}
+
+/// A display picked with `--monitor`, with its geometry in desktop coordinates.
+#[derive(Debug, Clone)]
+pub struct Monitor {
+    pub name: String,
+    pub rect: Rect,
+}
+
+/// Find a monitor by its index (as printed at startup) or by name, ignoring
+/// case. The error lists the monitors there are to pick from.
+pub fn find_monitor(selector: &str) -> Result<Monitor, String> {
+    let displays = DisplayInfo::all().map_err(|err| format!("failed to list monitors: {}", err))?;
+
+    for (idx, display) in displays.iter().enumerate() {
+        info!(
+            "Monitor {}: {} {}x{} at ({}, {}){}",
+            idx,
+            display.name,
+            display.width,
+            display.height,
+            display.x,
+            display.y,
+            if display.is_primary { " (primary)" } else { "" }
+        );
+    }
+
+    let display = match selector.parse::<usize>() {
+        Ok(idx) => displays.get(idx),
+        Err(_) => displays
+            .iter()
+            .find(|display| display.name.eq_ignore_ascii_case(selector)),
+    };
+    let Some(display) = display else {
+        let available: Vec<String> = displays
+            .iter()
+            .enumerate()
+            .map(|(idx, display)| format!("{} ({})", idx, display.name))
+            .collect();
+        return Err(format!(
+            "no monitor matching {:?}; available: {}",
+            selector,
+            available.join(", ")
+        ));
+    };
+
+    Ok(Monitor {
+        name: display.name.clone(),
+        rect: Rect::from_min_size(
+            pos2(display.x as f32, display.y as f32),
+            vec2(display.width as f32, display.height as f32),
+        ),
+    })
+}
+
+/// Where `window` should move to so that any edge within `threshold` points
+/// of the matching `screen` edge sits flush against it, or `None` if no edge
+/// is close enough.
+pub fn snap(window: Rect, screen: Rect, threshold: f32) -> Option<Pos2> {
+    let snap_axis = |start: f32, end: f32, screen_start: f32, screen_end: f32| {
+        if (start - screen_start).abs() <= threshold {
+            Some(screen_start)
+        } else if (end - screen_end).abs() <= threshold {
+            Some(screen_end - (end - start))
+        } else {
+            None
+        }
+    };
+
+    let x = snap_axis(window.left(), window.right(), screen.left(), screen.right());
+    let y = snap_axis(window.top(), window.bottom(), screen.top(), screen.bottom());
+    if x.is_none() && y.is_none() {
+        return None;
+    }
+
+    Some(pos2(x.unwrap_or(window.left()), y.unwrap_or(window.top())))
+}
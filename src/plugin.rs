@@ -0,0 +1,177 @@
+use libloading::Library;
+use std::{
+    ffi::{c_char, c_void, CString},
+    fmt,
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+/// the plugin interface version this build speaks
+const API_VERSION: u32 = 1;
+
+/// An effect or integration added at startup with `--plugin`.
+pub trait Plugin: fmt::Debug {
+    /// change a frame's unmultiplied RGBA `pixels` after the built-in
+    /// filters, before it's uploaded
+    fn filter_frame(&self, _pixels: &mut [u8], _size: [usize; 2]) {}
+
+    /// hear about something that happened to the overlay
+    fn on_event(&self, _event: &Event) {}
+}
+
+/// Something that happened to the overlay, as passed to `Plugin::on_event`.
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// a playlist entry or alert started playing
+    Clip(PathBuf),
+    /// the animation wrapped around, with the loops so far
+    Loop(usize),
+    Trigger,
+    Quit,
+}
+
+impl Event {
+    /// the event's name and detail as native plugins get them
+    fn parts(&self) -> (&'static str, String) {
+        match self {
+            Event::Clip(path) => ("clip", path.to_string_lossy().into_owned()),
+            Event::Loop(count) => ("loop", count.to_string()),
+            Event::Trigger => ("trigger", String::new()),
+            Event::Quit => ("quit", String::new()),
+        }
+    }
+}
+
+type VersionFn = unsafe extern "C" fn() -> u32;
+type NewFn = unsafe extern "C" fn() -> *mut c_void;
+type FilterFn = unsafe extern "C" fn(*mut c_void, *mut u8, u32, u32);
+type EventFn = unsafe extern "C" fn(*mut c_void, *const c_char, *const c_char);
+type FreeFn = unsafe extern "C" fn(*mut c_void);
+
+/// A plugin built as a shared library (`.so`, `.dylib`, or `.dll`) with a C
+/// interface:
+///
+/// ```c
+/// // required: the interface version the plugin was written for, 1
+/// uint32_t overlay_plugin_api_version(void);
+/// // optional: state handed back to every other call
+/// void *overlay_plugin_new(void);
+/// // optional: change a frame's unmultiplied RGBA pixels in place
+/// void overlay_plugin_filter(void *state, uint8_t *rgba, uint32_t width, uint32_t height);
+/// // optional: "clip" (detail: the file), "loop" (detail: the count),
+/// // "trigger", or "quit"
+/// void overlay_plugin_event(void *state, const char *event, const char *detail);
+/// // optional: release the state when the overlay exits
+/// void overlay_plugin_free(void *state);
+/// ```
+pub struct NativePlugin {
+    name: String,
+    state: *mut c_void,
+    filter: Option<FilterFn>,
+    event: Option<EventFn>,
+    free: Option<FreeFn>,
+    // keeps the functions above loaded, so it has to go last
+    _library: Library,
+}
+
+impl NativePlugin {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        // loading runs the library's initializers, which is what
+        // asking for a plugin means
+        let library = unsafe { Library::new(path) }.map_err(|err| err.to_string())?;
+        // the symbols are looked up with the signatures documented above
+        unsafe {
+            let version = library
+                .get::<VersionFn>(b"overlay_plugin_api_version\0")
+                .map_err(|_| "not an overlay plugin: no overlay_plugin_api_version".to_string())?;
+            let version = version();
+            if version != API_VERSION {
+                return Err(format!(
+                    "written for plugin interface {}, this overlay has {}",
+                    version, API_VERSION
+                ));
+            }
+
+            let state = match library.get::<NewFn>(b"overlay_plugin_new\0") {
+                Ok(new) => new(),
+                Err(_) => std::ptr::null_mut(),
+            };
+            Ok(Self {
+                name: path.file_stem().map_or_else(
+                    || path.display().to_string(),
+                    |stem| stem.to_string_lossy().into_owned(),
+                ),
+                state,
+                filter: library
+                    .get::<FilterFn>(b"overlay_plugin_filter\0")
+                    .ok()
+                    .map(|symbol| *symbol),
+                event: library
+                    .get::<EventFn>(b"overlay_plugin_event\0")
+                    .ok()
+                    .map(|symbol| *symbol),
+                free: library
+                    .get::<FreeFn>(b"overlay_plugin_free\0")
+                    .ok()
+                    .map(|symbol| *symbol),
+                _library: library,
+            })
+        }
+    }
+}
+
+impl Plugin for NativePlugin {
+    fn filter_frame(&self, pixels: &mut [u8], size: [usize; 2]) {
+        if let Some(filter) = self.filter {
+            // `pixels` holds `size[0] * size[1]` RGBA pixels
+            unsafe {
+                filter(
+                    self.state,
+                    pixels.as_mut_ptr(),
+                    size[0] as u32,
+                    size[1] as u32,
+                )
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event) {
+        let Some(on_event) = self.event else {
+            return;
+        };
+        let (name, detail) = event.parts();
+        let name = CString::new(name).unwrap_or_default();
+        let detail = CString::new(detail).unwrap_or_default();
+        // both strings outlive the call
+        unsafe { on_event(self.state, name.as_ptr(), detail.as_ptr()) }
+    }
+}
+
+impl Drop for NativePlugin {
+    fn drop(&mut self) {
+        if let Some(free) = self.free {
+            // `state` came from this library's `overlay_plugin_new`
+            unsafe { free(self.state) }
+        }
+    }
+}
+
+impl fmt::Debug for NativePlugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NativePlugin")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Load the `--plugin` at `path`, reporting what it offers.
+pub fn load(path: &Path) -> Result<Box<dyn Plugin>, String> {
+    let plugin = NativePlugin::load(path)?;
+    info!(
+        "Loaded plugin {} (frame filter: {}, events: {})",
+        plugin.name,
+        plugin.filter.is_some(),
+        plugin.event.is_some()
+    );
+    Ok(Box::new(plugin))
+}
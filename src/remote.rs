@@ -0,0 +1,66 @@
+use std::{
+    io::{self, Read},
+    path::Path,
+};
+use tracing::{debug, info};
+
+/// report download progress at most this often
+const PROGRESS_STEP: u64 = 256 * 1024;
+
+/// whether the input names an http(s) URL rather than a local path
+pub fn is_url(path: &Path) -> bool {
+    path.to_str()
+        .is_some_and(|s| s.starts_with("http://") || s.starts_with("https://"))
+}
+
+/// Start downloading `url` and return the response body as a stream, so
+/// decoders can produce frames while the rest of the file is still arriving.
+pub fn open(url: &str) -> io::Result<impl Read> {
+    info!("Downloading: {}", url);
+
+    let response = ureq::get(url).call().map_err(io::Error::other)?;
+    let total = response
+        .header("Content-Length")
+        .and_then(|len| len.parse().ok());
+
+    Ok(ProgressReader {
+        inner: response.into_reader(),
+        total,
+        read: 0,
+        next_report: PROGRESS_STEP,
+    })
+}
+
+struct ProgressReader<R> {
+    inner: R,
+    total: Option<u64>,
+    read: u64,
+    next_report: u64,
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+
+        let finished = n == 0 && self.next_report != u64::MAX;
+        if finished || self.read >= self.next_report {
+            match self.total {
+                Some(total) if total > 0 => debug!(
+                    "download progress: {:.1}KB/{:.1}KB ({:.1}%)",
+                    self.read as f64 / 1024.0,
+                    total as f64 / 1024.0,
+                    (self.read as f64 / total as f64) * 100.0
+                ),
+                _ => debug!("download progress: {:.1}KB", self.read as f64 / 1024.0),
+            }
+            self.next_report = if finished {
+                u64::MAX
+            } else {
+                self.read + PROGRESS_STEP
+            };
+        }
+
+        Ok(n)
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{
+    budget, compose, deliver, wait_while_held, BadFrames, InputFormat, LoaderOptions,
+    LoadingMessage, MAX_FAILED_FRAMES_IN_A_ROW,
+};
+use eframe::egui;
+use image::{
+    codecs::{png::PngDecoder, webp::WebPDecoder},
+    AnimationDecoder, Frames,
+};
+use std::{
+    collections::VecDeque,
+    io::Cursor,
+    sync::mpsc::Sender,
+    thread,
+    time::{Duration, Instant},
+};
+use tracing::{debug, info};
+
+/// a decoded frame, and where it is in the animation, waiting for its turn
+/// on screen
+type Pending = (usize, Vec<u8>, [usize; 2], Duration);
+
+/// An animation's frames decoded over and over from its encoded `data`,
+/// starting the decoder again after the last one.
+struct Looped<'a> {
+    data: &'a [u8],
+    format: InputFormat,
+    options: LoaderOptions,
+    frames: Frames<'a>,
+    // frames decoded since the decoder last started over
+    decoded: usize,
+    failed_in_a_row: usize,
+}
+
+impl<'a> Looped<'a> {
+    fn new(data: &'a [u8], format: InputFormat, options: LoaderOptions) -> Result<Self, String> {
+        Ok(Self {
+            data,
+            format,
+            options,
+            frames: open(data, format)?,
+            decoded: 0,
+            failed_in_a_row: 0,
+        })
+    }
+
+    fn next_frame(&mut self) -> Result<Pending, String> {
+        loop {
+            match self.frames.next() {
+                Some(Ok(frame)) => {
+                    self.decoded += 1;
+                    self.failed_in_a_row = 0;
+                    let delay = match (Duration::from(frame.delay()), self.options.zero_delay) {
+                        (delay, Some(zero_delay)) if delay.is_zero() => zero_delay,
+                        (delay, _) => delay,
+                    };
+                    let buffer = frame.into_buffer();
+                    let size = [buffer.width() as usize, buffer.height() as usize];
+                    return Ok((self.decoded - 1, buffer.into_raw(), size, delay));
+                }
+                // the frame before stays up a little longer in its place
+                Some(Err(err)) if self.options.bad_frames != BadFrames::Stop => {
+                    debug!("failed to decode frame {}: {}", self.decoded, err);
+                    self.failed_in_a_row += 1;
+                    if self.failed_in_a_row >= MAX_FAILED_FRAMES_IN_A_ROW {
+                        self.restart()?;
+                    }
+                }
+                Some(Err(err)) => {
+                    return Err(format!("failed to decode frame {}: {}", self.decoded, err))
+                }
+                None => self.restart()?,
+            }
+        }
+    }
+
+    fn restart(&mut self) -> Result<(), String> {
+        if self.decoded == 0 {
+            return Err("no frame could be decoded".to_string());
+        }
+        self.frames = open(self.data, self.format)?;
+        self.decoded = 0;
+        self.failed_in_a_row = 0;
+        Ok(())
+    }
+}
+
+fn open(data: &[u8], format: InputFormat) -> Result<Frames<'_>, String> {
+    let reader = Cursor::new(data);
+    Ok(match format {
+        InputFormat::Gif => compose::ComposedGifFrames::new(reader)
+            .map_err(|err| format!("failed to read GIF: {}", err))?
+            .into_frames(),
+        InputFormat::WebP => WebPDecoder::new(reader)
+            .map_err(|err| format!("failed to read WebP: {}", err))?
+            .into_frames(),
+        InputFormat::Png => PngDecoder::new(reader)
+            .map_err(|err| format!("failed to read PNG: {}", err))?
+            .apng()
+            .into_frames(),
+        _ => return Err(format!("{:?} input can't be streamed", format)),
+    })
+}
+
+/// Decode the GIF, WebP, or APNG in `data` as it plays, keeping at most
+/// `capacity` frames decoded ahead and pushing each to the UI when it's due,
+/// the way videos are played. The spare frames absorb the ones that take
+/// longer to decode. Frames shown smaller than they are queue up shrunk.
+///
+/// A frame goes out as soon as it's due rather than once the queue is full,
+/// so playback starts with the first frame decoded, and the time until the
+/// next one is due goes into decoding the frames after it, carrying on past
+/// the last into the next loop so the loop point never waits on a restart.
+/// Frames go out with their index in the animation, so the UI sees a loop go
+/// by when it starts from 0 again.
+pub fn play(
+    data: &[u8],
+    format: InputFormat,
+    options: LoaderOptions,
+    capacity: usize,
+    sender: &Sender<LoadingMessage>,
+    ctx: &egui::Context,
+) -> Result<(), String> {
+    let mut frames = Looped::new(data, format, options)?;
+    let mut ahead: VecDeque<Pending> = VecDeque::with_capacity(capacity);
+    let mut next_deadline = Instant::now();
+    // settled by the first frame
+    let mut shrink = None;
+    info!("Streaming frames, decoding up to {} ahead", capacity);
+
+    loop {
+        let due = !ahead.is_empty() && Instant::now() >= next_deadline;
+        if !due && ahead.len() < capacity {
+            let (frame_idx, pixels, size, delay) = frames.next_frame()?;
+            let factor = match shrink {
+                Some(factor) => factor,
+                None => {
+                    let factor = options.downscale_factor(size, ctx);
+                    if let Some(factor) = factor {
+                        // the UI still shows them at the original size
+                        if deliver(sender, LoadingMessage::RenderScale(factor)).is_err() {
+                            return Ok(());
+                        }
+                    }
+                    *shrink.insert(factor)
+                }
+            };
+            let (pixels, size) = match factor {
+                Some(factor) => budget::shrink_frame(pixels, size, factor),
+                None => (pixels, size),
+            };
+            ahead.push_back((frame_idx, pixels, size, delay));
+            continue;
+        }
+        let Some((frame_idx, pixels, size, delay)) = ahead.pop_front() else {
+            continue;
+        };
+
+        // decoding stops while the overlay is hidden or paused
+        if wait_while_held() {
+            next_deadline = Instant::now();
+        }
+        let now = Instant::now();
+        if next_deadline > now {
+            thread::sleep(next_deadline - now);
+        }
+        next_deadline += delay;
+
+        // the window closed, or it moved on to another input
+        if deliver(
+            sender,
+            LoadingMessage::StreamFrame(frame_idx, pixels, size, delay),
+        )
+        .is_err()
+        {
+            return Ok(());
+        }
+        ctx.request_repaint();
+    }
+}
@@ -0,0 +1,79 @@
+use chrono::{NaiveTime, Timelike};
+use std::{fmt, str::FromStr, time::Duration};
+
+/// Daily time windows during which the overlay is shown, e.g.
+/// `09:00-17:00` or `08:00-12:00,13:00-17:30`. A window whose end is before
+/// its start runs past midnight (`22:00-02:00`).
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    windows: Vec<(NaiveTime, NaiveTime)>,
+}
+
+impl Schedule {
+    /// whether `now` falls inside any window
+    pub fn is_active(&self, now: NaiveTime) -> bool {
+        self.windows.iter().any(|&(start, end)| {
+            if start <= end {
+                now >= start && now < end
+            } else {
+                now >= start || now < end
+            }
+        })
+    }
+
+    /// time from `now` until the next window starts or ends
+    pub fn until_next_change(&self, now: NaiveTime) -> Duration {
+        let now_secs = now.num_seconds_from_midnight() as i64;
+        self.windows
+            .iter()
+            .flat_map(|&(start, end)| [start, end])
+            .map(|boundary| {
+                let secs = boundary.num_seconds_from_midnight() as i64 - now_secs;
+                // boundaries earlier today come around again tomorrow
+                if secs <= 0 {
+                    secs + 24 * 60 * 60
+                } else {
+                    secs
+                }
+            })
+            .min()
+            .map_or(Duration::from_secs(60), |secs| {
+                Duration::from_secs(secs as u64)
+            })
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseScheduleError(String);
+
+impl fmt::Display for ParseScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid schedule window {:?}, expected HH:MM-HH:MM",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseScheduleError {}
+
+impl FromStr for Schedule {
+    type Err = ParseScheduleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let windows = s
+            .split(',')
+            .map(str::trim)
+            .map(|window| {
+                let parse = |t: &str| NaiveTime::parse_from_str(t.trim(), "%H:%M").ok();
+                window
+                    .split_once('-')
+                    .and_then(|(start, end)| Some((parse(start)?, parse(end)?)))
+                    .ok_or_else(|| ParseScheduleError(window.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { windows })
+    }
+}
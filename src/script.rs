@@ -0,0 +1,218 @@
+use crate::control::Command;
+use eframe::egui;
+use mlua::{Function, IntoLuaMulti, Lua, MultiValue, Table};
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+use tracing::{info, warn};
+
+/// What a script can see of the overlay, refreshed before every callback.
+#[derive(Debug, Clone, Default)]
+pub struct State {
+    pub position: Option<egui::Pos2>,
+    pub screen: Option<egui::Vec2>,
+    pub visible: bool,
+    pub paused: bool,
+    pub opacity: f32,
+    pub scale: f32,
+    pub input: PathBuf,
+    pub frame: usize,
+    pub frames: usize,
+}
+
+/// A `--script` written in Lua, driving the overlay from its `on_start`,
+/// `on_frame`, `on_loop`, and `on_trigger` callbacks through the `overlay`
+/// table:
+///
+/// ```lua
+/// function on_loop(count)
+///   local x, y = overlay.position()
+///   overlay.move_to(x + 40, y)
+///   if count % 5 == 0 then overlay.play("sleep.gif") end
+/// end
+/// ```
+pub struct Script {
+    lua: Lua,
+    state: Rc<RefCell<State>>,
+    // what the script asked for during the current callback
+    commands: Rc<RefCell<Vec<Command>>>,
+    // callbacks that raised an error, so they aren't retried every frame
+    failed: RefCell<HashSet<String>>,
+}
+
+impl Script {
+    /// Run the script at `path` so it can define its callbacks. Files it
+    /// plays are found relative to its folder.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let base = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let script = Self {
+            lua: Lua::new(),
+            state: Rc::default(),
+            commands: Rc::default(),
+            failed: RefCell::default(),
+        };
+        let api = script.api(base).map_err(|err| err.to_string())?;
+        script
+            .lua
+            .globals()
+            .set("overlay", api)
+            .map_err(|err| err.to_string())?;
+        script
+            .lua
+            .load(source)
+            .set_name(path.to_string_lossy())
+            .exec()
+            .map_err(|err| err.to_string())?;
+        info!("Loaded script {}", path.display());
+        Ok(script)
+    }
+
+    /// the `overlay` table scripts call into
+    fn api(&self, base: PathBuf) -> mlua::Result<Table> {
+        let lua = &self.lua;
+        let api = lua.create_table()?;
+
+        let state = &self.state;
+        api.set(
+            "position",
+            getter(lua, state, |state| {
+                let position = state.position.unwrap_or_default();
+                (position.x, position.y)
+            })?,
+        )?;
+        api.set(
+            "screen_size",
+            getter(lua, state, |state| {
+                let screen = state.screen.unwrap_or_default();
+                (screen.x, screen.y)
+            })?,
+        )?;
+        api.set("opacity", getter(lua, state, |state| state.opacity)?)?;
+        api.set("scale", getter(lua, state, |state| state.scale)?)?;
+        api.set("visible", getter(lua, state, |state| state.visible)?)?;
+        api.set("paused", getter(lua, state, |state| state.paused)?)?;
+        api.set("frame", getter(lua, state, |state| state.frame)?)?;
+        api.set("frames", getter(lua, state, |state| state.frames)?)?;
+        api.set(
+            "input",
+            getter(lua, state, |state| {
+                state.input.to_string_lossy().into_owned()
+            })?,
+        )?;
+
+        let (state, commands) = (self.state.clone(), self.commands.clone());
+        api.set(
+            "move_to",
+            lua.create_function(move |_, (x, y): (f32, f32)| {
+                // later reads in the same callback see where it's going
+                state.borrow_mut().position = Some(egui::pos2(x, y));
+                commands.borrow_mut().push(Command::Move(egui::pos2(x, y)));
+                Ok(())
+            })?,
+        )?;
+        let (state, commands) = (self.state.clone(), self.commands.clone());
+        api.set(
+            "set_opacity",
+            lua.create_function(move |_, opacity: f32| {
+                state.borrow_mut().opacity = opacity.clamp(0.0, 1.0);
+                commands.borrow_mut().push(Command::Opacity(opacity));
+                Ok(())
+            })?,
+        )?;
+        let (state, commands) = (self.state.clone(), self.commands.clone());
+        api.set(
+            "set_scale",
+            lua.create_function(move |_, scale: f32| {
+                state.borrow_mut().scale = scale;
+                commands.borrow_mut().push(Command::Scale(scale));
+                Ok(())
+            })?,
+        )?;
+
+        let simple: [(&str, fn() -> Command); 6] = [
+            ("show", || Command::Show),
+            ("hide", || Command::Hide),
+            ("pause", || Command::Pause),
+            ("resume", || Command::Resume),
+            ("trigger", || Command::Trigger),
+            ("quit", || Command::Quit),
+        ];
+        for (name, command) in simple {
+            let commands = self.commands.clone();
+            api.set(
+                name,
+                lua.create_function(move |_, ()| {
+                    commands.borrow_mut().push(command());
+                    Ok(())
+                })?,
+            )?;
+        }
+
+        let resolve = move |file: String| {
+            let file = PathBuf::from(file);
+            if file.is_relative() && !crate::is_stream(&file) {
+                base.join(file)
+            } else {
+                file
+            }
+        };
+        let commands = self.commands.clone();
+        let play_resolve = resolve.clone();
+        api.set(
+            "play",
+            lua.create_function(move |_, file: String| {
+                commands
+                    .borrow_mut()
+                    .push(Command::Play(play_resolve(file)));
+                Ok(())
+            })?,
+        )?;
+        let commands = self.commands.clone();
+        api.set(
+            "alert",
+            lua.create_function(move |_, (file, caption): (String, Option<String>)| {
+                commands
+                    .borrow_mut()
+                    .push(Command::Alert(resolve(file), caption));
+                Ok(())
+            })?,
+        )?;
+
+        Ok(api)
+    }
+
+    /// Call `callback` if the script defines it, handing it `arg` and the
+    /// overlay's `state`, and return what it asked the overlay to do.
+    pub fn call(&self, callback: &str, arg: Option<usize>, state: State) -> Vec<Command> {
+        if self.failed.borrow().contains(callback) {
+            return Vec::new();
+        }
+        let Ok(Some(function)) = self.lua.globals().get::<_, Option<Function>>(callback) else {
+            return Vec::new();
+        };
+
+        *self.state.borrow_mut() = state;
+        if let Err(err) = function.call::<_, MultiValue>(arg) {
+            warn!("script {} failed, not calling it again: {}", callback, err);
+            self.failed.borrow_mut().insert(callback.to_string());
+        }
+        std::mem::take(&mut *self.commands.borrow_mut())
+    }
+}
+
+/// a function reading one thing out of the overlay's latest `State`
+fn getter<'lua, R>(
+    lua: &'lua Lua,
+    state: &Rc<RefCell<State>>,
+    get: fn(&State) -> R,
+) -> mlua::Result<Function<'lua>>
+where
+    R: for<'a> IntoLuaMulti<'a> + 'static,
+{
+    let state = state.clone();
+    lua.create_function(move |_, ()| Ok(get(&state.borrow())))
+}
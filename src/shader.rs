@@ -0,0 +1,361 @@
+use eframe::{
+    egui,
+    egui_wgpu::{self, wgpu},
+};
+use std::{num::NonZeroU64, path::Path, sync::Arc};
+
+/// Declarations put in front of every `--shader` file. The file only has to
+/// provide the fragment stage:
+///
+/// ```wgsl
+/// @fragment
+/// fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+///     let color = textureSample(frame_texture, frame_sampler, in.uv);
+///     let scanline = 0.85 + 0.15 * sin(in.uv.y * uniforms.size.y * 3.14159);
+///     return vec4<f32>(color.rgb * scanline, color.a) * uniforms.opacity;
+/// }
+/// ```
+///
+/// Colors sampled from `frame_texture` are premultiplied by alpha, and the
+/// result is expected to be premultiplied too.
+const PRELUDE: &str = r#"
+struct Uniforms {
+    // seconds since the overlay started
+    time: f32,
+    opacity: f32,
+    // on-screen size in points
+    size: vec2<f32>,
+};
+
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+@group(0) @binding(1) var frame_texture: texture_2d<f32>;
+@group(0) @binding(2) var frame_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// two triangles covering the area the frame is drawn into
+@vertex
+fn vs_main(@builtin(vertex_index) idx: u32) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 0.0), vec2<f32>(0.0, 1.0),
+        vec2<f32>(0.0, 1.0), vec2<f32>(1.0, 0.0), vec2<f32>(1.0, 1.0),
+    );
+    let uv = corners[idx];
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+"#;
+
+/// size of `Uniforms` above, in bytes
+const UNIFORMS_SIZE: u64 = 16;
+
+/// GPU state for a `--shader`, kept in egui's callback resources.
+struct ShaderResources {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    uniforms: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    // the frame currently uploaded, and its version
+    frame: Option<(wgpu::BindGroup, wgpu::Texture, u64)>,
+}
+
+/// Compile the WGSL file at `path` and register it with the renderer, so
+/// [`ShaderState::callback`] can draw through it. Frames are sampled with
+/// the same filtering as regular textures.
+pub fn install(
+    cc: &eframe::CreationContext<'_>,
+    path: &Path,
+    texture_options: egui::TextureOptions,
+) -> Result<(), String> {
+    let render_state = cc
+        .wgpu_render_state
+        .as_ref()
+        .ok_or("the wgpu renderer isn't running")?;
+    let source = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let device = &render_state.device;
+
+    let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("overlay shader"),
+        source: wgpu::ShaderSource::Wgsl(format!("{}\n{}", PRELUDE, source).into()),
+    });
+
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("overlay shader"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: NonZeroU64::new(UNIFORMS_SIZE),
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("overlay shader"),
+        bind_group_layouts: &[&bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("overlay shader"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &module,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &module,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format: render_state.target_format,
+                blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    });
+
+    let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("overlay shader uniforms"),
+        size: UNIFORMS_SIZE,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        label: Some("overlay shader"),
+        mag_filter: filter_mode(texture_options.magnification),
+        min_filter: filter_mode(texture_options.minification),
+        ..Default::default()
+    });
+
+    render_state
+        .renderer
+        .write()
+        .callback_resources
+        .insert(ShaderResources {
+            pipeline,
+            bind_group_layout,
+            uniforms,
+            sampler,
+            frame: None,
+        });
+    Ok(())
+}
+
+fn filter_mode(filter: egui::TextureFilter) -> wgpu::FilterMode {
+    match filter {
+        egui::TextureFilter::Nearest => wgpu::FilterMode::Nearest,
+        egui::TextureFilter::Linear => wgpu::FilterMode::Linear,
+    }
+}
+
+/// Paint callback drawing one frame through the installed shader.
+struct ShaderFrame {
+    image: Arc<egui::ColorImage>,
+    // changes whenever `image` does, so it's only uploaded once
+    version: u64,
+    time: f32,
+    opacity: f32,
+    size: egui::Vec2,
+}
+
+impl egui_wgpu::CallbackTrait for ShaderFrame {
+    fn prepare(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        _encoder: &mut wgpu::CommandEncoder,
+        resources: &mut egui_wgpu::CallbackResources,
+    ) -> Vec<wgpu::CommandBuffer> {
+        let Some(resources) = resources.get_mut::<ShaderResources>() else {
+            return Vec::new();
+        };
+
+        let mut uniforms = Vec::with_capacity(UNIFORMS_SIZE as usize);
+        for value in [self.time, self.opacity, self.size.x, self.size.y] {
+            uniforms.extend_from_slice(&value.to_ne_bytes());
+        }
+        queue.write_buffer(&resources.uniforms, 0, &uniforms);
+
+        if resources
+            .frame
+            .as_ref()
+            .is_some_and(|(_, _, version)| *version == self.version)
+        {
+            return Vec::new();
+        }
+
+        let [width, height] = self.image.size;
+        let extent = wgpu::Extent3d {
+            width: width as u32,
+            height: height as u32,
+            depth_or_array_layers: 1,
+        };
+        // reuse the texture while the frame size stays the same
+        let texture = match resources.frame.take() {
+            Some((_, texture, _)) if texture.size() == extent => texture,
+            _ => device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("overlay shader frame"),
+                size: extent,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            }),
+        };
+
+        let pixels: Vec<u8> = self
+            .image
+            .pixels
+            .iter()
+            .flat_map(|color| color.to_array())
+            .collect();
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width as u32),
+                rows_per_image: Some(height as u32),
+            },
+            extent,
+        );
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("overlay shader"),
+            layout: &resources.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: resources.uniforms.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&resources.sampler),
+                },
+            ],
+        });
+        resources.frame = Some((bind_group, texture, self.version));
+        Vec::new()
+    }
+
+    fn paint<'a>(
+        &self,
+        _info: egui::PaintCallbackInfo,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        resources: &'a egui_wgpu::CallbackResources,
+    ) {
+        let Some(resources) = resources.get::<ShaderResources>() else {
+            return;
+        };
+        let Some((bind_group, _, _)) = &resources.frame else {
+            return;
+        };
+        render_pass.set_pipeline(&resources.pipeline);
+        render_pass.set_bind_group(0, bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+    }
+}
+
+/// Per-overlay state for drawing frames through the installed shader.
+pub struct ShaderState {
+    started: std::time::Instant,
+    // the frame last converted for upload, by index
+    image: Option<(usize, Arc<egui::ColorImage>)>,
+    version: u64,
+}
+
+impl Default for ShaderState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShaderState {
+    pub fn new() -> Self {
+        Self {
+            started: std::time::Instant::now(),
+            image: None,
+            version: 0,
+        }
+    }
+
+    /// drop the converted frame after the textures changed under it
+    pub fn invalidate(&mut self) {
+        self.image = None;
+    }
+
+    /// A paint callback drawing frame `idx` into `rect`, converting it with
+    /// `image` only when it isn't the frame drawn last time.
+    pub fn callback(
+        &mut self,
+        rect: egui::Rect,
+        idx: usize,
+        opacity: f32,
+        image: impl FnOnce() -> egui::ColorImage,
+    ) -> egui::PaintCallback {
+        let image = match &self.image {
+            Some((current, image)) if *current == idx => image.clone(),
+            _ => {
+                let image = Arc::new(image());
+                self.image = Some((idx, image.clone()));
+                self.version += 1;
+                image
+            }
+        };
+
+        egui_wgpu::Callback::new_paint_callback(
+            rect,
+            ShaderFrame {
+                image,
+                version: self.version,
+                time: self.started.elapsed().as_secs_f32(),
+                opacity,
+                size: rect.size(),
+            },
+        )
+    }
+}
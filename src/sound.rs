@@ -0,0 +1,74 @@
+use clap::ValueEnum;
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink};
+use std::{io::Cursor, path::Path, sync::Arc};
+use tracing::warn;
+
+/// When the `--sound` is played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PlayOn {
+    /// once, when the animation first appears
+    Start,
+    /// when the animation first appears and every time it starts over
+    Loop,
+    /// on every trigger (`T` or `trigger` on stdin)
+    Trigger,
+}
+
+/// A sound effect kept in memory so it can be played over and over, each
+/// play overlapping the last if it's still going.
+pub struct Sound {
+    // output stops when this is dropped
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+    data: Arc<[u8]>,
+    volume: f32,
+    play_on: PlayOn,
+    started: bool,
+}
+
+impl Sound {
+    /// read the sound at `path` and open the default output device for it
+    pub fn load(path: &Path, play_on: PlayOn, volume: f32) -> Result<Self, String> {
+        let data: Arc<[u8]> = std::fs::read(path).map_err(|err| err.to_string())?.into();
+        // refuse files that won't decode now rather than at the first cue
+        Decoder::new(Cursor::new(data.clone())).map_err(|err| err.to_string())?;
+        let (stream, handle) = OutputStream::try_default().map_err(|err| err.to_string())?;
+
+        Ok(Self {
+            _stream: stream,
+            handle,
+            data,
+            volume: volume.max(0.0),
+            play_on,
+            started: false,
+        })
+    }
+
+    /// play the sound if it's meant to go off on `event`; only the first
+    /// `Start` counts
+    pub fn cue(&mut self, event: PlayOn) {
+        let wanted = match event {
+            PlayOn::Start => {
+                let first = !self.started;
+                self.started = true;
+                first && self.play_on != PlayOn::Trigger
+            }
+            PlayOn::Loop | PlayOn::Trigger => self.play_on == event,
+        };
+        if wanted {
+            if let Err(err) = self.play() {
+                warn!("failed to play sound: {}", err);
+            }
+        }
+    }
+
+    fn play(&self) -> Result<(), String> {
+        let source = Decoder::new(Cursor::new(self.data.clone())).map_err(|err| err.to_string())?;
+        let sink = Sink::try_new(&self.handle).map_err(|err| err.to_string())?;
+        sink.set_volume(self.volume);
+        sink.append(source);
+        // keep playing after the sink goes out of scope
+        sink.detach();
+        Ok(())
+    }
+}
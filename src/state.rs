@@ -0,0 +1,81 @@
+use eframe::egui::{pos2, Pos2};
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Window placement and look remembered between runs, one file per input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowState {
+    pub position: Option<Pos2>,
+    pub scale: f32,
+    pub opacity: f32,
+}
+
+/// where the state for `input` lives, e.g. `~/.local/state/overlay_app/<hash>.state`
+fn state_path(input: &Path) -> Option<PathBuf> {
+    let dir = dirs::state_dir().or_else(dirs::data_local_dir)?;
+    // relative paths would collide between folders, so hash the full path
+    let input = fs::canonicalize(input).unwrap_or_else(|_| input.to_path_buf());
+    let mut hasher = DefaultHasher::new();
+    input.hash(&mut hasher);
+
+    Some(
+        dir.join("overlay_app")
+            .join(format!("{:016x}.state", hasher.finish())),
+    )
+}
+
+/// read the saved state for `input`, if there is any
+pub fn load(input: &Path) -> Option<WindowState> {
+    let contents = fs::read_to_string(state_path(input)?).ok()?;
+
+    let mut x = None;
+    let mut y = None;
+    let mut state = WindowState {
+        position: None,
+        scale: 1.0,
+        opacity: 1.0,
+    };
+    for line in contents.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let Ok(value) = value.trim().parse::<f32>() else {
+            continue;
+        };
+        match key.trim() {
+            "x" => x = Some(value),
+            "y" => y = Some(value),
+            "scale" => state.scale = value,
+            "opacity" => state.opacity = value,
+            _ => {}
+        }
+    }
+    state.position = x.zip(y).map(|(x, y)| pos2(x, y));
+
+    Some(state)
+}
+
+/// write `state` for `input`, creating the state directory if needed
+pub fn save(input: &Path, state: &WindowState) -> io::Result<()> {
+    let path = state_path(input)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no state directory"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut contents = String::new();
+    if let Some(position) = state.position {
+        contents.push_str(&format!("x={}\ny={}\n", position.x, position.y));
+    }
+    contents.push_str(&format!(
+        "scale={}\nopacity={}\n",
+        state.scale, state.opacity
+    ));
+
+    fs::write(path, contents)
+}
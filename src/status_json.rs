@@ -0,0 +1,64 @@
+use serde::Serialize;
+use std::path::PathBuf;
+use tracing::warn;
+
+/// A machine-readable status line printed with `--status-json`, one JSON
+/// object per line with the kind of line in `event`, e.g.
+/// `{"event":"stats","fps":30.0,"frame":12,"frames":48}`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum StatusLine {
+    /// the first frame of an input is ready
+    FirstFrame {
+        input: PathBuf,
+        elapsed_ms: u128,
+    },
+    Progress {
+        loaded: usize,
+        total: usize,
+    },
+    /// every frame of the input has been decoded
+    Loaded {
+        frames: usize,
+        elapsed_ms: u128,
+    },
+    /// the input couldn't be loaded
+    LoadError {
+        input: PathBuf,
+        error: String,
+    },
+    /// once a second
+    Stats {
+        fps: f32,
+        frame: usize,
+        frames: usize,
+    },
+    /// every ten seconds, in megabytes: the process's resident memory, the
+    /// decoded frames within it, and an estimate of their textures' GPU memory
+    Memory {
+        rss_mb: Option<f64>,
+        decoded_mb: f64,
+        texture_mb: f64,
+    },
+    /// at startup and whenever any of it changes
+    State(OverlayState),
+}
+
+/// The parts of the overlay's state a `state` line reports.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OverlayState {
+    pub visible: bool,
+    pub paused: bool,
+    pub opacity: f32,
+    pub scale: f32,
+    pub input: PathBuf,
+}
+
+impl StatusLine {
+    pub fn print(&self) {
+        match serde_json::to_string(self) {
+            Ok(json) => println!("{}", json),
+            Err(err) => warn!("failed to write status line: {}", err),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{LoadingMessage, STATIC_FRAME_DELAY};
+use eframe::egui;
+use resvg::{
+    tiny_skia::{Pixmap, Transform},
+    usvg::{self, fontdb, TreeParsing, TreePostProc},
+};
+use std::{
+    path::Path,
+    sync::mpsc::{Receiver, Sender},
+};
+use tracing::{info, warn};
+
+/// Parse an SVG and rasterize it at `scale`, then keep the parsed tree around
+/// and re-rasterize whenever a new scale arrives on `rescale_receiver`.
+///
+/// The tree is not `Send`, so it lives on the loader thread for the lifetime
+/// of the overlay and every render is pushed to the UI as a stream frame.
+pub fn serve(
+    path: &Path,
+    scale: f32,
+    sender: &Sender<LoadingMessage>,
+    rescale_receiver: &Receiver<f32>,
+    ctx: &egui::Context,
+) -> Result<(), String> {
+    let data = std::fs::read(path).map_err(|err| format!("failed to read SVG: {}", err))?;
+    let mut tree = usvg::Tree::from_data(&data, &usvg::Options::default())
+        .map_err(|err| format!("failed to parse SVG: {}", err))?;
+
+    let mut fontdb = fontdb::Database::new();
+    if tree.has_text_nodes() {
+        fontdb.load_system_fonts();
+    }
+    tree.postprocess(usvg::PostProcessingSteps::default(), &fontdb);
+
+    let mut render_idx = 0;
+    let mut scale = scale;
+
+    loop {
+        let Some(mut pixmap) = tree
+            .size
+            .to_int_size()
+            .scale_by(scale)
+            .and_then(|size| Pixmap::new(pixmap.width(), pixmap.height()))
+        else {
+            // an earlier render is still on screen, so only the first one is fatal
+            if render_idx == 0 {
+                return Err(format!("SVG cannot be rendered at scale {}", scale));
+            }
+            warn!("SVG cannot be rendered at scale {}", scale);
+            return Ok(());
+        };
+        resvg::render(
+            &tree,
+            Transform::from_scale(scale, scale),
+            &mut pixmap.as_mut(),
+        );
+
+        let pixels: Vec<u8> = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|p| {
+                let c = p.demultiply();
+                [c.red(), c.green(), c.blue(), c.alpha()]
+            })
+            .collect();
+
+        info!(
+            "SVG rasterized at {}x{} (scale {})",
+            pixmap.width(),
+            pixmap.height(),
+            scale
+        );
+
+        if sender.send(LoadingMessage::RenderScale(scale)).is_err() {
+            return Ok(());
+        }
+        let message = LoadingMessage::StreamFrame(
+            render_idx,
+            pixels,
+            [pixmap.width() as usize, pixmap.height() as usize],
+            STATIC_FRAME_DELAY,
+        );
+        if sender.send(message).is_err() {
+            return Ok(());
+        }
+        ctx.request_repaint();
+        render_idx += 1;
+
+        // block until the scale changes, collapsing bursts into the latest value
+        let Ok(next) = rescale_receiver.recv() else {
+            return Ok(());
+        };
+        scale = rescale_receiver.try_iter().last().unwrap_or(next);
+    }
+}
@@ -0,0 +1,111 @@
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+use std::error::Error;
+use tracing::{info, warn};
+
+/// Keep the overlay window out of the taskbar/dock and the Alt-Tab list.
+///
+/// egui has no viewport option for this, so it's done with each platform's
+/// own window hints once the window exists: a tool window style on Windows,
+/// `_NET_WM_STATE_SKIP_TASKBAR`/`SKIP_PAGER` on X11, and an accessory
+/// activation policy on macOS. Wayland has no equivalent.
+pub fn hide_from_taskbar(window: &impl HasRawWindowHandle) {
+    let result: Result<(), Box<dyn Error>> = match window.raw_window_handle() {
+        #[cfg(windows)]
+        RawWindowHandle::Win32(handle) => windows::hide(handle.hwnd as isize),
+        #[cfg(target_os = "linux")]
+        RawWindowHandle::Xlib(handle) => x11::hide(handle.window as u32),
+        #[cfg(target_os = "linux")]
+        RawWindowHandle::Xcb(handle) => x11::hide(handle.window),
+        #[cfg(target_os = "macos")]
+        RawWindowHandle::AppKit(_) => macos::hide(),
+        handle => Err(format!("not supported for {:?} windows", handle).into()),
+    };
+
+    match result {
+        Ok(()) => info!("Hidden from the taskbar"),
+        Err(err) => warn!("failed to hide from the taskbar: {}", err),
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::Error;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongPtrW, SetWindowLongPtrW, ShowWindow, GWL_EXSTYLE, SW_HIDE, SW_SHOWNOACTIVATE,
+        WS_EX_APPWINDOW, WS_EX_TOOLWINDOW,
+    };
+
+    pub fn hide(hwnd: isize) -> Result<(), Box<dyn Error>> {
+        // tool windows are left out of the taskbar and Alt-Tab; the shell only
+        // notices the style change when the window is shown again
+        unsafe {
+            let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+            let style = (style | WS_EX_TOOLWINDOW as isize) & !(WS_EX_APPWINDOW as isize);
+            ShowWindow(hwnd, SW_HIDE);
+            SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style);
+            ShowWindow(hwnd, SW_SHOWNOACTIVATE);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use super::Error;
+    use x11rb::{
+        connection::Connection,
+        protocol::xproto::{ClientMessageEvent, ConnectionExt, EventMask},
+    };
+
+    /// `_NET_WM_STATE_ADD` from the EWMH spec
+    const NET_WM_STATE_ADD: u32 = 1;
+    /// marks the request as coming from a normal application
+    const SOURCE_APPLICATION: u32 = 1;
+
+    pub fn hide(window: u32) -> Result<(), Box<dyn Error>> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+        let root = conn.setup().roots[screen_num].root;
+        let atom = |name: &[u8]| -> Result<u32, Box<dyn Error>> {
+            Ok(conn.intern_atom(false, name)?.reply()?.atom)
+        };
+
+        // a mapped window's state has to be changed by asking the window manager
+        let event = ClientMessageEvent::new(
+            32,
+            window,
+            atom(b"_NET_WM_STATE")?,
+            [
+                NET_WM_STATE_ADD,
+                atom(b"_NET_WM_STATE_SKIP_TASKBAR")?,
+                atom(b"_NET_WM_STATE_SKIP_PAGER")?,
+                SOURCE_APPLICATION,
+                0,
+            ],
+        );
+        conn.send_event(
+            false,
+            root,
+            EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            event,
+        )?;
+        conn.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::Error;
+    use objc::{class, msg_send, runtime::Object, sel, sel_impl};
+
+    /// `NSApplicationActivationPolicyAccessory`: no dock icon or app switcher entry
+    const ACTIVATION_POLICY_ACCESSORY: i64 = 1;
+
+    pub fn hide() -> Result<(), Box<dyn Error>> {
+        unsafe {
+            let app: *mut Object = msg_send![class!(NSApplication), sharedApplication];
+            let _: bool = msg_send![app, setActivationPolicy: ACTIVATION_POLICY_ACCESSORY];
+        }
+        Ok(())
+    }
+}
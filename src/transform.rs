@@ -0,0 +1,272 @@
+use clap::ValueEnum;
+use eframe::egui::{emath::Rot2, epaint::Vertex, Color32, Mesh, Pos2, Rect, TextureId, Vec2};
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+/// Clockwise quarter turns picked with `--rotate`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Rotation {
+    #[default]
+    #[value(name = "0")]
+    None,
+    #[value(name = "90")]
+    Quarter,
+    #[value(name = "180")]
+    Half,
+    #[value(name = "270")]
+    ThreeQuarters,
+}
+
+impl Rotation {
+    pub fn degrees(self) -> usize {
+        self.quarter_turns() * 90
+    }
+
+    fn quarter_turns(self) -> usize {
+        match self {
+            Rotation::None => 0,
+            Rotation::Quarter => 1,
+            Rotation::Half => 2,
+            Rotation::ThreeQuarters => 3,
+        }
+    }
+}
+
+/// How frames are turned and mirrored when drawn, so assets don't have to be
+/// re-exported to face the right way.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Orientation {
+    pub rotation: Rotation,
+    // mirroring applies to the rotated image, i.e. to what ends up on screen
+    pub flip_h: bool,
+    pub flip_v: bool,
+    // free rotation around the center on top of the quarter turns, in radians
+    pub angle: f32,
+}
+
+impl Orientation {
+    /// on-screen size of a `size` frame once it's rotated
+    pub fn rotated_size(&self, size: Vec2) -> Vec2 {
+        if self.rotation.quarter_turns() % 2 == 1 {
+            Vec2::new(size.y, size.x)
+        } else {
+            size
+        }
+    }
+
+    /// A mesh drawing the `uv` part of `texture` into `rect`, clipped to
+    /// `mask`. Turning and mirroring swap which texture corner each screen
+    /// corner samples.
+    pub fn mesh(
+        &self,
+        texture: TextureId,
+        uv: Rect,
+        rect: Rect,
+        mask: Mask,
+        tint: Color32,
+    ) -> Mesh {
+        // corners in clockwise order, starting at the top left
+        let uvs = [
+            uv.left_top(),
+            uv.right_top(),
+            uv.right_bottom(),
+            uv.left_bottom(),
+        ];
+        let corner_uvs = [0, 1, 2, 3].map(|mut corner| {
+            if self.flip_h {
+                corner = [1, 0, 3, 2][corner];
+            }
+            if self.flip_v {
+                corner = [3, 2, 1, 0][corner];
+            }
+            // turning clockwise brings the corner counter-clockwise of it into view
+            uvs[(corner + 4 - self.rotation.quarter_turns()) % 4]
+        });
+        // texture coordinate under a point of `rect`, given as 0.0..=1.0 across it
+        let uv_at = |point: Vec2| {
+            let top = corner_uvs[0].lerp(corner_uvs[1], point.x);
+            let bottom = corner_uvs[3].lerp(corner_uvs[2], point.x);
+            top.lerp(bottom, point.y)
+        };
+
+        // a fan of triangles from the center out to the outline
+        let outline = mask.outline(rect.size());
+        let mut mesh = Mesh::with_texture(texture);
+        for point in std::iter::once(rect.size() / 2.0).chain(outline.iter().copied()) {
+            mesh.vertices.push(Vertex {
+                pos: rect.min + point,
+                uv: uv_at(point / rect.size()),
+                color: tint,
+            });
+        }
+        let len = outline.len() as u32;
+        for idx in 0..len {
+            mesh.add_triangle(0, idx + 1, (idx + 1) % len + 1);
+        }
+
+        if self.angle != 0.0 {
+            mesh.rotate(Rot2::from_angle(self.angle), rect.center());
+        }
+        mesh
+    }
+}
+
+/// A point to look at and how far to zoom in on it, for `--ken-burns`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Focus {
+    // 0.0..=1.0 across and down the frame
+    pub center: Pos2,
+    // 1.0 shows the whole frame
+    pub zoom: f32,
+}
+
+/// Slow pan and zoom over a still image, from one focus to another and back,
+/// with the turnarounds eased so it loops without a jolt.
+#[derive(Debug, Clone, Copy)]
+pub struct KenBurns {
+    // seconds for one pass from `from` to `to`
+    pub duration: f32,
+    pub from: Focus,
+    pub to: Focus,
+}
+
+impl KenBurns {
+    /// the part of `uv` on screen `secs` into the effect
+    pub fn view(&self, uv: Rect, secs: f32) -> Rect {
+        let phase = (secs / self.duration.max(0.1)).rem_euclid(2.0);
+        let t = if phase > 1.0 { 2.0 - phase } else { phase };
+        let t = t * t * (3.0 - 2.0 * t);
+
+        let zoom = self.from.zoom + (self.to.zoom - self.from.zoom) * t;
+        let focus = self.from.center.lerp(self.to.center, t);
+        let size = uv.size() / zoom.max(1.0);
+        let center = uv.min + focus.to_vec2() * uv.size();
+        // near an edge, the view stops at the frame rather than showing past it
+        let min = (center - size / 2.0).clamp(uv.min, uv.max - size);
+        Rect::from_min_size(min, size)
+    }
+}
+
+/// Shape drawn frames are clipped to, picked with `--mask`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Mask {
+    #[default]
+    None,
+    /// the largest circle that fits, centered
+    Circle,
+    /// corners rounded off with this radius, in points
+    Rounded(f32),
+}
+
+/// straight pieces each rounded corner, or the whole circle, is made of
+const CORNER_SEGMENTS: usize = 8;
+const CIRCLE_SEGMENTS: usize = 64;
+
+impl Mask {
+    /// outline of the mask inside a `size` rectangle, clockwise
+    fn outline(self, size: Vec2) -> Vec<Vec2> {
+        match self {
+            Mask::None => vec![
+                Vec2::ZERO,
+                Vec2::new(size.x, 0.0),
+                size,
+                Vec2::new(0.0, size.y),
+            ],
+            Mask::Circle => arc(size / 2.0, size.min_elem() / 2.0, 0.0, TAU, CIRCLE_SEGMENTS)
+                // the last point would repeat the first
+                .take(CIRCLE_SEGMENTS)
+                .collect(),
+            Mask::Rounded(radius) => {
+                let radius = radius.clamp(0.0, size.min_elem() / 2.0);
+                let (near, far) = (radius, size - Vec2::splat(radius));
+                // corner centers, each with the angle its arc starts at
+                [
+                    (Vec2::new(near, near), PI),
+                    (Vec2::new(far.x, near), PI * 1.5),
+                    (far, 0.0),
+                    (Vec2::new(near, far.y), FRAC_PI_2),
+                ]
+                .into_iter()
+                .flat_map(|(center, start)| arc(center, radius, start, FRAC_PI_2, CORNER_SEGMENTS))
+                .collect()
+            }
+        }
+    }
+}
+
+/// points along a circle around `center`, `sweep` radians clockwise (on
+/// screen) from `start`, split into `segments` pieces
+fn arc(
+    center: Vec2,
+    radius: f32,
+    start: f32,
+    sweep: f32,
+    segments: usize,
+) -> impl Iterator<Item = Vec2> {
+    (0..=segments).map(move |idx| {
+        let angle = start + sweep * idx as f32 / segments as f32;
+        center + Vec2::angled(angle) * radius
+    })
+}
+
+/// parse `--mask` as `none`, `circle` or `rounded:<RADIUS>`
+pub fn parse_mask(s: &str) -> Result<Mask, String> {
+    match s.trim() {
+        "none" => Ok(Mask::None),
+        "circle" => Ok(Mask::Circle),
+        other => other
+            .strip_prefix("rounded:")
+            .ok_or_else(|| format!("expected circle or rounded:<RADIUS>, got {:?}", s))?
+            .trim()
+            .parse::<f32>()
+            .map(Mask::Rounded)
+            .map_err(|err| format!("invalid corner radius in {:?}: {}", s, err)),
+    }
+}
+
+/// parse `--crop` as `X,Y,WIDTH,HEIGHT` in pixels of the source frames
+pub fn parse_crop(s: &str) -> Result<Rect, String> {
+    let values = s
+        .split(',')
+        .map(|v| {
+            v.trim()
+                .parse::<f32>()
+                .map_err(|err| format!("invalid crop value {:?}: {}", v, err))
+        })
+        .collect::<Result<Vec<f32>, String>>()?;
+    let [x, y, width, height] = values[..] else {
+        return Err(format!("expected X,Y,WIDTH,HEIGHT, got {:?}", s));
+    };
+    if x < 0.0 || y < 0.0 || width <= 0.0 || height <= 0.0 {
+        return Err(format!("crop region {:?} is empty or off the frame", s));
+    }
+    Ok(Rect::from_min_size(
+        Pos2::new(x, y),
+        Vec2::new(width, height),
+    ))
+}
+
+/// parse a `--ken-burns-from`/`--ken-burns-to` focus as `X,Y,ZOOM`, with the
+/// point given as 0.0..=1.0 across and down the frame
+pub fn parse_focus(s: &str) -> Result<Focus, String> {
+    let values = s
+        .split(',')
+        .map(|v| {
+            v.trim()
+                .parse::<f32>()
+                .map_err(|err| format!("invalid focus value {:?}: {}", v, err))
+        })
+        .collect::<Result<Vec<f32>, String>>()?;
+    let [x, y, zoom] = values[..] else {
+        return Err(format!("expected X,Y,ZOOM, got {:?}", s));
+    };
+    if !(0.0..=1.0).contains(&x) || !(0.0..=1.0).contains(&y) {
+        return Err(format!("focus point {:?} is off the frame", s));
+    }
+    if zoom < 1.0 {
+        return Err(format!("zoom in {:?} must be at least 1", s));
+    }
+    Ok(Focus {
+        center: Pos2::new(x, y),
+        zoom,
+    })
+}
@@ -0,0 +1,53 @@
+use clap::ValueEnum;
+use eframe::egui::{vec2, Rect};
+
+/// How the animation comes in (`--enter`) and goes away (`--exit`) when the
+/// overlay is shown, hidden, or closed. Slides move in from and back out
+/// through the named edge of the window.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Transition {
+    #[default]
+    Fade,
+    Zoom,
+    SlideLeft,
+    SlideRight,
+    SlideTop,
+    SlideBottom,
+}
+
+impl Transition {
+    /// Where `rect` is drawn, and at what alpha, while the overlay is
+    /// `shown` (0 gone, 1 fully in); slides leave `bounds` entirely at 0.
+    pub fn apply(self, shown: f32, rect: Rect, bounds: Rect) -> (Rect, f32) {
+        if shown >= 1.0 {
+            return (rect, 1.0);
+        }
+        // fast at the visible end, so it settles in gently and leaves with a
+        // slow start whichever way it's going
+        let hidden = (1.0 - shown).powi(3);
+
+        match self {
+            Transition::Fade => (rect, shown),
+            Transition::Zoom => (
+                Rect::from_center_size(rect.center(), rect.size() * (1.0 - hidden)),
+                1.0,
+            ),
+            Transition::SlideLeft => (
+                rect.translate(vec2(-(rect.right() - bounds.left()) * hidden, 0.0)),
+                1.0,
+            ),
+            Transition::SlideRight => (
+                rect.translate(vec2((bounds.right() - rect.left()) * hidden, 0.0)),
+                1.0,
+            ),
+            Transition::SlideTop => (
+                rect.translate(vec2(0.0, -(rect.bottom() - bounds.top()) * hidden)),
+                1.0,
+            ),
+            Transition::SlideBottom => (
+                rect.translate(vec2(0.0, (bounds.bottom() - rect.top()) * hidden)),
+                1.0,
+            ),
+        }
+    }
+}
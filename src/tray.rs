@@ -0,0 +1,135 @@
+use eframe::egui;
+use std::sync::mpsc::{channel, Receiver};
+#[cfg(target_os = "linux")]
+use tracing::warn;
+use tray_icon::{
+    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu},
+    Icon, TrayIcon, TrayIconBuilder,
+};
+
+/// opacity levels offered in the tray menu, in percent
+const OPACITY_PRESETS: [u8; 4] = [25, 50, 75, 100];
+/// side length of the generated tray icon
+const ICON_SIZE: u32 = 32;
+
+/// something picked from the tray menu
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrayCommand {
+    ToggleVisible,
+    TogglePause,
+    Opacity(f32),
+    Quit,
+}
+
+impl TrayCommand {
+    /// map a menu item id back to the command it stands for
+    fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "show_hide" => Some(Self::ToggleVisible),
+            "pause" => Some(Self::TogglePause),
+            "quit" => Some(Self::Quit),
+            _ => id
+                .strip_prefix("opacity_")
+                .and_then(|percent| percent.parse::<u8>().ok())
+                .map(|percent| Self::Opacity(percent as f32 / 100.0)),
+        }
+    }
+}
+
+/// The tray icon and the channel its menu picks arrive on.
+pub struct Tray {
+    pub commands: Receiver<TrayCommand>,
+    // dropping the icon removes it from the tray; on Linux it lives on the GTK thread
+    #[cfg(not(target_os = "linux"))]
+    _icon: TrayIcon,
+}
+
+impl Tray {
+    /// Put an icon with a show/hide, pause, opacity and quit menu in the system
+    /// tray. Menu picks wake the UI up through `ctx`.
+    pub fn new(ctx: &egui::Context) -> Result<Self, String> {
+        let (sender, commands) = channel();
+        let ctx = ctx.clone();
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            if let Some(command) = TrayCommand::from_id(&event.id.0) {
+                let _ = sender.send(command);
+                ctx.request_repaint();
+            }
+        }));
+
+        // GTK objects can't leave the thread running the GTK main loop, so on
+        // Linux the whole tray is built and kept on a thread of its own
+        #[cfg(target_os = "linux")]
+        {
+            std::thread::spawn(|| {
+                if let Err(err) = gtk::init() {
+                    warn!("failed to initialize GTK for the tray icon: {}", err);
+                    return;
+                }
+                match build_icon() {
+                    Ok(_icon) => gtk::main(),
+                    Err(err) => warn!("failed to create tray icon: {}", err),
+                }
+            });
+            Ok(Self { commands })
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let icon = build_icon()?;
+            Ok(Self {
+                commands,
+                _icon: icon,
+            })
+        }
+    }
+}
+
+fn build_icon() -> Result<TrayIcon, String> {
+    let menu = Menu::new();
+    let opacity = Submenu::new("Opacity", true);
+    for percent in OPACITY_PRESETS {
+        opacity
+            .append(&MenuItem::with_id(
+                format!("opacity_{}", percent),
+                format!("{}%", percent),
+                true,
+                None,
+            ))
+            .map_err(|err| err.to_string())?;
+    }
+    menu.append_items(&[
+        &MenuItem::with_id("show_hide", "Show/Hide", true, None),
+        &MenuItem::with_id("pause", "Pause/Resume", true, None),
+        &opacity,
+        &PredefinedMenuItem::separator(),
+        &MenuItem::with_id("quit", "Quit", true, None),
+    ])
+    .map_err(|err| err.to_string())?;
+
+    TrayIconBuilder::new()
+        .with_menu(Box::new(menu))
+        .with_tooltip("GIF overlay")
+        .with_icon(icon_image()?)
+        .build()
+        .map_err(|err| err.to_string())
+}
+
+/// a plain filled circle, so the tray needs no bundled image file
+fn icon_image() -> Result<Icon, String> {
+    let center = ICON_SIZE as f32 / 2.0;
+    let rgba = (0..ICON_SIZE * ICON_SIZE)
+        .flat_map(|i| {
+            let x = (i % ICON_SIZE) as f32 + 0.5 - center;
+            let y = (i / ICON_SIZE) as f32 + 0.5 - center;
+            let alpha = if x * x + y * y <= center * center {
+                255
+            } else {
+                0
+            };
+            [0xf7, 0x4c, 0x00, alpha]
+        })
+        .collect();
+
+    Icon::from_rgba(rgba, ICON_SIZE, ICON_SIZE).map_err(|err| err.to_string())
+}
@@ -0,0 +1,282 @@
+use crate::control::{self, Command};
+use eframe::egui;
+use rand::Rng;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    path::{Path, PathBuf},
+    sync::{mpsc::Sender, Arc},
+    thread,
+    time::Duration,
+};
+use tracing::{info, warn};
+use tungstenite::Message;
+
+const CHAT_ADDRESS: &str = "irc.chat.twitch.tv:6667";
+const EVENTSUB_URL: &str = "wss://eventsub.wss.twitch.tv/ws";
+const HELIX_URL: &str = "https://api.twitch.tv/helix";
+
+/// how long to wait before reconnecting after Twitch drops the connection
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+/// A `--twitch` file: the channel to watch and the animation played as an
+/// alert for each kind of event.
+///
+/// ```toml
+/// channel = "mychannel"
+/// # follows and channel point redemptions need a user access token for the
+/// # channel, with the moderator:read:followers and
+/// # channel:read:redemptions scopes, and the client id it was made for
+/// client_id = "..."
+/// token = "..."
+///
+/// [alerts]
+/// follow = "follow.gif"
+/// sub = "sub.gif"
+/// gift = "gift.gif"
+/// raid = "raid.gif"
+/// cheer = "cheer.gif"
+/// "redeem:Hydrate" = "water.gif"
+/// redeem = "redeem.gif"
+/// "!hype" = "hype.gif"
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    channel: String,
+    client_id: Option<String>,
+    token: Option<String>,
+    #[serde(default)]
+    alerts: HashMap<String, PathBuf>,
+}
+
+/// read and parse a `--twitch` file, resolving alerts against its folder
+pub fn load(path: &Path) -> io::Result<Config> {
+    let contents = std::fs::read_to_string(path)?;
+    let mut config: Config =
+        toml::from_str(&contents).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    config.channel = config.channel.trim_start_matches('#').to_lowercase();
+
+    let base = path.parent().unwrap_or(Path::new(""));
+    config.alerts = config
+        .alerts
+        .into_iter()
+        .map(|(event, file)| {
+            // chat commands are matched whatever their case
+            let event = if event.starts_with('!') {
+                event.to_lowercase()
+            } else {
+                event
+            };
+            let file = if file.is_relative() && !crate::is_stream(&file) {
+                base.join(file)
+            } else {
+                file
+            };
+            (event, file)
+        })
+        .collect();
+    Ok(config)
+}
+
+impl Config {
+    /// queue the alert for the first of `events` that has one
+    fn alert(&self, events: &[&str], commands: &Sender<Command>, ctx: &egui::Context) {
+        let Some((event, file)) = events
+            .iter()
+            .find_map(|event| self.alerts.get(*event).map(|file| (event, file)))
+        else {
+            return;
+        };
+        info!("Twitch {}: playing {}", event, file.display());
+        let _ = control::send(commands, ctx, Command::Alert(file.clone(), None));
+    }
+}
+
+/// Watch the channel in `config` in the background: chat for subs, gifts,
+/// raids, cheers, and commands, and EventSub for follows and channel point
+/// redemptions if there's a token for it.
+pub fn connect(config: Config, commands: Sender<Command>, ctx: &egui::Context) {
+    info!(
+        "Watching twitch.tv/{} for {} kinds of alert",
+        config.channel,
+        config.alerts.len()
+    );
+    let config = Arc::new(config);
+
+    let chat_config = config.clone();
+    let chat_commands = commands.clone();
+    let chat_ctx = ctx.clone();
+    thread::spawn(move || loop {
+        if let Err(err) = watch_chat(&chat_config, &chat_commands, &chat_ctx) {
+            warn!("Twitch chat connection failed: {}", err);
+        }
+        thread::sleep(RECONNECT_DELAY);
+    });
+
+    let (Some(client_id), Some(token)) = (config.client_id.clone(), config.token.clone()) else {
+        warn!("No Twitch client_id and token given, follows and redemptions won't be seen");
+        return;
+    };
+    let ctx = ctx.clone();
+    thread::spawn(move || loop {
+        // a session Twitch asks to move is picked up again right away
+        if let Err(err) = watch_events(&config, &client_id, &token, &commands, &ctx) {
+            warn!("Twitch EventSub connection failed: {}", err);
+            thread::sleep(RECONNECT_DELAY);
+        }
+    });
+}
+
+/// read the channel's chat anonymously until the connection drops
+fn watch_chat(config: &Config, commands: &Sender<Command>, ctx: &egui::Context) -> io::Result<()> {
+    let stream = TcpStream::connect(CHAT_ADDRESS)?;
+    let mut writer = stream.try_clone()?;
+    // `justinfan` nicks can read chat without logging in
+    write!(
+        writer,
+        "CAP REQ :twitch.tv/tags twitch.tv/commands\r\nNICK justinfan{}\r\nJOIN #{}\r\n",
+        rand::thread_rng().gen_range(10000..100000),
+        config.channel
+    )?;
+
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if let Some(server) = line.strip_prefix("PING ") {
+            write!(writer, "PONG {}\r\n", server)?;
+        } else if let Some(event) = chat_event(&line) {
+            config.alert(&[event.as_str()], commands, ctx);
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "disconnected by Twitch",
+    ))
+}
+
+/// The alert a chat line stands for, if any. Lines look like
+/// `@tag=value;... :prefix COMMAND #channel :message`.
+fn chat_event(line: &str) -> Option<String> {
+    let (tags, rest) = match line.strip_prefix('@') {
+        Some(tagged) => tagged.split_once(' ')?,
+        None => ("", line),
+    };
+    let rest = match rest.strip_prefix(':') {
+        Some(prefixed) => prefixed.split_once(' ')?.1,
+        None => rest,
+    };
+    let (command, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+    let message = rest.split_once(" :").map_or("", |(_, message)| message);
+    let tag = |name: &str| {
+        tags.split(';')
+            .filter_map(|tag| tag.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    };
+
+    match command {
+        "USERNOTICE" => match tag("msg-id")? {
+            "sub" | "resub" => Some("sub".to_string()),
+            // a gift to the community also comes as one notice per sub
+            "submysterygift" => Some("gift".to_string()),
+            "subgift" if tag("msg-param-community-gift-id").is_none() => Some("gift".to_string()),
+            "raid" => Some("raid".to_string()),
+            _ => None,
+        },
+        "PRIVMSG" if tag("bits").is_some() => Some("cheer".to_string()),
+        "PRIVMSG" if message.starts_with('!') => {
+            message.split_whitespace().next().map(str::to_lowercase)
+        }
+        _ => None,
+    }
+}
+
+/// Follow the channel's EventSub events until Twitch ends the session,
+/// returning `Ok` if it asked to reconnect.
+fn watch_events(
+    config: &Config,
+    client_id: &str,
+    token: &str,
+    commands: &Sender<Command>,
+    ctx: &egui::Context,
+) -> Result<(), String> {
+    let helix = |request: ureq::Request| {
+        request
+            .set("Client-Id", client_id)
+            .set("Authorization", &format!("Bearer {}", token))
+    };
+    let users = helix(ureq::get(&format!("{}/users", HELIX_URL)))
+        .query("login", &config.channel)
+        .call()
+        .map_err(|err| err.to_string())?
+        .into_string()
+        .map_err(|err| err.to_string())?;
+    let users: serde_json::Value = serde_json::from_str(&users).map_err(|err| err.to_string())?;
+    let broadcaster = users["data"][0]["id"]
+        .as_str()
+        .ok_or_else(|| format!("no Twitch channel named {:?}", config.channel))?
+        .to_string();
+
+    let (mut socket, _) = tungstenite::connect(EVENTSUB_URL).map_err(|err| err.to_string())?;
+    loop {
+        let text = match socket.read().map_err(|err| err.to_string())? {
+            Message::Text(text) => text,
+            Message::Close(_) => return Err("closed by Twitch".to_string()),
+            _ => continue,
+        };
+        let message: serde_json::Value =
+            serde_json::from_str(&text).map_err(|err| err.to_string())?;
+
+        match message["metadata"]["message_type"].as_str() {
+            Some("session_welcome") => {
+                let session = message["payload"]["session"]["id"].as_str().unwrap_or("");
+                let subscriptions = [
+                    (
+                        "channel.follow",
+                        "2",
+                        serde_json::json!({
+                            "broadcaster_user_id": broadcaster,
+                            "moderator_user_id": broadcaster,
+                        }),
+                    ),
+                    (
+                        "channel.channel_points_custom_reward_redemption.add",
+                        "1",
+                        serde_json::json!({ "broadcaster_user_id": broadcaster }),
+                    ),
+                ];
+                for (kind, version, condition) in subscriptions {
+                    let body = serde_json::json!({
+                        "type": kind,
+                        "version": version,
+                        "condition": condition,
+                        "transport": { "method": "websocket", "session_id": session },
+                    });
+                    // a missing scope only costs that one kind of event
+                    if let Err(err) =
+                        helix(ureq::post(&format!("{}/eventsub/subscriptions", HELIX_URL)))
+                            .set("Content-Type", "application/json")
+                            .send_string(&body.to_string())
+                    {
+                        warn!("failed to subscribe to Twitch {}: {}", kind, err);
+                    }
+                }
+            }
+            Some("notification") => {
+                let event = &message["payload"]["event"];
+                match message["payload"]["subscription"]["type"].as_str() {
+                    Some("channel.follow") => config.alert(&["follow"], commands, ctx),
+                    Some("channel.channel_points_custom_reward_redemption.add") => {
+                        let reward = event["reward"]["title"].as_str().unwrap_or("");
+                        let redeem = format!("redeem:{}", reward);
+                        config.alert(&[redeem.as_str(), "redeem"], commands, ctx);
+                    }
+                    _ => {}
+                }
+            }
+            Some("session_reconnect") => return Ok(()),
+            _ => {}
+        }
+    }
+}
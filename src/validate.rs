@@ -0,0 +1,182 @@
+use crate::{is_stream, read_animation_dir, read_playlist, video, Args, InputFormat};
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::Path,
+};
+use tracing::warn;
+
+/// enough of a file to recognize its format by
+pub const HEADER_LEN: u64 = 64;
+
+/// the largest frame side the overlay will upload as a texture
+const MAX_FRAME_SIDE: u32 = 16384;
+
+/// Check the inputs `args` names before any window opens, returning what's
+/// wrong with the first bad one and how to fix it.
+pub fn args(args: &Args) -> Result<(), String> {
+    #[cfg(not(feature = "shader"))]
+    if args.shader.is_some() {
+        return Err("`--shader` needs the overlay built with `--features shader`".to_string());
+    }
+
+    if let Some(end_frame) = args.end_frame.filter(|&end| end < args.start_frame) {
+        return Err(format!(
+            "`--start-frame {}` comes after `--end-frame {}`; the range is inclusive, \
+             so pass a start at or before the end",
+            args.start_frame, end_frame
+        ));
+    }
+
+    let sprite_sheet = args.columns.is_some();
+    for path in args.input.iter().chain(&args.idle).chain(&args.active) {
+        input(path, sprite_sheet)?;
+    }
+
+    // inputs pulled in by `--playlist` and `--dir`
+    let mut listed = Vec::new();
+    if let Some(path) = &args.playlist {
+        let entries = read_playlist(path).map_err(|err| unreadable(path, err))?;
+        if entries.is_empty() {
+            return Err(format!(
+                "playlist {} doesn't list any inputs",
+                path.display()
+            ));
+        }
+        listed.extend(entries);
+    }
+
+    if let Some(dir) = &args.dir {
+        let entries = read_animation_dir(dir).map_err(|err| unreadable(dir, err))?;
+        if entries.is_empty() {
+            return Err(format!(
+                "{} has no animations in it (looked for {})",
+                dir.display(),
+                crate::ANIMATION_EXTENSIONS.join(", ")
+            ));
+        }
+        listed.extend(entries);
+    }
+
+    // the rest of the playlist can still play around a bad entry, so those
+    // are only warnings, unless there's nothing left to play at all
+    let mut playable = args.input.len();
+    for entry in &listed {
+        match input(entry, sprite_sheet) {
+            Ok(_) => playable += 1,
+            Err(err) => warn!("playlist entry will be skipped: {}", err),
+        }
+    }
+    if !listed.is_empty() && playable == 0 && args.particles.is_none() {
+        return Err("none of the playlist entries can play".to_string());
+    }
+    Ok(())
+}
+
+/// Check that `path` exists and is something the overlay can decode,
+/// returning its format if it's a single file. `sprite_sheet` lifts the
+/// frame size limit, as the sheet gets sliced up.
+pub fn input(path: &Path, sprite_sheet: bool) -> Result<Option<InputFormat>, String> {
+    // stdin and URLs can't be looked at without using them up
+    if is_stream(path) {
+        return Ok(None);
+    }
+
+    let metadata = fs::metadata(path).map_err(|err| unreadable(path, err))?;
+    if metadata.is_dir() {
+        return image_folder(path).map(|()| None);
+    }
+    if metadata.len() == 0 {
+        return Err(format!("{} is empty", path.display()));
+    }
+
+    let mut header = Vec::new();
+    File::open(path)
+        .and_then(|file| file.take(HEADER_LEN).read_to_end(&mut header))
+        .map_err(|err| unreadable(path, err))?;
+    let Some(format) = InputFormat::recognize(path, &header) else {
+        return Err(format!(
+            "{} isn't something the overlay can play; pass a GIF, WebP, APNG, AVIF, SVG, \
+             Lottie JSON, video, or still image",
+            path.display()
+        ));
+    };
+    if let Some(named) = InputFormat::from_extension(path).filter(|named| *named != format) {
+        warn!(
+            "{} is named like a {:?} file but is {:?}, playing it as {:?}",
+            path.display(),
+            named,
+            format,
+            format
+        );
+    }
+
+    match format {
+        InputFormat::Video | InputFormat::Avif if !video::tools_available() => Err(format!(
+            "{} is decoded through ffmpeg, which isn't installed; install it and make sure \
+             `ffmpeg` and `ffprobe` are on the PATH",
+            path.display()
+        )),
+        #[cfg(not(feature = "lottie"))]
+        InputFormat::Lottie => Err(format!(
+            "{} is a Lottie animation, which needs the overlay built with `--features lottie`",
+            path.display()
+        )),
+        InputFormat::Gif | InputFormat::WebP | InputFormat::Png | InputFormat::Image => {
+            let (width, height) = image::io::Reader::open(path)
+                .and_then(|reader| reader.with_guessed_format())
+                .map_err(|err| unreadable(path, err))?
+                .into_dimensions()
+                .map_err(|err| format!("{} looks damaged: {}", path.display(), err))?;
+            if width == 0 || height == 0 {
+                return Err(format!("{} is {}x{} pixels", path.display(), width, height));
+            }
+            if !sprite_sheet && width.max(height) > MAX_FRAME_SIDE {
+                return Err(format!(
+                    "{} is {}x{} pixels, past the {} pixel limit for a frame; shrink it first",
+                    path.display(),
+                    width,
+                    height,
+                    MAX_FRAME_SIDE
+                ));
+            }
+            Ok(Some(format))
+        }
+        _ => Ok(Some(format)),
+    }
+}
+
+/// a folder given as an input plays the numbered images inside it
+fn image_folder(dir: &Path) -> Result<(), String> {
+    let has_images = fs::read_dir(dir)
+        .map_err(|err| unreadable(dir, err))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .any(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .and_then(image::ImageFormat::from_extension)
+                .is_some()
+        });
+    if has_images {
+        return Ok(());
+    }
+    if read_animation_dir(dir).is_ok_and(|entries| !entries.is_empty()) {
+        return Err(format!(
+            "{} has no image frames in it; to pick from a folder of animations, use `--dir {}`",
+            dir.display(),
+            dir.display()
+        ));
+    }
+    Err(format!("{} has no images in it", dir.display()))
+}
+
+/// describe why `path` couldn't be opened
+fn unreadable(path: &Path, err: io::Error) -> String {
+    match err.kind() {
+        io::ErrorKind::NotFound => format!("{} not found", path.display()),
+        io::ErrorKind::PermissionDenied => {
+            format!("{} can't be read: permission denied", path.display())
+        }
+        _ => format!("{} can't be read: {}", path.display(), err),
+    }
+}
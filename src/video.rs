@@ -0,0 +1,338 @@
+use crate::{budget, deliver, wait_while_held, Decoder, LoaderOptions, LoadingMessage};
+use eframe::egui;
+use std::{
+    io::{self, Read},
+    path::Path,
+    process::{Command, Stdio},
+    sync::mpsc::Sender,
+    thread,
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+/// fallback when ffprobe reports no usable frame rate
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(33);
+
+struct VideoInfo {
+    width: usize,
+    height: usize,
+    frame_delay: Duration,
+}
+
+/// whether `ffmpeg` and `ffprobe`, which video and AVIF inputs are decoded
+/// through, can be run
+pub fn tools_available() -> bool {
+    ["ffmpeg", "ffprobe"].iter().all(|tool| {
+        Command::new(tool)
+            .arg("-version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    })
+}
+
+/// query the first video stream's dimensions and frame rate through `ffprobe`
+fn probe(path: &Path) -> io::Result<VideoInfo> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "stream=width,height,r_frame_rate",
+            "-of",
+            "csv=p=0",
+        ])
+        .arg(path)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut fields = stdout.trim().split(',');
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "unexpected ffprobe output");
+
+    let width = fields
+        .next()
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(invalid)?;
+    let height = fields
+        .next()
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(invalid)?;
+    let frame_delay = fields
+        .next()
+        .and_then(parse_frame_rate)
+        .map(|fps| Duration::from_secs_f64(1.0 / fps))
+        .unwrap_or(DEFAULT_FRAME_DELAY);
+
+    Ok(VideoInfo {
+        width,
+        height,
+        frame_delay,
+    })
+}
+
+/// parse ffprobe's rational frame rate, e.g. `30000/1001`
+fn parse_frame_rate(value: &str) -> Option<f64> {
+    let (num, den) = value.split_once('/').unwrap_or((value, "1"));
+    let fps = num.parse::<f64>().ok()? / den.parse::<f64>().ok()?;
+    (fps.is_finite() && fps > 0.0).then_some(fps)
+}
+
+/// per-frame display durations of the first video stream, in decode order,
+/// keeping the 0s ones GIFs often have
+fn probe_frame_durations(path: &Path) -> io::Result<Vec<Option<Duration>>> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "frame=duration_time",
+            "-of",
+            "csv=p=0",
+        ])
+        .arg(path)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| {
+            line.trim()
+                .trim_end_matches(',')
+                .parse::<f64>()
+                .ok()
+                .filter(|secs| secs.is_finite() && *secs >= 0.0)
+                .map(Duration::from_secs_f64)
+        })
+        .collect())
+}
+
+/// ffmpeg arguments that scale frames to `size`; bilinear is plenty for
+/// shrinking and much faster than the default bicubic
+fn scale_args(size: [usize; 2]) -> Vec<String> {
+    vec![
+        "-vf".to_string(),
+        format!("scale={}:{}:flags=bilinear", size[0], size[1]),
+    ]
+}
+
+/// ffmpeg arguments that go before the input: with `--decoder ffmpeg`, decode
+/// on the GPU where it can, falling back to software where it can't
+fn input_args(options: &LoaderOptions) -> &'static [&'static str] {
+    match options.decoder {
+        Decoder::Builtin => &[],
+        Decoder::Ffmpeg => &["-hwaccel", "auto"],
+    }
+}
+
+/// Decode every frame of an image sequence container (e.g. animated AVIF, or
+/// a GIF with `--decoder ffmpeg`) up front, keeping the per-frame durations
+/// stored in the file, 0ms ones included unless `--force-delay` replaces
+/// them, and having ffmpeg shrink the frames to the size they're shown at,
+/// or further if they'd take up more than `--max-memory`.
+pub fn decode_sequence(
+    path: &Path,
+    options: LoaderOptions,
+    sender: &Sender<LoadingMessage>,
+    ctx: &egui::Context,
+) -> Result<(), String> {
+    let info = probe(path).map_err(|err| format!("failed to probe image sequence: {}", err))?;
+    let durations = probe_frame_durations(path).unwrap_or_default();
+    info!(
+        "Image sequence: {}x{}, {} frames",
+        info.width,
+        info.height,
+        durations.len()
+    );
+
+    let native = [info.width, info.height];
+    let shown = options.downscale_factor(native, ctx);
+    let budget = options
+        .max_memory
+        .and_then(|budget| budget::shrink_factor(budget, native, durations.len().max(1)));
+    let shrink = shown.into_iter().chain(budget).reduce(f32::min);
+    let [width, height] = shrink.map_or(native, |factor| budget::shrunk_size(native, factor));
+    let mut scale_filter = Vec::new();
+    if let Some(factor) = shrink {
+        if budget == shrink {
+            info!(
+                "Frames won't fit in --max-memory, shrinking them to {}x{}",
+                width, height
+            );
+        } else {
+            info!(
+                "Frames are shown smaller, shrinking them to {}x{}",
+                width, height
+            );
+        }
+        scale_filter = scale_args([width, height]);
+        deliver(sender, LoadingMessage::RenderScale(factor))?;
+    }
+
+    let mut child = Command::new("ffmpeg")
+        .args(["-v", "error"])
+        .args(input_args(&options))
+        .arg("-i")
+        .arg(path)
+        .args(scale_filter)
+        .args([
+            "-vsync",
+            "passthrough",
+            "-f",
+            "rawvideo",
+            "-pix_fmt",
+            "rgba",
+            "-",
+        ])
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("failed to run ffmpeg: {}", err))?;
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| "ffmpeg stdout not captured".to_string())?;
+
+    let mut pixels = vec![0; width * height * 4];
+    let mut frame_count = 0;
+
+    while stdout.read_exact(&mut pixels).is_ok() {
+        let delay = durations
+            .get(frame_count)
+            .copied()
+            .flatten()
+            .unwrap_or(info.frame_delay);
+        let delay = match (delay, options.zero_delay) {
+            (delay, Some(zero_delay)) if delay.is_zero() => zero_delay,
+            (delay, _) => delay,
+        };
+
+        let message =
+            LoadingMessage::FrameReady(frame_count, pixels.clone(), [width, height], delay);
+        if let Err(err) = deliver(sender, message) {
+            let _ = child.kill();
+            return Err(err);
+        }
+        frame_count += 1;
+    }
+
+    let _ = child.wait();
+
+    if frame_count == 0 {
+        return Err("ffmpeg produced no frames".to_string());
+    }
+    deliver(sender, LoadingMessage::LoadingComplete(frame_count))
+}
+
+/// Decode `path` with `ffmpeg` and push frames to the UI at playback speed,
+/// restarting the decoder at the end of the clip so the video loops. Frame
+/// indices start from 0 again with every loop, which is how the UI counts
+/// them.
+///
+/// Only the most recent frame is kept by the UI, so memory use stays constant
+/// regardless of clip length. Videos shown smaller than they are get shrunk
+/// by ffmpeg.
+pub fn stream(
+    path: &Path,
+    options: LoaderOptions,
+    sender: &Sender<LoadingMessage>,
+    ctx: &egui::Context,
+) -> Result<(), String> {
+    let info = probe(path).map_err(|err| format!("failed to probe video stream: {}", err))?;
+    info!(
+        "Video stream: {}x{} @ {:.2?} per frame",
+        info.width, info.height, info.frame_delay
+    );
+
+    let native = [info.width, info.height];
+    let shrink = options.downscale_factor(native, ctx);
+    let [width, height] = shrink.map_or(native, |factor| budget::shrunk_size(native, factor));
+    let mut scale_filter = Vec::new();
+    if let Some(factor) = shrink {
+        info!(
+            "Video is shown smaller, shrinking it to {}x{}",
+            width, height
+        );
+        scale_filter = scale_args([width, height]);
+        deliver(sender, LoadingMessage::RenderScale(factor))?;
+    }
+
+    let frame_len = width * height * 4;
+    let mut played = false;
+
+    loop {
+        let mut child = Command::new("ffmpeg")
+            .args(["-v", "error"])
+            .args(input_args(&options))
+            .arg("-i")
+            .arg(path)
+            .args(&scale_filter)
+            .args(["-f", "rawvideo", "-pix_fmt", "rgba", "-"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("failed to run ffmpeg: {}", err))?;
+        let mut stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| "ffmpeg stdout not captured".to_string())?;
+
+        let mut next_deadline = Instant::now();
+        let mut pixels = vec![0; frame_len];
+        let mut frame_idx = 0;
+
+        while stdout.read_exact(&mut pixels).is_ok() {
+            // ffmpeg waits on the pipe while the overlay is hidden or paused,
+            // and the clip picks up where it left off
+            if wait_while_held() {
+                next_deadline = Instant::now();
+            }
+            let now = Instant::now();
+            if next_deadline > now {
+                thread::sleep(next_deadline - now);
+            }
+            next_deadline += info.frame_delay;
+
+            let message = LoadingMessage::StreamFrame(
+                frame_idx,
+                pixels.clone(),
+                [width, height],
+                info.frame_delay,
+            );
+            if sender.send(message).is_err() {
+                // the window has been closed
+                let _ = child.kill();
+                return Ok(());
+            }
+            ctx.request_repaint();
+            frame_idx += 1;
+        }
+
+        let _ = child.wait();
+
+        if frame_idx == 0 {
+            // a clip that played before is already on screen
+            if !played {
+                return Err("ffmpeg produced no frames".to_string());
+            }
+            warn!("ffmpeg produced no frames, stopping video playback");
+            return Ok(());
+        }
+        played = true;
+    }
+}
@@ -0,0 +1,103 @@
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// A `--webhook-rules` file: which alert to play for which JSON payload
+/// POSTed to `/webhook`.
+///
+/// ```toml
+/// [[rule]]
+/// source = "kofi"                  # only payloads sent to /webhook/kofi
+/// when = { type = "Donation" }     # fields that must match
+/// alert = "coffee.gif"
+/// caption = "{from_name} bought a coffee! {message}"
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Rules {
+    #[serde(default, rename = "rule")]
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Rule {
+    source: Option<String>,
+    // dotted paths into the payload, e.g. `sender.login`, and the text their
+    // value has to have
+    #[serde(default)]
+    when: HashMap<String, String>,
+    alert: PathBuf,
+    caption: Option<String>,
+}
+
+/// read and parse a rules file, resolving alerts against its folder
+pub fn load(path: &Path) -> io::Result<Rules> {
+    let contents = std::fs::read_to_string(path)?;
+    let mut rules: Rules =
+        toml::from_str(&contents).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let base = path.parent().unwrap_or(Path::new(""));
+    for rule in &mut rules.rules {
+        if rule.alert.is_relative() && !crate::is_stream(&rule.alert) {
+            rule.alert = base.join(&rule.alert);
+        }
+    }
+    Ok(rules)
+}
+
+impl Rules {
+    /// The alert and caption of the first rule matching `payload`, sent to
+    /// `/webhook/<source>` or plain `/webhook`.
+    pub fn alert(
+        &self,
+        source: Option<&str>,
+        payload: &serde_json::Value,
+    ) -> Option<(PathBuf, Option<String>)> {
+        let rule = self.rules.iter().find(|rule| {
+            (rule.source.is_none() || rule.source.as_deref() == source)
+                && rule.when.iter().all(|(path, wanted)| {
+                    field(payload, path).is_some_and(|value| value == *wanted)
+                })
+        })?;
+        let caption = rule
+            .caption
+            .as_deref()
+            .map(|template| fill_template(template, payload));
+        Some((rule.alert.clone(), caption))
+    }
+}
+
+/// the payload's value at a dotted `path` as text, e.g. `sender.login` or
+/// `commits.0.message`
+fn field(payload: &serde_json::Value, path: &str) -> Option<String> {
+    let value = path
+        .split('.')
+        .try_fold(payload, |value, key| match value {
+            serde_json::Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+            _ => value.get(key),
+        })?;
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(text) => Some(text.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// replace each `{path}` in `template` with that field of the payload,
+/// leaving it empty if the payload doesn't have it
+fn fill_template(template: &str, payload: &serde_json::Value) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        filled.push_str(&rest[..start]);
+        let path = rest[start + 1..start + end].trim();
+        filled.push_str(&field(payload, path).unwrap_or_default());
+        rest = &rest[start + end + 1..];
+    }
+    filled.push_str(rest);
+    filled
+}
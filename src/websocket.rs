@@ -0,0 +1,52 @@
+use crate::control::{self, Command};
+use eframe::egui;
+use std::{
+    io,
+    net::{TcpListener, TcpStream},
+    sync::mpsc::Sender,
+    thread,
+};
+use tracing::{info, warn};
+use tungstenite::Message;
+
+/// Accept WebSocket connections on `127.0.0.1:port`, e.g. from a web
+/// dashboard or chat bot, taking the same JSON commands as `--ipc` and
+/// answering each text message with a JSON reply.
+pub fn serve(port: u16, commands: Sender<Command>, ctx: &egui::Context) -> io::Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    info!("WebSocket listening on ws://127.0.0.1:{}", port);
+
+    let ctx = ctx.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming().filter_map(Result::ok) {
+            let commands = commands.clone();
+            let ctx = ctx.clone();
+            thread::spawn(move || serve_client(stream, &commands, &ctx));
+        }
+    });
+    Ok(())
+}
+
+fn serve_client(stream: TcpStream, commands: &Sender<Command>, ctx: &egui::Context) {
+    // events are small and should go out right away
+    let _ = stream.set_nodelay(true);
+    let mut socket = match tungstenite::accept(stream) {
+        Ok(socket) => socket,
+        Err(err) => {
+            warn!("WebSocket handshake failed: {}", err);
+            return;
+        }
+    };
+
+    loop {
+        let reply = match socket.read() {
+            Ok(Message::Text(text)) => control::handle_request(&text, commands, ctx),
+            Ok(Message::Close(_)) | Err(_) => break,
+            // pings are answered by tungstenite itself
+            Ok(_) => continue,
+        };
+        if socket.send(Message::Text(reply)).is_err() {
+            break;
+        }
+    }
+}